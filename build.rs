@@ -0,0 +1,13 @@
+//! Compiles the gRPC service definition when the `grpc` feature is enabled.
+//!
+//! The proto file is parsed with `protox`, so no `protoc` installation is needed.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/foodscraper.proto");
+    #[cfg(feature = "grpc")]
+    {
+        let descriptors = protox::compile(["proto/foodscraper.proto"], ["proto"])?;
+        tonic_build::configure().compile_fds(descriptors)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! How much an extracted recipe can be trusted, per field and overall, so that batch runs can flag suspect results
+//! for review.
+//!
+//! A field's score starts from how it was found: from structured data, by the site's preferred selector, or by one
+//! of its alternatives. It is then lowered when the value looks off for that field, e.g. a one-word description or
+//! a single "ingredient" holding the whole page. Missing fields score 0.
+
+use crate::{Recipe, RecipeCssSelectors};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Overall scores below this mark a recipe as low confidence by default.
+pub const DEFAULT_MIN_CONFIDENCE: f64 = 0.5;
+
+/// The fields counted in the overall score, with their weights.
+const WEIGHTS: &[(&str, f64)] = &[("title", 0.2), ("description", 0.1), ("ingredients", 0.3), ("steps", 0.3), ("image", 0.1)];
+
+/// The confidence scores of a recipe, each between 0 and 1.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct Confidence {
+    /// The weighted mean of the title, description, ingredients, steps and image scores.
+    pub overall: f64,
+    /// The score of every field, keyed like `matched_selectors`.
+    pub fields: BTreeMap<String, f64>,
+}
+
+impl Confidence {
+    /// Whether the overall score is below `min`.
+    pub fn is_low(&self, min: f64) -> bool {
+        self.overall < min
+    }
+}
+
+/// Scores a recipe extracted with the selectors.
+pub fn score(recipe: &Recipe, selectors: &RecipeCssSelectors) -> Confidence {
+    let strategy = |field: &str, candidates: &[String]| match recipe.matched_selectors.get(field).map(String::as_str) {
+        Some("json-ld") => 1.0,
+        Some(matched) if candidates.first().is_some_and(|first| first == matched) => 0.9,
+        Some(_) => 0.7,
+        None => 0.6,
+    };
+    let chars = |text: &str| text.chars().count();
+    let mut fields = BTreeMap::new();
+    let mut set = |field: &str, candidates: &[String], plausibility: Option<f64>| {
+        let score = plausibility.map_or(0.0, |plausibility| strategy(field, candidates) * plausibility);
+        fields.insert(field.to_string(), (score * 100.0).round() / 100.0);
+    };
+
+    set("title", &selectors.title.candidates, recipe.title.as_deref().map(|title| if (3..=150).contains(&chars(title)) { 1.0 } else { 0.5 }));
+    set(
+        "description",
+        &selectors.description.candidates,
+        recipe.description.as_deref().map(|description| match chars(description) {
+            0..20 => 0.7,
+            20..=2000 => 1.0,
+            _ => 0.6,
+        }),
+    );
+    set(
+        "ingredients",
+        &selectors.ingredients.candidates,
+        recipe.ingredients.as_deref().filter(|ingredients| !ingredients.is_empty()).map(|ingredients| {
+            let count = if (2..=60).contains(&ingredients.len()) { 1.0 } else { 0.5 };
+            let lengths = if ingredients.iter().any(|ingredient| chars(ingredient) > 200) { 0.6 } else { 1.0 };
+            count * lengths
+        }),
+    );
+    set(
+        "steps",
+        &selectors.steps.candidates,
+        recipe.steps.as_deref().filter(|steps| !steps.is_empty()).map(|steps| {
+            let average = steps.iter().map(|step| chars(step)).sum::<usize>() / steps.len();
+            match (steps.len(), average) {
+                (1, 1500..) => 0.6,
+                (_, 10..=1000) => 1.0,
+                _ => 0.6,
+            }
+        }),
+    );
+    set(
+        "image",
+        &selectors.image.candidates,
+        recipe.image_link.as_deref().map(|link| if link.starts_with("http://") || link.starts_with("https://") { 1.0 } else { 0.5 }),
+    );
+    if recipe.total_time.is_some() || !selectors.total_time.candidates.is_empty() {
+        set("total_time", &selectors.total_time.candidates, recipe.total_time.map(|minutes| if (1..=2880).contains(&minutes) { 1.0 } else { 0.5 }));
+    }
+
+    let overall: f64 = WEIGHTS.iter().map(|(field, weight)| fields[*field] * weight).sum();
+    Confidence { overall: (overall * 100.0).round() / 100.0, fields }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        let selectors = RecipeCssSelectors::from_toml("[s]\ntitle = [\"h1.t\", \"h1\"]\ningredients = \"ul\"\nsteps = \"ol\"\n", "s").unwrap();
+        let mut recipe = Recipe {
+            title: Some("Courgettesoep".to_string()),
+            ingredients: Some(vec!["2 courgettes".to_string(), "1 ui".to_string()]),
+            steps: Some(vec!["Snij de groenten en kook ze 20 minuten.".to_string()]),
+            matched_selectors: [("title", "h1"), ("ingredients", "ul"), ("steps", "json-ld")]
+                .into_iter()
+                .map(|(field, selector)| (field.to_string(), selector.to_string()))
+                .collect(),
+            ..Default::default()
+        };
+        let confidence = score(&recipe, &selectors);
+        assert_eq!(confidence.fields["title"], 0.7);
+        assert_eq!(confidence.fields["ingredients"], 0.9);
+        assert_eq!(confidence.fields["steps"], 1.0);
+        assert_eq!(confidence.fields["description"], 0.0);
+        assert!(!confidence.fields.contains_key("total_time"));
+        assert_eq!(confidence.overall, 0.71);
+        assert!(!confidence.is_low(DEFAULT_MIN_CONFIDENCE));
+
+        recipe.ingredients = Some(vec!["Ingrediënten ".repeat(40)]);
+        recipe.steps = None;
+        let confidence = score(&recipe, &selectors);
+        assert_eq!(confidence.fields["ingredients"], 0.27);
+        assert!(confidence.is_low(DEFAULT_MIN_CONFIDENCE));
+    }
+}
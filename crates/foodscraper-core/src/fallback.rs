@@ -0,0 +1,107 @@
+//! Fallback pages with simpler markup (AMP or print versions), tried when the main extraction comes back mostly empty.
+//!
+//! ```toml
+//! [15gram.fallback]
+//! amp = true                       # follow <link rel="amphtml">, or:
+//! pattern = "^([^?#]*).*$"         # rewrite the recipe URL with a regex
+//! replacement = "$1?print=1"
+//! ingredients = ".print-ingredients"  # selectors for the fallback page; missing ones come from the site
+//! ```
+
+use crate::{Recipe, RecipeCssSelectors};
+use regex::Regex;
+use scraper::{Html, Selector};
+use std::sync::LazyLock;
+use url::Url;
+
+/// Where the fallback page of a site is found, and the selectors that apply to it.
+#[derive(Debug)]
+pub struct Fallback {
+    /// Follow the page's `<link rel="amphtml">`.
+    pub amp: bool,
+    /// A regex rewriting the recipe URL into the fallback URL, with its replacement (`$1` etc.).
+    pub rewrite: Option<(Regex, String)>,
+    pub selectors: RecipeCssSelectors,
+}
+
+impl Fallback {
+    /// The URL of the fallback page for a recipe page, if the rule yields one that differs from it.
+    pub fn url(&self, document: &Html, page_url: &str) -> Option<String> {
+        static AMP: LazyLock<Selector> = LazyLock::new(|| Selector::parse(r#"link[rel~="amphtml"][href]"#).unwrap());
+
+        let url = if self.amp {
+            let href = document.select(&AMP).next()?.value().attr("href")?;
+            Url::parse(page_url).ok()?.join(href.trim()).ok()?.to_string()
+        } else {
+            let (pattern, replacement) = self.rewrite.as_ref()?;
+            pattern.replace(page_url, replacement.as_str()).into_owned()
+        };
+        (url != page_url).then_some(url)
+    }
+}
+
+/// Whether an extraction found so little that the fallback page is worth a try: fewer than two of
+/// the title, ingredients and steps.
+pub fn is_mostly_empty(recipe: &Recipe) -> bool {
+    let found = [
+        recipe.title.is_some(),
+        recipe.ingredients.as_ref().is_some_and(|ingredients| !ingredients.is_empty()),
+        recipe.steps.as_ref().is_some_and(|steps| !steps.is_empty()),
+    ];
+    found.iter().filter(|found| **found).count() < 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SELECTORS: &str = r#"
+        [15gram]
+        domain = "15gram.be"
+        title = "h1"
+        ingredients = ".ingredients"
+
+        [15gram.fallback]
+        pattern = "^([^?#]*).*$"
+        replacement = "$1?print=1"
+        ingredients = ".print li"
+    "#;
+
+    #[test]
+    fn test_fallback_selectors_inherit_from_site() {
+        let selectors = RecipeCssSelectors::from_toml(SELECTORS, "15gram").unwrap();
+        let fallback = selectors.fallback.unwrap();
+        assert_eq!(fallback.selectors.title.candidates, vec!["h1"]);
+        assert_eq!(fallback.selectors.ingredients.candidates, vec![".print li"]);
+        assert!(fallback.selectors.fallback.is_none());
+
+        let document = Html::parse_document("");
+        assert_eq!(
+            fallback.url(&document, "https://15gram.be/recepten/soep?utm_source=x").as_deref(),
+            Some("https://15gram.be/recepten/soep?print=1")
+        );
+    }
+
+    #[test]
+    fn test_amp_fallback_url() {
+        let fallback = Fallback {
+            amp: true,
+            rewrite: None,
+            selectors: RecipeCssSelectors::from_toml(SELECTORS, "15gram").unwrap(),
+        };
+        let document = Html::parse_document(r#"<link rel="amphtml" href="/recepten/soep/amp">"#);
+        assert_eq!(
+            fallback.url(&document, "https://15gram.be/recepten/soep").as_deref(),
+            Some("https://15gram.be/recepten/soep/amp")
+        );
+        assert_eq!(fallback.url(&Html::parse_document(""), "https://15gram.be/recepten/soep"), None);
+    }
+
+    #[test]
+    fn test_is_mostly_empty() {
+        let mut recipe = Recipe { title: Some("Soep".to_string()), ingredients: Some(Vec::new()), ..Default::default() };
+        assert!(is_mostly_empty(&recipe));
+        recipe.steps = Some(vec!["Kook.".to_string()]);
+        assert!(!is_mostly_empty(&recipe));
+    }
+}
@@ -0,0 +1,206 @@
+//! The selector of one recipe field in `selectors.toml`.
+//!
+//! A field is a single CSS selector or a list of them, tried in order until one matches, for sites that
+//! A/B-test their layouts:
+//!
+//! ```toml
+//! title = ["h1.recipe-title", "h1"]
+//! ```
+//!
+//! The table form takes options; `select_all` makes every matching element one list entry instead of splitting the
+//! text of the first one:
+//!
+//! ```toml
+//! ingredients = { selector = ".ingredients li", select_all = true }
+//! ```
+//!
+//! and `attr` reads an attribute of the element instead of its text:
+//!
+//! ```toml
+//! image = { selector = "meta[property='og:image']", attr = "content" }
+//! ```
+//!
+//! Regex rules clean up the selected values: each `replace` rule in turn, then `capture`, which keeps the first
+//! group (or the whole match) and drops values it does not match:
+//!
+//! ```toml
+//! steps = { selector = "#preparation", replace = [{ pattern = "^Bereiding:\\s*", replacement = "" }] }
+//! ```
+//!
+//! Candidates starting with `/` or `./` are XPath expressions (see [`crate::xpath`]), as are those listed under
+//! `xpath` in the table form:
+//!
+//! ```toml
+//! ingredients = { xpath = "//h2[contains(., 'Ingrediënten')]/following-sibling::ul[1]" }
+//! ```
+
+use crate::xpath::{self, XPath};
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use toml::Value;
+
+/// The candidate selectors of one field, in order of preference.
+#[derive(Debug, Clone, Default)]
+pub struct FieldSelector {
+    pub candidates: Vec<String>,
+    /// Whether every element matched by the candidate is used, rather than the first.
+    pub select_all: bool,
+    /// The attribute read from the element instead of its text.
+    pub attr: Option<String>,
+    /// The `replace` rules, as pattern and replacement, applied in order.
+    pub replace: Vec<(Regex, String)>,
+    /// The `capture` rule, applied after the replacements.
+    pub capture: Option<Regex>,
+}
+
+impl FieldSelector {
+    /// Reads a field given as a string, a list of strings, or a table with a `selector` of either kind and options;
+    /// anything else leaves it unset. Fails on a rule that is not a valid regex.
+    pub fn from_value(value: Option<&Value>) -> Result<Self, regex::Error> {
+        let options = value.and_then(Value::as_table);
+        let option = |name: &str| options.and_then(|options| options.get(name));
+        let strings = |value: Option<&Value>| match value {
+            Some(Value::String(selector)) => vec![selector.clone()],
+            Some(Value::Array(selectors)) => selectors.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+            _ => Vec::new(),
+        };
+        let mut candidates = strings(options.map_or(value, |options| options.get("selector")));
+        // XPath given under `xpath` is relative to the document, so `html/body` is written `/html/body` here.
+        let xpaths = strings(option("xpath")).into_iter();
+        candidates.extend(xpaths.map(|xpath| if xpath::looks_like_xpath(&xpath) { xpath } else { format!("/{}", xpath) }));
+        let replace = option("replace").and_then(Value::as_array).into_iter().flatten().filter_map(|rule| {
+            let pattern = rule.get("pattern")?.as_str()?;
+            let replacement = rule.get("replacement").and_then(Value::as_str).unwrap_or_default();
+            Some(Regex::new(pattern).map(|pattern| (pattern, replacement.to_string())))
+        });
+        Ok(FieldSelector {
+            candidates: candidates.into_iter().filter(|selector| !selector.trim().is_empty()).collect(),
+            select_all: option("select_all").and_then(Value::as_bool).unwrap_or(false),
+            attr: option("attr").and_then(Value::as_str).map(str::to_string),
+            replace: replace.collect::<Result<_, _>>()?,
+            capture: option("capture").and_then(Value::as_str).map(Regex::new).transpose()?,
+        })
+    }
+
+    /// Runs the field's regex rules over a value; `None` when `capture` does not match it.
+    pub fn apply_rules(&self, value: &str) -> Option<String> {
+        let mut value = value.to_string();
+        for (pattern, replacement) in &self.replace {
+            value = pattern.replace_all(&value, replacement.as_str()).trim().to_string();
+        }
+        match &self.capture {
+            Some(capture) => {
+                let captures = capture.captures(&value)?;
+                Some(captures.get(1).or_else(|| captures.get(0))?.as_str().trim().to_string())
+            }
+            None => Some(value),
+        }
+    }
+
+    /// The values of `attr` on the elements, trimmed and without empty ones, when the field reads an attribute.
+    pub fn attribute_values(&self, elements: &[ElementRef]) -> Option<Vec<String>> {
+        let attr = self.attr.as_deref()?;
+        let values = elements.iter().filter_map(|element| element.value().attr(attr)).map(str::trim).filter(|value| !value.is_empty());
+        Some(values.map(str::to_string).collect())
+    }
+
+    /// The candidates that are neither valid CSS nor valid XPath, which [`select`](Self::select) skips.
+    pub fn invalid_candidates(&self) -> impl Iterator<Item = &str> {
+        self.candidates.iter().map(String::as_str).filter(|candidate| {
+            if xpath::looks_like_xpath(candidate) { XPath::parse(candidate).is_err() } else { Selector::parse(candidate).is_err() }
+        })
+    }
+
+    /// The elements matched by the first candidate that matches anything, with that candidate: all of them with
+    /// `select_all`, otherwise the first.
+    ///
+    /// Candidates that are not valid CSS or XPath are skipped.
+    pub fn select<'a>(&self, document: &'a Html) -> Option<(&str, Vec<ElementRef<'a>>)> {
+        let limit = if self.select_all { usize::MAX } else { 1 };
+        self.candidates.iter().find_map(|candidate| {
+            let elements: Vec<_> = if xpath::looks_like_xpath(candidate) {
+                XPath::parse(candidate).ok()?.select(document).into_iter().take(limit).collect()
+            } else {
+                document.select(&Selector::parse(candidate).ok()?).take(limit).collect()
+            };
+            (!elements.is_empty()).then_some((candidate.as_str(), elements))
+        })
+    }
+}
+
+impl From<&str> for FieldSelector {
+    fn from(selector: &str) -> Self {
+        FieldSelector { candidates: vec![selector.to_string()], ..Default::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_first_tries_candidates_in_order() {
+        let field = FieldSelector::from_value(Some(&toml::from_str::<Value>("f = ['h1.old', 'h1..bad', 'h1.new', 'h1']").unwrap()["f"])).unwrap();
+        assert_eq!(field.candidates.len(), 4);
+        let document = Html::parse_document("<h1 class=\"new\">Soep</h1>");
+        let (matched, elements) = field.select(&document).unwrap();
+        assert_eq!(matched, "h1.new");
+        assert_eq!(elements[0].text().collect::<String>(), "Soep");
+        assert!(FieldSelector::from("h2").select(&document).is_none());
+        assert!(FieldSelector::from_value(None).unwrap().candidates.is_empty());
+    }
+
+    #[test]
+    fn test_select_all_from_table() {
+        let value: Value = toml::from_str("f = { selector = ['ol.old li', 'ul li'], select_all = true }").unwrap();
+        let field = FieldSelector::from_value(value.get("f")).unwrap();
+        let document = Html::parse_document("<ul><li>Snij de ui.</li><li>Bak de ui.</li></ul>");
+        let (matched, elements) = field.select(&document).unwrap();
+        assert_eq!(matched, "ul li");
+        assert_eq!(elements.len(), 2);
+        assert_eq!(FieldSelector::from("ul li").select(&document).unwrap().1.len(), 1);
+    }
+
+    #[test]
+    fn test_select_xpath_candidates() {
+        let value: Value = toml::from_str(
+            r#"f = ["//h2[contains(., 'Ingrediënten')]/following-sibling::ul[1]/li", "ul li"]
+               g = { xpath = "html/body/ul", select_all = true }"#,
+        )
+        .unwrap();
+        let document = Html::parse_document("<h2>Ingrediënten</h2><ul><li>1 ui</li></ul><h2>Extra</h2><ul><li>Zout</li></ul>");
+        let field = FieldSelector::from_value(value.get("f")).unwrap();
+        let (matched, elements) = field.select(&document).unwrap();
+        assert!(matched.starts_with("//h2"));
+        assert_eq!(elements[0].text().collect::<String>(), "1 ui");
+        let field = FieldSelector::from_value(value.get("g")).unwrap();
+        assert_eq!(field.candidates, vec!["/html/body/ul"]);
+        assert_eq!(field.select(&document).unwrap().1.len(), 2);
+    }
+
+    #[test]
+    fn test_attribute_values() {
+        let value: Value = toml::from_str("f = { selector = 'meta[name=description]', attr = 'content' }").unwrap();
+        let field = FieldSelector::from_value(value.get("f")).unwrap();
+        let document = Html::parse_document(r#"<meta name="description" content=" Romige soep. ">"#);
+        let (_, elements) = field.select(&document).unwrap();
+        assert_eq!(field.attribute_values(&elements), Some(vec!["Romige soep.".to_string()]));
+        assert_eq!(FieldSelector::from("meta").attribute_values(&elements), None);
+    }
+
+    #[test]
+    fn test_apply_rules() {
+        let value: Value = toml::from_str(
+            r#"f = { selector = "p", replace = [{ pattern = "(?i)^bereiding:\\s*", replacement = "" }], capture = "^(.+?)\\.?$" }
+               g = { selector = "p", capture = "voor (\\d+) personen" }
+               h = { selector = "p", capture = "(" }"#,
+        )
+        .unwrap();
+        let field = FieldSelector::from_value(value.get("f")).unwrap();
+        assert_eq!(field.apply_rules("Bereiding: Snij de ui.").as_deref(), Some("Snij de ui"));
+        let servings = FieldSelector::from_value(value.get("g")).unwrap();
+        assert_eq!(servings.apply_rules("Recept voor 4 personen").as_deref(), Some("4"));
+        assert_eq!(servings.apply_rules("Recept"), None);
+        assert!(FieldSelector::from_value(value.get("h")).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! Picking recipe image URLs out of the page.
+
+use scraper::{ElementRef, Html, Selector};
+use url::Url;
+
+/// Attributes checked for the image URL when a site does not configure `image_attributes`, in order.
+///
+/// `srcset`-style attributes are listed first so the largest candidate wins over a small `src`.
+pub const DEFAULT_IMAGE_ATTRIBUTES: &[&str] =
+    &["srcset", "data-srcset", "data-lazy-srcset", "data-src", "data-lazy-src", "data-original", "src"];
+
+/// Finds the image URL for a selected element.
+///
+/// The element's own attributes are tried first, then those of the `img`/`source` elements inside it,
+/// and finally images inside a `<noscript>` fallback within or right after the element. Inline `data:`
+/// URIs, which lazy-loading scripts use as placeholders, are skipped. The link is returned as written.
+pub fn image_link_from(element: ElementRef, attributes: &[String]) -> Option<String> {
+    static IMAGES: std::sync::LazyLock<Selector> = std::sync::LazyLock::new(|| Selector::parse("img, source").unwrap());
+    static NOSCRIPT: std::sync::LazyLock<Selector> = std::sync::LazyLock::new(|| Selector::parse("noscript").unwrap());
+
+    let from_element = |element: ElementRef| {
+        attributes.iter().find_map(|attribute| {
+            let value = element.value().attr(attribute)?.trim();
+            let link = if attribute.ends_with("srcset") { largest_srcset_candidate(value)? } else { value };
+            (!link.is_empty() && !link.starts_with("data:")).then(|| link.to_string())
+        })
+    };
+    if let Some(link) = from_element(element).or_else(|| element.select(&IMAGES).find_map(from_element)) {
+        return Some(link);
+    }
+
+    let following_noscript = element.next_siblings().filter_map(ElementRef::wrap).next().filter(|e| e.value().name() == "noscript");
+    element.select(&NOSCRIPT).chain(following_noscript).find_map(|noscript| {
+        let fallback = Html::parse_fragment(&noscript.text().collect::<String>());
+        fallback.select(&IMAGES).find_map(from_element)
+    })
+}
+
+/// Picks the URL of the largest candidate in a `srcset` attribute.
+///
+/// Candidates are compared by their width (`640w`) or pixel density (`2x`) descriptor; a candidate
+/// without a descriptor counts as `1x`.
+pub fn largest_srcset_candidate(srcset: &str) -> Option<&str> {
+    let mut best: Option<(&str, f64)> = None;
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            break;
+        }
+        let url_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (url, after) = rest.split_at(url_end);
+        let (url, descriptor, after) = match url.strip_suffix(',') {
+            Some(url) => (url.trim_end_matches(','), "", after),
+            None => {
+                let descriptor_end = after.find(',').unwrap_or(after.len());
+                (url, after[..descriptor_end].trim(), &after[descriptor_end..])
+            }
+        };
+        rest = after;
+
+        let size = match descriptor.chars().last() {
+            Some('w' | 'x') => descriptor[..descriptor.len() - 1].parse::<f64>().unwrap_or(0.0),
+            _ => 1.0,
+        };
+        if !url.is_empty() && best.is_none_or(|(_, best_size)| size > best_size) {
+            best = Some((url, size));
+        }
+    }
+    best.map(|(url, _)| url)
+}
+
+/// Resolves a possibly relative link against the page it appeared on.
+pub fn resolve_link(link: &str, page_url: &str) -> String {
+    Url::parse(page_url).and_then(|base| base.join(link)).map(String::from).unwrap_or_else(|_| link.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_largest_srcset_candidate() {
+        let srcset = "/img/soep-320.jpg 320w, /img/soep-1280.jpg 1280w, /img/soep-640.jpg 640w";
+        assert_eq!(largest_srcset_candidate(srcset), Some("/img/soep-1280.jpg"));
+        assert_eq!(largest_srcset_candidate("a.jpg, b.jpg 2x"), Some("b.jpg"));
+        assert_eq!(
+            largest_srcset_candidate("https://cdn.example/w_400,h_300/soep.jpg 400w, https://cdn.example/w_800,h_600/soep.jpg 800w"),
+            Some("https://cdn.example/w_800,h_600/soep.jpg")
+        );
+        assert_eq!(largest_srcset_candidate(" "), None);
+    }
+
+    fn image_link(html: &str, selector: &str) -> Option<String> {
+        let document = Html::parse_document(html);
+        let attributes: Vec<String> = DEFAULT_IMAGE_ATTRIBUTES.iter().map(|a| a.to_string()).collect();
+        let element = document.select(&Selector::parse(selector).unwrap()).next().unwrap();
+        image_link_from(element, &attributes)
+    }
+
+    #[test]
+    fn test_image_link_from_lazy_attributes() {
+        let html = r#"<img class="r" src="data:image/gif;base64,R0lGOD" data-lazy-src="/img/soep.jpg">"#;
+        assert_eq!(image_link(html, "img.r").as_deref(), Some("/img/soep.jpg"));
+        let html = r#"<div class="r"><picture><img data-src="/img/pasta.jpg"></picture></div>"#;
+        assert_eq!(image_link(html, "div.r").as_deref(), Some("/img/pasta.jpg"));
+    }
+
+    #[test]
+    fn test_image_link_from_noscript_fallback() {
+        let html = r#"<div><img class="r lazy"><noscript><img src="/img/stoofvlees.jpg"></noscript></div>"#;
+        assert_eq!(image_link(html, "img.r").as_deref(), Some("/img/stoofvlees.jpg"));
+        let html = r#"<figure class="r"><noscript><img src="/img/wafels.jpg"></noscript></figure>"#;
+        assert_eq!(image_link(html, "figure.r").as_deref(), Some("/img/wafels.jpg"));
+    }
+
+    #[test]
+    fn test_resolve_link() {
+        assert_eq!(resolve_link("/img/soep.jpg", "https://15gram.be/recepten/soep"), "https://15gram.be/img/soep.jpg");
+        assert_eq!(resolve_link("https://cdn.example/soep.jpg", "https://15gram.be/"), "https://cdn.example/soep.jpg");
+    }
+}
@@ -0,0 +1,137 @@
+//! Reading recipes from the schema.org `Recipe` structured data (JSON-LD) that many sites embed for search engines.
+//!
+//! Sites with `structured_data = true` in `selectors.toml` are read this way first; their CSS selectors, if any,
+//! fill in whatever the structured data leaves out.
+
+use crate::Recipe;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde_json::Value;
+use std::sync::LazyLock;
+
+static LD_JSON: LazyLock<Selector> = LazyLock::new(|| Selector::parse(r#"script[type="application/ld+json"]"#).unwrap());
+
+/// Matches ISO 8601 durations such as `PT1H30M` or `P0DT45M`.
+static ISO_DURATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^P(?:(\d+)D)?(?:T(?:(\d+)H)?(?:(\d+)M)?(?:(\d+(?:\.\d+)?)S)?)?$").unwrap()
+});
+
+/// The recipe described by the first schema.org `Recipe` in the page's JSON-LD blocks, if any.
+///
+/// Only the recipe fields are set; `source_url` is left empty and the image link is not resolved.
+pub fn recipe_from_document(document: &Html) -> Option<Recipe> {
+    document
+        .select(&LD_JSON)
+        .filter_map(|script| serde_json::from_str::<Value>(&script.text().collect::<String>()).ok())
+        .find_map(|value| find_recipe(&value).map(recipe_from_value))
+}
+
+/// Finds the `Recipe` object in a JSON-LD value, looking through arrays and `@graph` lists.
+fn find_recipe(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Array(items) => items.iter().find_map(find_recipe),
+        Value::Object(_) if is_recipe(value) => Some(value),
+        Value::Object(object) => object.get("@graph").and_then(find_recipe),
+        _ => None,
+    }
+}
+
+/// Whether the object's `@type` is, or includes, `Recipe`.
+fn is_recipe(value: &Value) -> bool {
+    match value.get("@type") {
+        Some(Value::String(kind)) => kind == "Recipe",
+        Some(Value::Array(kinds)) => kinds.iter().any(|kind| kind == "Recipe"),
+        _ => false,
+    }
+}
+
+fn recipe_from_value(value: &Value) -> Recipe {
+    let text = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    let ingredients = value.get("recipeIngredient").or_else(|| value.get("ingredients")).map(strings);
+    Recipe {
+        title: text("name").or_else(|| text("headline")),
+        description: text("description"),
+        ingredients: ingredients.filter(|ingredients| !ingredients.is_empty()),
+        steps: value.get("recipeInstructions").map(instructions).filter(|steps| !steps.is_empty()),
+        image_link: value.get("image").and_then(image_url),
+        total_time: ["totalTime", "cookTime"].iter().find_map(|key| value.get(*key)?.as_str().and_then(parse_iso_duration)),
+        ..Default::default()
+    }
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(text) => vec![text.clone()],
+        Value::Array(items) => items.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Flattens `recipeInstructions`: plain text, a list of texts, `HowToStep`s, or `HowToSection`s of those.
+fn instructions(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(text) => text.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect(),
+        Value::Array(items) => items.iter().flat_map(instructions).collect(),
+        Value::Object(object) => match object.get("itemListElement") {
+            Some(steps) => instructions(steps),
+            None => object.get("text").or_else(|| object.get("name")).map(instructions).unwrap_or_default(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// The URL of `image`: a URL, an `ImageObject`, or a list of either (the first is taken).
+fn image_url(value: &Value) -> Option<String> {
+    match value {
+        Value::String(url) => Some(url.clone()),
+        Value::Array(items) => items.iter().find_map(image_url),
+        Value::Object(object) => object.get("url").and_then(image_url),
+        _ => None,
+    }
+}
+
+/// Parses an ISO 8601 duration into whole minutes, rounding seconds up.
+pub fn parse_iso_duration(duration: &str) -> Option<u32> {
+    let captures = ISO_DURATION.captures(duration.trim())?;
+    let number = |index: usize| captures.get(index).and_then(|m| m.as_str().parse::<f64>().ok()).unwrap_or(0.0);
+    let seconds = number(1) * 86400.0 + number(2) * 3600.0 + number(3) * 60.0 + number(4);
+    (seconds > 0.0).then(|| (seconds / 60.0).ceil() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_iso_duration() {
+        assert_eq!(parse_iso_duration("PT1H30M"), Some(90));
+        assert_eq!(parse_iso_duration("P0DT45M"), Some(45));
+        assert_eq!(parse_iso_duration("PT90S"), Some(2));
+        assert_eq!(parse_iso_duration("PT0M"), None);
+        assert_eq!(parse_iso_duration("45 minutes"), None);
+    }
+
+    #[test]
+    fn test_recipe_from_graph_with_sections() {
+        let html = r#"<script type="application/ld+json">{"@context": "https://schema.org", "@graph": [
+            {"@type": "WebPage", "name": "Page"},
+            {"@type": ["Recipe", "NewsArticle"], "name": "Tomato soup", "recipeIngredient": ["4 tomatoes", "1 onion"],
+             "recipeInstructions": [{"@type": "HowToSection", "name": "Soup", "itemListElement": [
+                {"@type": "HowToStep", "text": "Chop the onion."}, {"@type": "HowToStep", "text": "Add the tomatoes."}]}],
+             "image": [{"@type": "ImageObject", "url": "https://example.com/soup.jpg"}], "totalTime": "PT40M"}
+        ]}</script>"#;
+        let recipe = recipe_from_document(&Html::parse_document(html)).unwrap();
+        assert_eq!(recipe.title.as_deref(), Some("Tomato soup"));
+        assert_eq!(recipe.ingredients.unwrap().len(), 2);
+        assert_eq!(recipe.steps.unwrap(), vec!["Chop the onion.", "Add the tomatoes."]);
+        assert_eq!(recipe.image_link.as_deref(), Some("https://example.com/soup.jpg"));
+        assert_eq!(recipe.total_time, Some(40));
+    }
+
+    #[test]
+    fn test_recipe_from_document_without_recipe() {
+        let html = r#"<script type="application/ld+json">{"@type": "Organization", "name": "Site"}</script>
+            <script type="application/ld+json">not json</script>"#;
+        assert_eq!(recipe_from_document(&Html::parse_document(html)), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! Lightweight language detection for scraped recipe text.
+//!
+//! Recipes are short and full of domain words, so instead of a statistical
+//! model we score the text against small lists of very common function words.
+//! That is plenty to tell Flemish, French, English and German recipes apart.
+
+/// Common function words per ISO 639-1 language code.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "met", "in", "op", "voor", "je", "is", "dat", "niet", "of", "aan", "tot",
+            "ook", "bij", "om", "uit", "wat", "zijn", "wordt", "laat", "snij", "voeg", "gr", "el", "kl",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "un", "une", "des", "et", "du", "de", "avec", "dans", "pour", "sur", "au", "aux", "est",
+            "pas", "ou", "en", "ajoutez", "faites", "cuire",
+        ],
+    ),
+    (
+        "en",
+        &[
+            "the", "a", "and", "of", "with", "in", "to", "for", "on", "is", "it", "or", "into", "until", "add", "cook",
+            "cup", "cups", "tbsp", "tsp",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "mit", "ein", "eine", "in", "zu", "von", "für", "auf", "ist", "nicht", "oder",
+            "den", "dem", "im", "etwas",
+        ],
+    ),
+];
+
+/// Minimum number of stopword hits before a guess is trusted.
+const MIN_HITS: usize = 3;
+
+/// Detects the language of the given text, returning an ISO 639-1 code such as `nl` or `fr`.
+///
+/// Returns `None` when the text is too short or no language clearly wins.
+pub fn detect_language(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+
+    let mut scores: Vec<(&str, usize)> = STOPWORDS
+        .iter()
+        .map(|(code, stopwords)| (*code, words.iter().filter(|w| stopwords.contains(&w.as_str())).count()))
+        .collect();
+    scores.sort_by_key(|(_, hits)| std::cmp::Reverse(*hits));
+
+    match scores.as_slice() {
+        [(code, best), rest @ ..] if *best >= MIN_HITS && rest.first().is_none_or(|(_, second)| best > second) => {
+            Some(code.to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language_dutch() {
+        let text = "Verwarm de airfryer 5 min. voor op 200 ºC. Halveer het pizzadeeg en vouw de randen om.";
+        assert_eq!(detect_language(text), Some("nl".to_string()));
+    }
+
+    #[test]
+    fn test_detect_language_french() {
+        let text = "Faites cuire les pâtes dans une casserole d'eau salée et ajoutez la sauce avec du fromage.";
+        assert_eq!(detect_language(text), Some("fr".to_string()));
+    }
+
+    #[test]
+    fn test_detect_language_english() {
+        let text = "Add the onions to the pan and cook until soft, then stir in the tomatoes.";
+        assert_eq!(detect_language(text), Some("en".to_string()));
+    }
+
+    #[test]
+    fn test_detect_language_too_short() {
+        assert_eq!(detect_language("Lasagne"), None);
+    }
+}
@@ -0,0 +1,595 @@
+//! The network-free recipe extraction pipeline of FoodScraper: HTML plus the selectors of a site in,
+//! a [`Recipe`] out. It builds for `wasm32-unknown-unknown`, where the `wasm` feature exposes it to JavaScript.
+
+pub mod confidence;
+pub mod fallback;
+pub mod field;
+pub mod images;
+pub mod jsonld;
+pub mod language;
+pub mod report;
+pub mod schema;
+pub mod steps;
+pub mod text;
+pub mod urls;
+#[cfg(feature = "wasm")]
+mod wasm;
+pub mod xpath;
+
+use field::FieldSelector;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use steps::{StepCleanup, StepTimer};
+use toml::Value;
+use url::Url;
+
+/// Caching validators returned by the server, replayed on the next fetch of the same page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HttpCache {
+    /// The `ETag` response header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// The `Last-Modified` response header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+/// A scraped recipe, as saved to the JSON files and the database.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct Recipe {
+    /// The title of the recipe.
+    pub title: Option<String>,
+    /// A brief description of the recipe.
+    pub description: Option<String>,
+    /// A list of ingredients required for the recipe.
+    pub ingredients: Option<Vec<String>>,
+    /// A list of steps to prepare the recipe.
+    pub steps: Option<Vec<String>>,
+    /// Durations mentioned in the steps, each pointing back to the step it was found in.
+    pub timers: Option<Vec<StepTimer>>,
+    /// A link to an image of the prepared recipe.
+    pub image_link: Option<String>,
+    /// The file name of a thumbnail of the downloaded image, next to the recipe file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    /// The total preparation time in minutes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_time: Option<u32>,
+    /// The detected ISO 639-1 language code of the recipe text (e.g. `nl`, `fr`, `en`).
+    pub language: Option<String>,
+    /// The URL source of the recipe.
+    pub source_url: String,
+    /// When the recipe was scraped, as an RFC 3339 timestamp.
+    pub scraped_at: Option<String>,
+    /// User-assigned tags for organising the library.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Whether the user marked the recipe as a favorite.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub favorite: bool,
+    /// HTTP caching validators from the last fetch, used by `refresh` for conditional requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_cache: Option<HttpCache>,
+    /// The archived copy the recipe was extracted from, when the live page was gone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_copy: Option<ArchivedCopy>,
+    /// Per field, the selector that matched on the page, or `json-ld` when the field came from structured data.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub matched_selectors: BTreeMap<String, String>,
+    /// How much the extraction can be trusted, per field and overall (see [`confidence`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<confidence::Confidence>,
+}
+
+/// A Wayback Machine snapshot of a recipe page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedCopy {
+    /// The snapshot's address on web.archive.org.
+    pub url: String,
+    /// When the snapshot was taken, as an RFC 3339 timestamp.
+    pub archived_at: String,
+}
+
+/// The CSS selectors locating the parts of a recipe on one site, from `selectors.toml`. Each field can list
+/// several selectors, tried in order.
+#[derive(Debug)]
+pub struct RecipeCssSelectors {
+    /// The CSS selectors for the recipe title.
+    pub title: FieldSelector,
+    /// The CSS selectors for the recipe description.
+    pub description: FieldSelector,
+    /// The CSS selectors for the recipe ingredients.
+    pub ingredients: FieldSelector,
+    /// The CSS selector, inside the ingredients, of one ingredient whose parts (quantity, unit, name) sit in
+    /// separate elements; optional. Each match becomes one ingredient.
+    pub ingredient_item: String,
+    /// The CSS selector, inside the ingredients, of section headings such as "Voor de saus"; optional, used together
+    /// with `ingredient_item`. Each heading is kept as a line ending in a colon before the ingredients of its section.
+    pub ingredient_heading: String,
+    /// The CSS selectors for the recipe steps.
+    pub steps: FieldSelector,
+    /// The CSS selectors for the recipe image.
+    pub image: FieldSelector,
+    /// The attributes holding the image URL, in order of preference.
+    pub image_attributes: Vec<String>,
+    /// The CSS selectors for the total preparation time; optional.
+    pub total_time: FieldSelector,
+    /// How the extracted steps are cleaned up for this site.
+    pub step_cleanup: StepCleanup,
+    /// Whether the recipe is read from the page's schema.org JSON-LD first, with the selectors as a fallback.
+    pub structured_data: bool,
+    /// The simpler page to extract from when these selectors find too little.
+    pub fallback: Option<Box<fallback::Fallback>>,
+}
+
+impl RecipeCssSelectors {
+    /// Reads the selectors of `website` from the contents of a `selectors.toml` file, failing on entries that do
+    /// not match the [`schema`].
+    pub fn from_toml(content: &str, website: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let value: Value = toml::from_str(content)?;
+
+        let website_selectors = value.get(website).ok_or("Website not found in selectors file")?;
+        schema::validate_site(website, website_selectors)?;
+        let mut selectors = Self::from_value(website_selectors)?;
+        if let Some(Value::Table(fallback)) = website_selectors.get("fallback") {
+            // The fallback page inherits every selector it does not set from the site.
+            let mut merged = website_selectors.as_table().cloned().unwrap_or_default();
+            merged.remove("fallback");
+            merged.extend(fallback.iter().map(|(key, value)| (key.clone(), value.clone())));
+            let rewrite = match (fallback.get("pattern").and_then(Value::as_str), fallback.get("replacement").and_then(Value::as_str)) {
+                (Some(pattern), Some(replacement)) => Some((regex::Regex::new(pattern)?, replacement.to_string())),
+                _ => None,
+            };
+            selectors.fallback = Some(Box::new(fallback::Fallback {
+                amp: fallback.get("amp").and_then(Value::as_bool).unwrap_or(false),
+                rewrite,
+                selectors: Self::from_value(&Value::Table(merged))?,
+            }));
+        }
+        Ok(selectors)
+    }
+
+    /// Reads the selectors from one site's table, without its fallback.
+    fn from_value(website_selectors: &Value) -> Result<Self, regex::Error> {
+        Ok(RecipeCssSelectors {
+            title: FieldSelector::from_value(website_selectors.get("title"))?,
+            description: FieldSelector::from_value(website_selectors.get("description"))?,
+            ingredients: FieldSelector::from_value(website_selectors.get("ingredients"))?,
+            ingredient_item: website_selectors.get("ingredient_item").and_then(Value::as_str).unwrap_or_default().to_string(),
+            ingredient_heading: website_selectors.get("ingredient_heading").and_then(Value::as_str).unwrap_or_default().to_string(),
+            steps: FieldSelector::from_value(website_selectors.get("steps"))?,
+            image: FieldSelector::from_value(website_selectors.get("image"))?,
+            image_attributes: match website_selectors.get("image_attributes").and_then(Value::as_array) {
+                Some(attributes) => attributes.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+                None => images::DEFAULT_IMAGE_ATTRIBUTES.iter().map(|attribute| attribute.to_string()).collect(),
+            },
+            total_time: FieldSelector::from_value(website_selectors.get("total_time"))?,
+            step_cleanup: StepCleanup::from_toml(website_selectors.get("step_cleanup")),
+            structured_data: website_selectors.get("structured_data").and_then(Value::as_bool).unwrap_or(false),
+            fallback: None,
+        })
+    }
+
+    /// Reads the selectors of the site the URL belongs to (see [`site_for_url`]).
+    pub fn for_url(content: &str, url: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let website = site_for_url(content, url).ok_or("No site in the selectors file has the URL's domain")?;
+        Self::from_toml(content, &website)
+    }
+}
+
+/// Extracts the recipe details from the HTML document using the provided selectors, and scores how much they can be
+/// trusted.
+///
+/// Sites with `structured_data` take each field from the page's JSON-LD recipe when it has one (see [`jsonld`]).
+/// The recipe's `source_url` is the page's canonical URL when it declares one on the same site, and is normalized
+/// either way (see [`urls::normalize_url`]).
+#[tracing::instrument(level = "debug", skip(document, selectors))]
+pub fn extract_recipe(document: &Html, selectors: &RecipeCssSelectors, source_url: &str) -> Recipe {
+    let mut matched_selectors = BTreeMap::new();
+    let mut select = |name: &str, field| select_field(document, name, field, &mut matched_selectors);
+    let title = select("title", &selectors.title);
+    let description = select("description", &selectors.description);
+    let ingredients = select("ingredients", &selectors.ingredients);
+    let steps = select("steps", &selectors.steps);
+    let image = select("image", &selectors.image);
+    let total_time = select("total_time", &selectors.total_time);
+    let list = |field: &FieldSelector, elements: &[ElementRef]| match field.select_all {
+        true => element_texts(elements),
+        false => None,
+    };
+    let mut recipe = Recipe {
+        title: match selectors.title.attribute_values(&title) {
+            Some(values) => values.into_iter().next(),
+            None => get_recipe_title(title.first().copied()),
+        },
+        description: match selectors.description.attribute_values(&description).or_else(|| list(&selectors.description, &description)) {
+            Some(paragraphs) => Some(paragraphs.join(" ")).filter(|text| !text.is_empty()),
+            None => get_recipe_description(description.first().copied()),
+        },
+        ingredients: (selectors.ingredients.attribute_values(&ingredients))
+            .or_else(|| list(&selectors.ingredients, &ingredients))
+            .or_else(|| get_recipe_ingredients(ingredients.first().copied(), selectors)),
+        steps: (selectors.steps.attribute_values(&steps))
+            .or_else(|| list(&selectors.steps, &steps))
+            .or_else(|| get_recipe_steps(steps.first().copied())),
+        image_link: match &selectors.image.attr {
+            Some(attr) => get_recipe_image(image.first().copied(), std::slice::from_ref(attr), source_url),
+            None => get_recipe_image(image.first().copied(), &selectors.image_attributes, source_url),
+        },
+        total_time: get_recipe_total_time(
+            match selectors.total_time.attribute_values(&total_time) {
+                Some(values) => values.into_iter().next(),
+                None => total_time.first().map(|e| e.text().collect::<Vec<_>>().join(" ")),
+            }
+            .and_then(|text| selectors.total_time.apply_rules(&text)),
+        ),
+        source_url: urls::canonical_link(document, source_url).unwrap_or_else(|| urls::normalize_url(source_url)),
+        ..Default::default()
+    };
+    if let Some(structured) = selectors.structured_data.then(|| jsonld::recipe_from_document(document)).flatten() {
+        let mut from_structured = |name: &str, found: bool| {
+            if found {
+                matched_selectors.insert(name.to_string(), "json-ld".to_string());
+            }
+        };
+        from_structured("title", structured.title.is_some());
+        from_structured("description", structured.description.is_some());
+        from_structured("ingredients", structured.ingredients.is_some());
+        from_structured("steps", structured.steps.is_some());
+        from_structured("image", structured.image_link.is_some());
+        from_structured("total_time", structured.total_time.is_some());
+        recipe.title = structured.title.or(recipe.title);
+        recipe.description = structured.description.or(recipe.description);
+        recipe.ingredients = structured.ingredients.or(recipe.ingredients);
+        recipe.steps = structured.steps.or(recipe.steps);
+        recipe.image_link = structured.image_link.map(|link| images::resolve_link(&link, source_url)).or(recipe.image_link);
+        recipe.total_time = structured.total_time.or(recipe.total_time);
+    }
+    recipe.matched_selectors = matched_selectors;
+    sanitize_recipe(&mut recipe);
+    apply_rules(&mut recipe, selectors);
+    recipe.steps = recipe.steps.map(|steps| steps::clean_steps(&steps, &selectors.step_cleanup));
+    recipe.timers = recipe.steps.as_deref().map(steps::extract_timers).filter(|timers| !timers.is_empty());
+    recipe.language = language::detect_language(&recipe_text(&recipe));
+    recipe.confidence = Some(confidence::score(&recipe, selectors));
+    recipe
+}
+
+/// Runs the text sanitization pass over every string field of the recipe, dropping values that end up empty.
+fn sanitize_recipe(recipe: &mut Recipe) {
+    for field in [&mut recipe.title, &mut recipe.description] {
+        *field = field.as_deref().map(text::sanitize_text).filter(|s| !s.is_empty());
+    }
+    for list in [&mut recipe.ingredients, &mut recipe.steps].into_iter().flatten() {
+        *list = list.iter().map(|s| text::sanitize_text(s)).filter(|s| !s.is_empty()).collect();
+    }
+}
+
+/// Runs the regex rules of each field over its sanitized values (see [`FieldSelector::apply_rules`]).
+fn apply_rules(recipe: &mut Recipe, selectors: &RecipeCssSelectors) {
+    let fields = [(&mut recipe.title, &selectors.title), (&mut recipe.description, &selectors.description), (&mut recipe.image_link, &selectors.image)];
+    for (field, rules) in fields {
+        *field = field.as_deref().and_then(|value| rules.apply_rules(value)).filter(|s| !s.is_empty());
+    }
+    for (list, rules) in [(&mut recipe.ingredients, &selectors.ingredients), (&mut recipe.steps, &selectors.steps)] {
+        if let Some(list) = list {
+            *list = list.iter().filter_map(|value| rules.apply_rules(value)).filter(|s| !s.is_empty()).collect();
+        }
+    }
+}
+
+/// Concatenates all human-readable text fields of a recipe, for text analysis.
+fn recipe_text(recipe: &Recipe) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    parts.extend(recipe.title.as_deref());
+    parts.extend(recipe.description.as_deref());
+    for list in [&recipe.ingredients, &recipe.steps].into_iter().flatten() {
+        parts.extend(list.iter().map(String::as_str));
+    }
+    parts.join(" ")
+}
+
+/// Selects the elements of a field, recording the selector that matched under the field's name.
+fn select_field<'a>(document: &'a Html, name: &str, field: &FieldSelector, matched: &mut BTreeMap<String, String>) -> Vec<ElementRef<'a>> {
+    let Some((candidate, elements)) = field.select(document) else {
+        tracing::debug!(field = name, candidates = ?field.candidates, "no selector matched");
+        return Vec::new();
+    };
+    tracing::debug!(field = name, selector = candidate, elements = elements.len(), "selector matched");
+    matched.insert(name.to_string(), candidate.to_string());
+    elements
+}
+
+/// One entry per element, from its visible text; elements without text are dropped.
+fn element_texts(elements: &[ElementRef]) -> Option<Vec<String>> {
+    let texts: Vec<String> = elements
+        .iter()
+        .map(|element| text::collapse_whitespace(&visible_text(*element).concat()))
+        .filter(|text| !text.is_empty())
+        .collect();
+    (!elements.is_empty()).then_some(texts)
+}
+
+fn get_recipe_title(element: Option<ElementRef>) -> Option<String> {
+    let title = element.map(|e| e.text().collect::<String>());
+    tracing::debug!(field = "title", value = ?title, "extracted");
+    title
+}
+
+fn get_recipe_description(element: Option<ElementRef>) -> Option<String> {
+    let description = element.map(|e| e.text().collect::<Vec<_>>().join(" ").trim().to_string());
+    tracing::debug!(field = "description", value = ?description, "extracted");
+    description
+}
+
+/// The text nodes of an element, skipping scripts, styles and embedded players.
+fn visible_text(element: ElementRef<'_>) -> Vec<&str> {
+    const HIDDEN: [&str; 6] = ["script", "style", "noscript", "template", "iframe", "video"];
+    element
+        .descendants()
+        .filter(|node| {
+            node.ancestors()
+                .take_while(|ancestor| ancestor.id() != element.id())
+                .filter_map(ElementRef::wrap)
+                .all(|ancestor| !HIDDEN.contains(&ancestor.value().name()))
+        })
+        .filter_map(|node| node.value().as_text().map(|text| &**text))
+        .collect()
+}
+
+/// Reads one ingredient per text node, or one per `ingredient_item` match with its text nodes joined when set,
+/// along with the `ingredient_heading` section headings.
+fn get_recipe_ingredients(element: Option<ElementRef>, selectors: &RecipeCssSelectors) -> Option<Vec<String>> {
+    let item_selector = Selector::parse(&selectors.ingredient_item).ok();
+    let heading_selector = Selector::parse(&selectors.ingredient_heading).ok();
+    let joined = |element: ElementRef| visible_text(element).iter().map(|s| s.trim()).filter(|s| !s.is_empty()).collect::<Vec<_>>().join(" ");
+    let ingredients = element.map(|e| match &item_selector {
+        Some(item_selector) => e
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .filter_map(|element| {
+                if heading_selector.as_ref().is_some_and(|heading| heading.matches(&element)) {
+                    Some(format!("{}:", joined(element).trim_end_matches(':')))
+                } else {
+                    item_selector.matches(&element).then(|| joined(element))
+                }
+            })
+            .filter(|s| !s.is_empty() && s != ":")
+            .collect(),
+        None => visible_text(e).iter().map(|&s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+    });
+    tracing::debug!(field = "ingredients", value = ?ingredients, "extracted");
+    ingredients
+}
+
+fn get_recipe_steps(element: Option<ElementRef>) -> Option<Vec<String>> {
+    let steps = element.map(|e| visible_text(e).iter().map(|&s| s.trim().to_string()).filter(|s| !s.is_empty()).collect());
+    tracing::debug!(field = "steps", value = ?steps, "extracted");
+    steps
+}
+
+/// Reads the image link from the configured attributes (see `images::image_link_from`) as an absolute URL.
+fn get_recipe_image(element: Option<ElementRef>, attributes: &[String], page_url: &str) -> Option<String> {
+    let image_link = element
+        .and_then(|e| images::image_link_from(e, attributes))
+        .map(|link| images::resolve_link(&link, page_url));
+    tracing::debug!(field = "image", value = ?image_link, "extracted");
+    image_link
+}
+
+/// Parses the total time from an ISO 8601 duration or a written one such as `1 uur 10 minuten`.
+fn get_recipe_total_time(text: Option<String>) -> Option<u32> {
+    let total_time = text.and_then(|text| jsonld::parse_iso_duration(&text).or_else(|| steps::parse_duration_minutes(&text)));
+    tracing::debug!(field = "total_time", value = ?total_time, "extracted");
+    total_time
+}
+
+pub fn parse_website_name(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    url.host_str()?.split('.').next().map(|s| s.to_string())
+}
+
+/// The key of the site in a `selectors.toml` whose `domain` matches the host of the URL.
+///
+/// `domain` is a pattern or a list of patterns (see [`domain_matches`]). A site naming the host exactly
+/// wins over one that only matches through a wildcard.
+pub fn site_for_url(content: &str, url: &str) -> Option<String> {
+    let host = Url::parse(url).ok()?.host_str()?.to_lowercase();
+    let sites: toml::Table = toml::from_str(content).ok()?;
+    let patterns = |selectors: &Value| -> Vec<String> {
+        match selectors.get("domain") {
+            Some(Value::String(domain)) => vec![domain.to_lowercase()],
+            Some(Value::Array(domains)) => domains.iter().filter_map(Value::as_str).map(str::to_lowercase).collect(),
+            _ => Vec::new(),
+        }
+    };
+    let bare_host = host.strip_prefix("www.").unwrap_or(&host);
+    let exact = sites.iter().find(|(_, selectors)| patterns(selectors).iter().any(|pattern| pattern == bare_host));
+    let matching = exact.or_else(|| sites.iter().find(|(_, selectors)| patterns(selectors).iter().any(|pattern| domain_matches(pattern, &host))));
+    matching.map(|(site, _)| site.clone())
+}
+
+/// Matches a host against a domain pattern from the selectors file, case-insensitively:
+///
+/// - `15gram.be` matches that host, with or without a `www.` prefix;
+/// - `*.vrt.be` matches `vrt.be` and every subdomain of it;
+/// - `libelle-lekker.*` matches the domain under any top-level domain, e.g. `libelle-lekker.nl`.
+pub fn domain_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    if let Some(parent) = pattern.strip_prefix("*.") {
+        return host == parent || host.ends_with(&format!(".{}", parent));
+    }
+    if let Some(name) = pattern.strip_suffix(".*") {
+        return host.strip_prefix(name).and_then(|rest| rest.strip_prefix('.')).is_some_and(|tld| !tld.is_empty());
+    }
+    host == pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_website_name_valid() {
+        let url = "https://15gram.be/recipe";
+        let expected_name = Some("15gram".to_string());
+        assert_eq!(parse_website_name(url), expected_name);
+    }
+
+    #[test]
+    fn test_parse_website_name_invalid() {
+        let url = "invalid_url";
+        assert_eq!(parse_website_name(url), None);
+    }
+
+    #[test]
+    fn test_extract_recipe_sanitizes_title() {
+        let document = Html::parse_document("<h1 class=\"t\">Kip <span>met</span>\n  frietjes &amp; mayo</h1>");
+        let selectors = RecipeCssSelectors {
+            title: FieldSelector::from("h1.t"),
+            description: FieldSelector::default(),
+            ingredients: FieldSelector::default(),
+            ingredient_item: String::new(),
+            ingredient_heading: String::new(),
+            steps: FieldSelector::default(),
+            image: FieldSelector::default(),
+            image_attributes: Vec::new(),
+            total_time: FieldSelector::default(),
+            step_cleanup: StepCleanup::default(),
+            structured_data: false,
+            fallback: None,
+        };
+        let recipe = extract_recipe(&document, &selectors, "https://15gram.be/recipe");
+        assert_eq!(recipe.title, Some("Kip met frietjes & mayo".to_string()));
+        assert_eq!(recipe.matched_selectors.get("title").map(String::as_str), Some("h1.t"));
+        assert_eq!(recipe.matched_selectors.len(), 1);
+    }
+
+    #[test]
+    fn test_extract_recipe_select_all_keeps_one_entry_per_element() {
+        let document = Html::parse_document(
+            "<ol class=\"s\"><li>Snij de <b>ui</b> fijn.</li><li>Bak de ui <em>glazig</em>.</li></ol><div class=\"d\"><p>Snel.</p><p>Lekker.</p></div>",
+        );
+        let toml = "[s]\nsteps = { selector = \"ol.s li\", select_all = true }\ndescription = { selector = \".d p\", select_all = true }\n";
+        let selectors = RecipeCssSelectors::from_toml(toml, "s").unwrap();
+        let recipe = extract_recipe(&document, &selectors, "https://15gram.be/recipe");
+        assert_eq!(recipe.steps, Some(vec!["Snij de ui fijn.".to_string(), "Bak de ui glazig.".to_string()]));
+        assert_eq!(recipe.description.as_deref(), Some("Snel. Lekker."));
+    }
+
+    #[test]
+    fn test_extract_recipe_reads_configured_attributes() {
+        let document = Html::parse_document(
+            r#"<meta property="og:image" content="/img/soep.jpg"><meta name="description" content="Romige soep.">
+            <time class="t" datetime="PT1H10M">Een uur en tien minuten</time>"#,
+        );
+        let toml = r#"[s]
+            image = { selector = "meta[property='og:image']", attr = "content" }
+            description = { selector = "meta[name=description]", attr = "content" }
+            total_time = { selector = "time.t", attr = "datetime" }
+        "#;
+        let selectors = RecipeCssSelectors::from_toml(toml, "s").unwrap();
+        let recipe = extract_recipe(&document, &selectors, "https://15gram.be/recepten/soep");
+        assert_eq!(recipe.image_link.as_deref(), Some("https://15gram.be/img/soep.jpg"));
+        assert_eq!(recipe.description.as_deref(), Some("Romige soep."));
+        assert_eq!(recipe.total_time, Some(70));
+    }
+
+    #[test]
+    fn test_extract_recipe_applies_regex_rules() {
+        let document =
+            Html::parse_document("<h1>Recept: Pompoensoep</h1><ol><li>Bereiding: Snij de pompoen.</li></ol><p class=\"t\">Klaar in 25 min</p>");
+        let toml = r#"[s]
+            title = { selector = "h1", replace = [{ pattern = "^Recept:", replacement = "" }] }
+            steps = { selector = "ol li", select_all = true, replace = [{ pattern = "^Bereiding:\\s*", replacement = "" }] }
+            total_time = { selector = "p.t", capture = "(\\d+ min)" }
+        "#;
+        let selectors = RecipeCssSelectors::from_toml(toml, "s").unwrap();
+        let recipe = extract_recipe(&document, &selectors, "https://15gram.be/recepten/soep");
+        assert_eq!(recipe.title.as_deref(), Some("Pompoensoep"));
+        assert_eq!(recipe.steps, Some(vec!["Snij de pompoen.".to_string()]));
+        assert_eq!(recipe.total_time, Some(25));
+        assert!(RecipeCssSelectors::from_toml("[s]\ntitle = { selector = \"h1\", capture = \"(\" }\n", "s").is_err());
+    }
+
+    fn select<'a>(document: &'a Html, selector: &'a str) -> Option<ElementRef<'a>> {
+        document.select(&Selector::parse(selector).unwrap()).next()
+    }
+
+    #[test]
+    fn test_get_recipe_ingredients_joins_item_parts() {
+        let document = Html::parse_document(
+            "<ul class=\"i\"><li><span>200</span> <span>g</span> <span>courgette</span></li><li><span>1</span> <span>ui</span></li></ul>",
+        );
+        let mut selectors = RecipeCssSelectors::from_toml("[s]\ningredients = \"ul.i\"\n", "s").unwrap();
+        assert_eq!(get_recipe_ingredients(select(&document, "ul.i"), &selectors).map(|i| i.len()), Some(5));
+        selectors.ingredient_item = "li".to_string();
+        assert_eq!(get_recipe_ingredients(select(&document, "ul.i"), &selectors), Some(vec!["200 g courgette".to_string(), "1 ui".to_string()]));
+    }
+
+    #[test]
+    fn test_get_recipe_ingredients_keeps_section_headings() {
+        let document = Html::parse_document(
+            "<div class=\"i\"><h3>Voor de saus:</h3><ul><li>2 tomaten</li></ul><h3>Voor de pasta</h3><ul><li>400 g spaghetti</li></ul></div>",
+        );
+        let toml = "[s]\ningredients = \"div.i\"\ningredient_item = \"li\"\ningredient_heading = \"h3\"\n";
+        let selectors = RecipeCssSelectors::from_toml(toml, "s").unwrap();
+        let ingredients = get_recipe_ingredients(select(&document, "div.i"), &selectors).unwrap();
+        assert_eq!(ingredients, vec!["Voor de saus:", "2 tomaten", "Voor de pasta:", "400 g spaghetti"]);
+    }
+
+    #[test]
+    fn test_get_recipe_steps_skips_embedded_players() {
+        let document = Html::parse_document(
+            "<ol class=\"s\"><li>Kook de pasta.</li><div class=\"video\"><iframe src=\"https://www.youtube.com/embed/x\"></iframe><script>player.init({\"id\": 1})</script></div><li>Serveer.</li></ol>",
+        );
+        assert_eq!(get_recipe_steps(select(&document, "ol.s")), Some(vec!["Kook de pasta.".to_string(), "Serveer.".to_string()]));
+    }
+
+    #[test]
+    fn test_get_recipe_image_prefers_largest_srcset() {
+        let document = Html::parse_document(
+            "<img class=\"r\" src=\"/img/placeholder.gif\" data-srcset=\"/img/soep-400.jpg 400w, /img/soep-1200.jpg 1200w\">",
+        );
+        let attributes: Vec<String> = images::DEFAULT_IMAGE_ATTRIBUTES.iter().map(|a| a.to_string()).collect();
+        let image = get_recipe_image(select(&document, "img.r"), &attributes, "https://15gram.be/recepten/soep");
+        assert_eq!(image, Some("https://15gram.be/img/soep-1200.jpg".to_string()));
+    }
+
+    #[test]
+    fn test_site_for_url() {
+        let content = "[15gram]\ndomain = \"15gram.be\"\n\n[njam]\ndomain = \"njam.tv\"\n";
+        assert_eq!(site_for_url(content, "https://njam.tv/recepten/soep").as_deref(), Some("njam"));
+        assert_eq!(site_for_url(content, "https://15gram.be/recepten/soep").as_deref(), Some("15gram"));
+        assert_eq!(site_for_url(content, "https://example.com/15gram.be/"), None);
+        assert_eq!(site_for_url(content, "https://WWW.15gram.be/recepten/soep").as_deref(), Some("15gram"));
+
+        let content = "[vrt]\ndomain = \"*.vrt.be\"\n\n[dagelijksekost]\ndomain = [\"dagelijksekost.vrt.be\", \"dagelijksekost.be\"]\n";
+        assert_eq!(site_for_url(content, "https://dagelijksekost.vrt.be/gerechten").as_deref(), Some("dagelijksekost"));
+        assert_eq!(site_for_url(content, "https://dagelijksekost.be/gerechten").as_deref(), Some("dagelijksekost"));
+        assert_eq!(site_for_url(content, "https://www.vrt.be/nieuws").as_deref(), Some("vrt"));
+    }
+
+    #[test]
+    fn test_domain_matches() {
+        assert!(domain_matches("*.vrt.be", "dagelijksekost.vrt.be"));
+        assert!(domain_matches("*.vrt.be", "vrt.be"));
+        assert!(!domain_matches("*.vrt.be", "notvrt.be"));
+        assert!(domain_matches("libelle-lekker.*", "www.libelle-lekker.nl"));
+        assert!(!domain_matches("libelle-lekker.*", "libelle-lekker"));
+        assert!(domain_matches("15gram.be", "www.15gram.be"));
+        assert!(!domain_matches("15gram.be", "15gram.be.example.com"));
+    }
+
+    #[test]
+    fn test_selectors_from_toml() {
+        let content = "[15gram]\ntitle = \"h1\"\nimage = \"img.r\"\n";
+        let selectors = RecipeCssSelectors::from_toml(content, "15gram").unwrap();
+        assert_eq!(selectors.title.candidates, vec!["h1"]);
+        assert_eq!(selectors.image_attributes.first().map(String::as_str), Some("srcset"));
+        assert!(RecipeCssSelectors::from_toml(content, "njam").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Warnings about fields an extraction left empty, and why, instead of a silent `null` in the output.
+
+use crate::field::FieldSelector;
+use crate::{Recipe, RecipeCssSelectors};
+use serde::{Deserialize, Serialize};
+
+/// Why a field is empty.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// None of the field's selectors matched an element.
+    NoMatch,
+    /// A selector of the field is neither valid CSS nor valid XPath, so it was skipped.
+    InvalidSelector,
+    /// A selector matched, but no text was left after cleaning up.
+    EmptyText,
+}
+
+/// A problem with one field of an extracted recipe.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub field: String,
+    pub kind: WarningKind,
+    /// What happened, for people.
+    pub message: String,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// The report of one scrape, as written by `--report`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Report {
+    /// The URL that was scraped.
+    pub url: String,
+    pub confidence: Option<crate::confidence::Confidence>,
+    pub warnings: Vec<Warning>,
+}
+
+/// The warnings for a recipe extracted with the selectors. Fields the site has no selectors for are not reported.
+pub fn warnings(recipe: &Recipe, selectors: &RecipeCssSelectors) -> Vec<Warning> {
+    let fields: [(&str, &FieldSelector, bool); 6] = [
+        ("title", &selectors.title, recipe.title.is_some()),
+        ("description", &selectors.description, recipe.description.is_some()),
+        ("ingredients", &selectors.ingredients, recipe.ingredients.as_ref().is_some_and(|ingredients| !ingredients.is_empty())),
+        ("steps", &selectors.steps, recipe.steps.as_ref().is_some_and(|steps| !steps.is_empty())),
+        ("image", &selectors.image, recipe.image_link.is_some()),
+        ("total_time", &selectors.total_time, recipe.total_time.is_some()),
+    ];
+    let mut warnings = Vec::new();
+    for (field, selector, filled) in fields {
+        let mut warn = |kind, message| warnings.push(Warning { field: field.to_string(), kind, message });
+        for candidate in selector.invalid_candidates() {
+            warn(WarningKind::InvalidSelector, format!("selector `{}` is not valid CSS or XPath and was skipped", candidate));
+        }
+        match recipe.matched_selectors.get(field) {
+            _ if filled => {}
+            Some(matched) => warn(WarningKind::EmptyText, format!("`{}` matched, but no text was left", matched)),
+            None if !selector.candidates.is_empty() => {
+                let tried: Vec<_> = selector.candidates.iter().map(|candidate| format!("`{}`", candidate)).collect();
+                warn(WarningKind::NoMatch, format!("no selector matched (tried {})", tried.join(", ")))
+            }
+            None => {}
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract_recipe;
+    use scraper::Html;
+
+    #[test]
+    fn test_warnings() {
+        let selectors = RecipeCssSelectors::from_toml("[s]\ntitle = \"h1\"\ndescription = \"p.intro\"\nsteps = [\"ol.steps\", \"ol\"]\n", "s").unwrap();
+        let document = Html::parse_document("<h1>Soep</h1><p class=\"intro\">  </p>");
+        let recipe = extract_recipe(&document, &selectors, "https://example.com/soep");
+        let warnings = warnings(&recipe, &selectors);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].to_string(), "description: `p.intro` matched, but no text was left");
+        assert_eq!(warnings[0].kind, WarningKind::EmptyText);
+        assert_eq!(warnings[1].to_string(), "steps: no selector matched (tried `ol.steps`, `ol`)");
+
+        let mut selectors = selectors;
+        selectors.title = FieldSelector::from("h1..bad");
+        let warnings = super::warnings(&recipe, &selectors);
+        assert_eq!(warnings[0].kind, WarningKind::InvalidSelector);
+    }
+}
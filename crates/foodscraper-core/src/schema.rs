@@ -0,0 +1,331 @@
+//! The schema of `selectors.toml`, checked when the file is loaded so that a typo or a broken selector is reported
+//! where it is instead of silently leaving a field empty.
+//!
+//! The file may start with the schema `version` it is written for; files without one are read as version 1. Every
+//! other top-level key is a site table, which needs a `domain` and may only use the keys listed here.
+
+use crate::xpath::{self, XPath};
+use scraper::Selector;
+use std::fmt;
+use toml::Value;
+
+/// The newest schema version this build understands.
+pub const SCHEMA_VERSION: i64 = 1;
+
+/// What a value in the selectors file must be.
+enum Kind {
+    Bool,
+    Text,
+    /// A valid CSS selector.
+    Css,
+    /// A valid regex.
+    Regex,
+    /// A CSS selector, or an XPath expression when it starts with `/` or `./`.
+    Selector,
+    /// An XPath expression, relative to the document unless it starts with `/`.
+    XPath,
+    /// One value of the kind, or a list of them.
+    OneOrMany(&'static Kind),
+    /// A list of values of the kind.
+    List(&'static Kind),
+    /// A recipe field: a selector, a list of them, or a table of [`FIELD_OPTIONS`].
+    Field,
+    Table(&'static [Entry]),
+}
+
+/// A key of a table in the selectors file.
+struct Entry {
+    key: &'static str,
+    kind: Kind,
+    required: bool,
+}
+
+const fn optional(key: &'static str, kind: Kind) -> Entry {
+    Entry { key, kind, required: false }
+}
+
+const fn required(key: &'static str, kind: Kind) -> Entry {
+    Entry { key, kind, required: true }
+}
+
+const FIELD_OPTIONS: &[Entry] = &[
+    optional("selector", Kind::OneOrMany(&Kind::Selector)),
+    optional("xpath", Kind::OneOrMany(&Kind::XPath)),
+    optional("select_all", Kind::Bool),
+    optional("attr", Kind::Text),
+    optional("replace", Kind::List(&Kind::Table(&[required("pattern", Kind::Regex), optional("replacement", Kind::Text)]))),
+    optional("capture", Kind::Regex),
+];
+
+const STEP_CLEANUP: &[Entry] = &[
+    optional("enabled", Kind::Bool),
+    optional("strip_prefixes", Kind::Bool),
+    optional("merge_fragments", Kind::Bool),
+    optional("ignore", Kind::List(&Kind::Text)),
+];
+
+/// The fallback page takes the site's selectors, besides how to get to it.
+const FALLBACK: &[Entry] = &[
+    optional("amp", Kind::Bool),
+    optional("pattern", Kind::Regex),
+    optional("replacement", Kind::Text),
+    optional("title", Kind::Field),
+    optional("description", Kind::Field),
+    optional("ingredients", Kind::Field),
+    optional("ingredient_item", Kind::Css),
+    optional("ingredient_heading", Kind::Css),
+    optional("steps", Kind::Field),
+    optional("image", Kind::Field),
+    optional("image_attributes", Kind::List(&Kind::Text)),
+    optional("total_time", Kind::Field),
+    optional("step_cleanup", Kind::Table(STEP_CLEANUP)),
+    optional("structured_data", Kind::Bool),
+];
+
+const SITE: &[Entry] = &[
+    optional("domain", Kind::OneOrMany(&Kind::Text)),
+    optional("title", Kind::Field),
+    optional("description", Kind::Field),
+    optional("ingredients", Kind::Field),
+    optional("ingredient_item", Kind::Css),
+    optional("ingredient_heading", Kind::Css),
+    optional("steps", Kind::Field),
+    optional("image", Kind::Field),
+    optional("image_attributes", Kind::List(&Kind::Text)),
+    optional("total_time", Kind::Field),
+    optional("step_cleanup", Kind::Table(STEP_CLEANUP)),
+    optional("structured_data", Kind::Bool),
+    optional("fallback", Kind::Table(FALLBACK)),
+    optional(
+        "crawl",
+        Kind::Table(&[
+            optional("recipe_urls", Kind::List(&Kind::Regex)),
+            optional("recipe_link", Kind::Css),
+            optional("next_page", Kind::Css),
+        ]),
+    ),
+    optional("search", Kind::Table(&[required("url", Kind::Text), required("result", Kind::Css)])),
+    optional("test_urls", Kind::List(&Kind::Text)),
+];
+
+impl Kind {
+    fn describe(&self) -> String {
+        match self {
+            Kind::Bool => "a boolean".to_string(),
+            Kind::Text => "a string".to_string(),
+            Kind::Css => "a CSS selector".to_string(),
+            Kind::Regex => "a regex".to_string(),
+            Kind::Selector => "a CSS selector or XPath expression".to_string(),
+            Kind::XPath => "an XPath expression".to_string(),
+            Kind::OneOrMany(kind) => format!("{} or a list of them", kind.describe()),
+            Kind::List(kind) => format!("a list of {}", kind.describe().trim_start_matches("a ").trim_start_matches("an ")),
+            Kind::Field => "a selector, a list of selectors or a table of options".to_string(),
+            Kind::Table(_) => "a table".to_string(),
+        }
+    }
+
+    /// Checks the value at `path`, adding what is wrong with it to `errors`.
+    fn check(&self, path: &str, value: &Value, errors: &mut Vec<SchemaError>) {
+        let mut error = |message: String| errors.push(SchemaError { path: path.to_string(), message });
+        match (self, value) {
+            (Kind::Bool, Value::Boolean(_)) | (Kind::Text, Value::String(_)) => {}
+            (Kind::Css, Value::String(selector)) => {
+                // scraper's parse errors are debug dumps of the tokenizer state, so only the selector is reported.
+                if Selector::parse(selector).is_err() {
+                    error(format!("invalid CSS selector `{}`", selector));
+                }
+            }
+            (Kind::Regex, Value::String(pattern)) => {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    error(format!("invalid regex `{}`: {}", pattern, e));
+                }
+            }
+            (Kind::Selector, Value::String(selector)) if xpath::looks_like_xpath(selector) => Kind::XPath.check(path, value, errors),
+            (Kind::Selector, Value::String(_)) => Kind::Css.check(path, value, errors),
+            (Kind::XPath, Value::String(expression)) => {
+                let absolute = if xpath::looks_like_xpath(expression) { expression.clone() } else { format!("/{}", expression) };
+                if let Err(e) = XPath::parse(&absolute) {
+                    error(format!("invalid XPath expression `{}`: {}", expression, e));
+                }
+            }
+            (Kind::OneOrMany(kind), Value::Array(_)) => Kind::List(kind).check(path, value, errors),
+            (Kind::OneOrMany(kind), value) => kind.check(path, value, errors),
+            (Kind::List(kind), Value::Array(items)) => {
+                for (i, item) in items.iter().enumerate() {
+                    kind.check(&format!("{}[{}]", path, i), item, errors);
+                }
+            }
+            (Kind::Field, Value::String(_) | Value::Array(_)) => Kind::OneOrMany(&Kind::Selector).check(path, value, errors),
+            (Kind::Field, Value::Table(options)) => {
+                if !options.contains_key("selector") && !options.contains_key("xpath") {
+                    error("missing required key `selector` or `xpath`".to_string());
+                }
+                Kind::Table(FIELD_OPTIONS).check(path, value, errors)
+            }
+            (Kind::Table(entries), Value::Table(table)) => check_table(path, table, entries, errors),
+            (kind, value) => error(format!("expected {}, found {}", kind.describe(), value.type_str())),
+        }
+    }
+}
+
+fn check_table(path: &str, table: &toml::Table, entries: &[Entry], errors: &mut Vec<SchemaError>) {
+    for (key, value) in table {
+        let key_path = format!("{}.{}", path, key);
+        match entries.iter().find(|entry| entry.key == key) {
+            Some(entry) => entry.kind.check(&key_path, value, errors),
+            None => {
+                let keys: Vec<_> = entries.iter().map(|entry| entry.key).collect();
+                errors.push(SchemaError { path: key_path, message: format!("unknown key; expected one of {}", keys.join(", ")) });
+            }
+        }
+    }
+    for entry in entries.iter().filter(|entry| entry.required && !table.contains_key(entry.key)) {
+        errors.push(SchemaError { path: path.to_string(), message: format!("missing required key `{}`", entry.key) });
+    }
+}
+
+/// A problem with one entry of a selectors file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    /// Where the entry is, e.g. `15gram.steps.replace[0].pattern`.
+    pub path: String,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Every problem found in a selectors file.
+#[derive(Debug)]
+pub struct InvalidSelectors(pub Vec<SchemaError>);
+
+impl fmt::Display for InvalidSelectors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let errors: Vec<_> = self.0.iter().map(SchemaError::to_string).collect();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+impl std::error::Error for InvalidSelectors {}
+
+/// Checks the table of one site, without requiring its `domain`, so partial sites such as overrides pass.
+pub fn validate_site(name: &str, site: &Value) -> Result<(), InvalidSelectors> {
+    let mut errors = Vec::new();
+    Kind::Table(SITE).check(name, site, &mut errors);
+    if errors.is_empty() { Ok(()) } else { Err(InvalidSelectors(errors)) }
+}
+
+/// Checks the contents of a complete selectors file: its version, and every site with its `domain`.
+pub fn validate(content: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let sites: toml::Table = toml::from_str(content)?;
+    let mut errors = Vec::new();
+    for (name, site) in &sites {
+        if name == "version" {
+            let message = match site.as_integer() {
+                Some(1..=SCHEMA_VERSION) => continue,
+                Some(version) if version > SCHEMA_VERSION => {
+                    format!("version {} is newer than this FoodScraper supports ({}); update FoodScraper", version, SCHEMA_VERSION)
+                }
+                _ => format!("expected a positive integer, found {}", site),
+            };
+            errors.push(SchemaError { path: name.clone(), message });
+            continue;
+        }
+        Kind::Table(SITE).check(name, site, &mut errors);
+        if site.is_table() && site.get("domain").is_none() {
+            errors.push(SchemaError { path: name.clone(), message: "missing required key `domain`".to_string() });
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(Box::new(InvalidSelectors(errors))) }
+}
+
+/// The names of the sites in a selectors file's top-level table, leaving out its `version`.
+pub fn site_names(sites: &toml::Table) -> impl Iterator<Item = &String> {
+    sites.keys().filter(|name| name.as_str() != "version")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(content: &str) -> Vec<String> {
+        match validate(content) {
+            Ok(()) => Vec::new(),
+            Err(e) => e.downcast::<InvalidSelectors>().unwrap().0.iter().map(SchemaError::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_every_option() {
+        let content = r##"
+            version = 1
+
+            [15gram]
+            domain = ["15gram.be", "15gram.nl"]
+            title = ["h1.recipe-title", "h1"]
+            ingredients = { xpath = "//h2[contains(., 'Ingrediënten')]/following-sibling::ul[1]/li", select_all = true }
+            ingredient_item = "li"
+            steps = { selector = "#preparation li", select_all = true, replace = [{ pattern = "^Bereiding:\\s*", replacement = "" }] }
+            image = { selector = "meta[property='og:image']", attr = "content" }
+            total_time = { selector = ".recipe-meta", capture = "(\\d+ min)" }
+            structured_data = false
+
+            [15gram.step_cleanup]
+            ignore = ["tip"]
+
+            [15gram.fallback]
+            pattern = "^([^?#]*).*$"
+            replacement = "$1?print=1"
+            ingredients = ".print-ingredients li"
+
+            [15gram.crawl]
+            recipe_urls = ["^https://15gram\\.be/recepten/"]
+
+            [15gram.search]
+            url = "https://15gram.be/zoeken?q={query}"
+            result = ".search-result a"
+        "##;
+        assert_eq!(errors(content), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_validate_reports_each_problem() {
+        let content = r##"
+            [15gram]
+            domain = "15gram.be"
+            titel = "h1"
+            ingredients = "ul..bad"
+            steps = { selector = "ol", select_all = "yes", replace = [{ replacement = "" }] }
+            total_time = { selector = "p", capture = "(" }
+
+            [15gram.search]
+            url = "https://15gram.be/zoeken?q={query}"
+
+            [njam]
+            title = 1
+        "##;
+        let errors = errors(content);
+        assert!(errors.iter().any(|e| e.starts_with("15gram.titel: unknown key; expected one of domain, title,")));
+        assert!(errors.iter().any(|e| e == "15gram.ingredients: invalid CSS selector `ul..bad`"));
+        assert!(errors.contains(&"15gram.steps.select_all: expected a boolean, found string".to_string()));
+        assert!(errors.contains(&"15gram.steps.replace[0]: missing required key `pattern`".to_string()));
+        assert!(errors.iter().any(|e| e.starts_with("15gram.total_time.capture: invalid regex `(`")));
+        assert!(errors.contains(&"15gram.search: missing required key `result`".to_string()));
+        assert!(errors.contains(&"njam.title: expected a selector, a list of selectors or a table of options, found integer".to_string()));
+        assert!(errors.contains(&"njam: missing required key `domain`".to_string()));
+        assert_eq!(errors.len(), 8);
+    }
+
+    #[test]
+    fn test_validate_version() {
+        assert!(validate("version = 1\n[s]\ndomain = \"s.be\"\n").is_ok());
+        assert!(errors("version = 2\n").iter().any(|e| e.contains("version 2 is newer")));
+        assert!(errors("version = \"1\"\n").iter().any(|e| e.starts_with("version: expected a positive integer")));
+        assert!(validate_site("s", &toml::from_str::<Value>("title = \"h1\"").unwrap()).is_ok());
+        assert!(validate("not toml").is_err());
+    }
+}
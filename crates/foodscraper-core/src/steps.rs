@@ -0,0 +1,257 @@
+//! Cleanup pass for extracted instruction steps.
+//!
+//! Steps are taken from the text nodes of the steps container, so they often carry numbering
+//! ("Stap 1:"), stray advertising lines, or are split in pieces wherever the site uses inline
+//! markup. This module turns that into one clean entry per actual step.
+
+use crate::text::collapse_whitespace;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use toml::Value;
+
+/// Lines containing any of these (case-insensitive) are dropped unless a site overrides the list.
+const DEFAULT_IGNORED: &[&str] = &["advertentie", "advertisement", "lees ook", "lees meer", "bekijk ook", "publicité"];
+
+/// Matches step numbering such as `Stap 1:`, `Step 2 -`, `3.` or `4)` at the start of a line.
+static STEP_PREFIX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:(?:stap|step|étape|etape)\s*\d+\s*[.:)\-–]?|\d+\s*[.:)])(?:\s+|$)").unwrap()
+});
+
+/// Matches numeric durations such as `20 minuten`, `1,5 uur`, `10-15 min.` or `2 hours`.
+static NUMERIC_DURATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(\d+(?:[.,]\d+)?)(?:\s*(?:-|–|à|a|tot|to|of|or)\s*(\d+(?:[.,]\d+)?))?\s*(seconden|secondes|seconde|seconds|second|sec|minuutjes|minuutje|minuten|minuut|minutes|minute|mins|min|uren|uur|heures|heure|hours|hour|hrs|hr|h)\b",
+    )
+    .unwrap()
+});
+
+/// Matches spelled-out durations that carry no digits.
+static WORD_DURATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(anderhalf uur|een half ?uur|half ?uur|een kwartier|kwartier|een uur|één uur|une heure|an hour|half an hour)\b")
+        .unwrap()
+});
+
+/// A duration mentioned in an instruction step, usable as a cooking timer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepTimer {
+    /// Zero-based index of the step in the recipe's `steps` list.
+    pub step: usize,
+    /// The duration as written in the step, e.g. `20 minuten`.
+    pub text: String,
+    /// The duration in seconds (the lower bound for ranges such as `10-15 min`).
+    pub seconds: u64,
+    /// The upper bound in seconds when the step gives a range.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_seconds: Option<u64>,
+}
+
+/// Per-site configuration of the step cleanup pass, read from the `step_cleanup` table in selectors.toml.
+#[derive(Debug, Clone)]
+pub struct StepCleanup {
+    /// Whether the cleanup pass runs at all.
+    pub enabled: bool,
+    /// Strip leading step numbering.
+    pub strip_prefixes: bool,
+    /// Merge fragments that continue the previous sentence into one step.
+    pub merge_fragments: bool,
+    /// Case-insensitive substrings marking lines to drop (advertising, cross-links, ...).
+    pub ignore: Vec<String>,
+}
+
+impl Default for StepCleanup {
+    fn default() -> Self {
+        StepCleanup {
+            enabled: true,
+            strip_prefixes: true,
+            merge_fragments: true,
+            ignore: DEFAULT_IGNORED.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl StepCleanup {
+    /// Builds the configuration from an optional `step_cleanup` TOML table, falling back to the defaults.
+    pub fn from_toml(table: Option<&Value>) -> Self {
+        let defaults = StepCleanup::default();
+        let Some(table) = table else {
+            return defaults;
+        };
+        let flag = |key: &str, default: bool| table.get(key).and_then(Value::as_bool).unwrap_or(default);
+        StepCleanup {
+            enabled: flag("enabled", defaults.enabled),
+            strip_prefixes: flag("strip_prefixes", defaults.strip_prefixes),
+            merge_fragments: flag("merge_fragments", defaults.merge_fragments),
+            ignore: table
+                .get("ignore")
+                .and_then(Value::as_array)
+                .map(|list| list.iter().filter_map(Value::as_str).map(str::to_lowercase).collect())
+                .unwrap_or(defaults.ignore),
+        }
+    }
+}
+
+/// Cleans the raw step fragments according to the given options.
+pub fn clean_steps(steps: &[String], options: &StepCleanup) -> Vec<String> {
+    if !options.enabled {
+        return steps.to_vec();
+    }
+
+    let lines = steps.iter().map(|step| collapse_whitespace(step)).filter_map(|line| {
+        let line = if options.strip_prefixes { STEP_PREFIX.replace(&line, "").trim().to_string() } else { line };
+        let lowercase = line.to_lowercase();
+        let ignored = options.ignore.iter().any(|pattern| lowercase.contains(pattern.as_str()));
+        (!line.is_empty() && !ignored).then_some(line)
+    });
+
+    let mut cleaned: Vec<String> = Vec::new();
+    for line in lines {
+        match cleaned.last_mut() {
+            Some(previous) if options.merge_fragments && continues_sentence(previous, &line) => {
+                previous.push(' ');
+                previous.push_str(&line);
+            }
+            _ => cleaned.push(line),
+        }
+    }
+    cleaned
+}
+
+/// A fragment continues the previous one when that one has no closing punctuation and the fragment
+/// does not start a new sentence.
+fn continues_sentence(previous: &str, fragment: &str) -> bool {
+    let previous_closed = previous.ends_with(['.', '!', '?', '…', ':', ')', '"']);
+    let starts_sentence = fragment.chars().next().is_some_and(char::is_uppercase);
+    !previous_closed && !starts_sentence
+}
+
+/// Finds all durations mentioned in the given steps, in step order.
+pub fn extract_timers(steps: &[String]) -> Vec<StepTimer> {
+    let mut timers = Vec::new();
+    for (index, step) in steps.iter().enumerate() {
+        let mut found: Vec<(usize, StepTimer)> = Vec::new();
+        for captures in NUMERIC_DURATION.captures_iter(step) {
+            let whole = captures.get(0).unwrap();
+            let unit = unit_seconds(&captures[3]);
+            let seconds = |amount: &str| (amount.replace(',', ".").parse::<f64>().unwrap_or(0.0) * unit as f64).round() as u64;
+            found.push((
+                whole.start(),
+                StepTimer {
+                    step: index,
+                    text: whole.as_str().to_string(),
+                    seconds: seconds(&captures[1]),
+                    max_seconds: captures.get(2).map(|upper| seconds(upper.as_str())),
+                },
+            ));
+        }
+        for phrase in WORD_DURATION.find_iter(step) {
+            let seconds = match phrase.as_str().to_lowercase().as_str() {
+                "anderhalf uur" => 5400,
+                p if p.contains("half") => 1800,
+                p if p.contains("kwartier") => 900,
+                _ => 3600,
+            };
+            let timer = StepTimer { step: index, text: phrase.as_str().to_string(), seconds, max_seconds: None };
+            found.push((phrase.start(), timer));
+        }
+        found.sort_by_key(|(position, _)| *position);
+        timers.extend(found.into_iter().map(|(_, timer)| timer));
+    }
+    timers
+}
+
+/// Parses a duration text such as `45 min` or `1 uur 15 minuten` into whole minutes, summing its parts.
+pub fn parse_duration_minutes(text: &str) -> Option<u32> {
+    estimated_minutes(&extract_timers(&[text.to_string()]))
+}
+
+/// Estimates the time a recipe takes by adding up the timers of its steps, in whole minutes.
+pub fn estimated_minutes(timers: &[StepTimer]) -> Option<u32> {
+    let seconds: u64 = timers.iter().map(|timer| timer.max_seconds.unwrap_or(timer.seconds)).sum();
+    (!timers.is_empty()).then(|| seconds.div_ceil(60) as u32)
+}
+
+/// Number of seconds in one of the duration units recognised by [`NUMERIC_DURATION`].
+fn unit_seconds(unit: &str) -> u64 {
+    match unit.to_lowercase().as_str() {
+        u if u.starts_with("sec") => 1,
+        u if u.starts_with("min") => 60,
+        _ => 3600,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_clean_steps_strips_prefixes() {
+        let steps = strings(&["Stap 1: Snij de ui.", "2. Bak de ui.", "3) Serveer."]);
+        assert_eq!(clean_steps(&steps, &StepCleanup::default()), strings(&["Snij de ui.", "Bak de ui.", "Serveer."]));
+    }
+
+    #[test]
+    fn test_clean_steps_keeps_leading_quantities() {
+        let steps = strings(&["2 eieren kloppen.", "1.5 kg aardappelen schillen."]);
+        assert_eq!(clean_steps(&steps, &StepCleanup::default()), steps);
+    }
+
+    #[test]
+    fn test_clean_steps_drops_empty_and_ignored_lines() {
+        let steps = strings(&["Stap 1", "Snij de ui.", "Advertentie", "Lees ook: onze beste soepen"]);
+        assert_eq!(clean_steps(&steps, &StepCleanup::default()), strings(&["Snij de ui."]));
+    }
+
+    #[test]
+    fn test_clean_steps_merges_wrapped_fragments() {
+        let steps = strings(&["Verwarm de oven op", "200 ºC", "en bak 20 min.", "Serveer   warm."]);
+        assert_eq!(
+            clean_steps(&steps, &StepCleanup::default()),
+            strings(&["Verwarm de oven op 200 ºC en bak 20 min.", "Serveer warm."])
+        );
+    }
+
+    #[test]
+    fn test_step_cleanup_from_toml() {
+        let table: Value = toml::from_str("merge_fragments = false\nignore = [\"Tip\"]").unwrap();
+        let options = StepCleanup::from_toml(Some(&table));
+        assert!(options.strip_prefixes);
+        assert!(!options.merge_fragments);
+        assert_eq!(options.ignore, vec!["tip".to_string()]);
+    }
+
+    #[test]
+    fn test_extract_timers_numeric() {
+        let steps = strings(&["Snij de ui.", "Laat 20 minuten sudderen en bak daarna 1,5 uur in de oven."]);
+        let timers = extract_timers(&steps);
+        assert_eq!(timers.len(), 2);
+        assert_eq!(timers[0], StepTimer { step: 1, text: "20 minuten".to_string(), seconds: 1200, max_seconds: None });
+        assert_eq!(timers[1].seconds, 5400);
+    }
+
+    #[test]
+    fn test_extract_timers_range_and_words() {
+        let steps = strings(&["Bak 10-15 min. op 200 ºC.", "Laat een kwartier rusten."]);
+        let timers = extract_timers(&steps);
+        assert_eq!(timers[0].seconds, 600);
+        assert_eq!(timers[0].max_seconds, Some(900));
+        assert_eq!(timers[1], StepTimer { step: 1, text: "een kwartier".to_string(), seconds: 900, max_seconds: None });
+    }
+
+    #[test]
+    fn test_extract_timers_ignores_temperatures_and_quantities() {
+        let steps = strings(&["Verwarm de oven op 200 ºC en gebruik 4 eieren."]);
+        assert!(extract_timers(&steps).is_empty());
+    }
+
+    #[test]
+    fn test_parse_duration_minutes() {
+        assert_eq!(parse_duration_minutes("45 min"), Some(45));
+        assert_eq!(parse_duration_minutes("1 uur 15 minuten"), Some(75));
+        assert_eq!(parse_duration_minutes("snel klaar"), None);
+    }
+}
@@ -0,0 +1,41 @@
+//! Text sanitization for values extracted from recipe pages.
+
+use scraper::Html;
+
+/// Cleans a scraped string: strips HTML tags, decodes entities and collapses whitespace.
+///
+/// Values taken from `inner_html()` or from CMS fields that double-escape their content can
+/// contain markup such as `<span>` or `&amp;`; this reduces them to the plain text a reader sees.
+pub fn sanitize_text(raw: &str) -> String {
+    let decoded = if raw.contains('<') || raw.contains('&') {
+        Html::parse_fragment(raw).root_element().text().collect::<String>()
+    } else {
+        raw.to_string()
+    };
+    collapse_whitespace(&decoded)
+}
+
+/// Replaces every run of whitespace (including non-breaking spaces) with a single space and trims the ends.
+pub fn collapse_whitespace(text: &str) -> String {
+    text.split(|c: char| c.is_whitespace() || c == '\u{a0}').filter(|s| !s.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_text_strips_tags_and_entities() {
+        assert_eq!(sanitize_text("Kip <span class=\"x\">met</span> frietjes &amp; mayo"), "Kip met frietjes & mayo");
+    }
+
+    #[test]
+    fn test_sanitize_text_collapses_whitespace() {
+        assert_eq!(sanitize_text("  Pizza\n\t uit de\u{a0}airfryer  "), "Pizza uit de airfryer");
+    }
+
+    #[test]
+    fn test_sanitize_text_plain_text_unchanged() {
+        assert_eq!(sanitize_text("Luikse balletjes"), "Luikse balletjes");
+    }
+}
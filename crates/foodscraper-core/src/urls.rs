@@ -0,0 +1,78 @@
+//! Normalizing recipe URLs, so the same recipe shared through different links is stored once.
+
+use scraper::{Html, Selector};
+use std::sync::LazyLock;
+use url::Url;
+
+/// Query parameters added by newsletters and social networks to track where a click came from.
+const TRACKING_PARAMETERS: &[&str] = &["fbclid", "gclid", "dclid", "msclkid", "igshid", "mc_cid", "mc_eid", "_ga"];
+
+/// Normalizes a URL: lowercases the host, and drops the fragment and `utm_*`/`fbclid`-style tracking parameters.
+///
+/// Unparseable input is returned trimmed but otherwise unchanged.
+pub fn normalize_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url.trim()) else {
+        return url.trim().to_string();
+    };
+    if let Some(host) = parsed.host_str().map(str::to_lowercase) {
+        let _ = parsed.set_host(Some(&host));
+    }
+    parsed.set_fragment(None);
+    let kept: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| !is_tracking_parameter(key))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        parsed.set_query(None);
+    } else if kept.len() != parsed.query_pairs().count() {
+        parsed.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    parsed.to_string()
+}
+
+fn is_tracking_parameter(key: &str) -> bool {
+    let key = key.to_lowercase();
+    key.starts_with("utm_") || TRACKING_PARAMETERS.contains(&key.as_str())
+}
+
+/// The URL the page declares as its canonical address with `<link rel="canonical">`, normalized.
+///
+/// Only a canonical URL on the same host as the page (ignoring `www.`) is returned, so a syndicated copy
+/// is not attributed to a site whose selectors do not apply to it.
+pub fn canonical_link(document: &Html, page_url: &str) -> Option<String> {
+    static CANONICAL: LazyLock<Selector> = LazyLock::new(|| Selector::parse(r#"link[rel~="canonical"][href]"#).unwrap());
+
+    let page = Url::parse(page_url).ok()?;
+    let href = document.select(&CANONICAL).next()?.value().attr("href")?;
+    let canonical = page.join(href.trim()).ok()?;
+    let bare = |url: &Url| url.host_str().map(|host| host.to_lowercase().trim_start_matches("www.").to_string());
+    (matches!(canonical.scheme(), "http" | "https") && bare(&canonical) == bare(&page)).then(|| normalize_url(canonical.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("https://15Gram.BE/recepten/soep?utm_source=facebook&utm_medium=social&fbclid=abc#bereiding"),
+            "https://15gram.be/recepten/soep"
+        );
+        assert_eq!(normalize_url("https://15gram.be/zoeken?q=soep&utm_campaign=x"), "https://15gram.be/zoeken?q=soep");
+        assert_eq!(normalize_url("https://15gram.be/zoeken?q=soep"), "https://15gram.be/zoeken?q=soep");
+        assert_eq!(normalize_url(" not a url "), "not a url");
+    }
+
+    #[test]
+    fn test_canonical_link() {
+        let document = Html::parse_document(r#"<head><link rel="canonical" href="/recepten/soep?utm_source=rss"></head>"#);
+        assert_eq!(
+            canonical_link(&document, "https://www.15gram.be/r/123").as_deref(),
+            Some("https://www.15gram.be/recepten/soep")
+        );
+        let syndicated = Html::parse_document(r#"<link rel="canonical" href="https://example.com/soep">"#);
+        assert_eq!(canonical_link(&syndicated, "https://15gram.be/recepten/soep"), None);
+    }
+}
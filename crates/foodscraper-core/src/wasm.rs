@@ -0,0 +1,20 @@
+//! The JavaScript interface of the WebAssembly build.
+//!
+//! ```js
+//! import init, { extractRecipe } from "./pkg/foodscraper_core.js";
+//! await init();
+//! const recipe = extractRecipe(html, "https://15gram.be/recepten/...", selectorsToml);
+//! ```
+
+use crate::RecipeCssSelectors;
+use scraper::Html;
+use wasm_bindgen::prelude::*;
+
+/// Extracts the recipe from already fetched `html`, using the selectors `selectors_toml` (the contents of
+/// `selectors.toml`) configures for the site `url` belongs to. Returns an object shaped like the recipe JSON.
+#[wasm_bindgen(js_name = extractRecipe)]
+pub fn extract_recipe(html: &str, url: &str, selectors_toml: &str) -> Result<JsValue, JsError> {
+    let selectors = RecipeCssSelectors::for_url(selectors_toml, url).map_err(|e| JsError::new(&e.to_string()))?;
+    let recipe = crate::extract_recipe(&Html::parse_document(html), &selectors, url);
+    Ok(serde_wasm_bindgen::to_value(&recipe)?)
+}
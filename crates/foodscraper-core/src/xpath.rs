@@ -0,0 +1,562 @@
+//! A small XPath 1.0 subset, for recipe fields that are awkward or impossible to select with CSS, such as "the list
+//! after the heading that says Ingrediënten":
+//!
+//! ```text
+//! //h2[contains(., 'Ingrediënten')]/following-sibling::ul[1]
+//! ```
+//!
+//! Supported are absolute and relative location paths with `/` and `//`; the `child`, `descendant`,
+//! `descendant-or-self`, `self`, `parent`, `ancestor`, `following-sibling` and `preceding-sibling` axes, with `.`
+//! and `..`; name and `*` tests; and predicates built from positions, `last()`, `@attr`, `text()`, string and
+//! number literals, `=`, `!=`, `and`, `or`, and the `contains`, `starts-with`, `normalize-space` and `not`
+//! functions. Expressions select elements only.
+
+use scraper::{ElementRef, Html};
+use std::collections::HashSet;
+
+/// A parsed XPath expression.
+#[derive(Debug, Clone)]
+pub struct XPath {
+    path: Path,
+}
+
+/// Whether a field selector is an XPath expression rather than CSS: it starts with `/` or `./`, which no CSS
+/// selector does.
+pub fn looks_like_xpath(selector: &str) -> bool {
+    let selector = selector.trim_start();
+    selector.starts_with('/') || selector.starts_with("./")
+}
+
+impl XPath {
+    /// Parses an expression, failing with a message on syntax outside the supported subset.
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let path = parser.path()?;
+        match parser.peek() {
+            None => Ok(XPath { path }),
+            Some(token) => Err(format!("Unexpected {:?} in XPath '{}'.", token, expression)),
+        }
+    }
+
+    /// The elements the expression selects in the document, in document order.
+    pub fn select<'a>(&self, document: &'a Html) -> Vec<ElementRef<'a>> {
+        let context = Context { document };
+        match context.path(&self.path, &[document.root_element()], true) {
+            Nodes::Elements(elements) => elements,
+            Nodes::Strings(_) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Slash,
+    DoubleSlash,
+    OpenBracket,
+    CloseBracket,
+    OpenParen,
+    CloseParen,
+    At,
+    Comma,
+    Equals,
+    NotEquals,
+    Axis(String),
+    Dot,
+    DoubleDot,
+    Star,
+    Name(String),
+    Literal(String),
+    Number(f64),
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        i += 1;
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '/' if next == Some('/') => {
+                i += 1;
+                Token::DoubleSlash
+            }
+            '/' => Token::Slash,
+            '[' => Token::OpenBracket,
+            ']' => Token::CloseBracket,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            '=' => Token::Equals,
+            '!' if next == Some('=') => {
+                i += 1;
+                Token::NotEquals
+            }
+            '*' => Token::Star,
+            '.' if next == Some('.') => {
+                i += 1;
+                Token::DoubleDot
+            }
+            '.' if !next.is_some_and(|c| c.is_ascii_digit()) => Token::Dot,
+            '\'' | '"' => {
+                let end = chars[i..].iter().position(|&q| q == c).ok_or_else(|| format!("Unterminated string in XPath '{}'.", expression))?;
+                let literal: String = chars[i..i + end].iter().collect();
+                i += end + 1;
+                Token::Literal(literal)
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i - 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let number: String = chars[start..i].iter().collect();
+                Token::Number(number.parse().map_err(|_| format!("Invalid number '{}' in XPath '{}'.", number, expression))?)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i - 1;
+                while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '-' | '_' | '.')) {
+                    i += 1;
+                }
+                let name: String = chars[start..i].iter().collect();
+                if chars.get(i) == Some(&':') && chars.get(i + 1) == Some(&':') {
+                    i += 2;
+                    Token::Axis(name)
+                } else {
+                    Token::Name(name)
+                }
+            }
+            c => return Err(format!("Unexpected '{}' in XPath '{}'.", c, expression)),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+struct Path {
+    absolute: bool,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    axis: Axis,
+    test: NodeTest,
+    predicates: Vec<Expr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Axis {
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Itself,
+    Parent,
+    Ancestor,
+    FollowingSibling,
+    PrecedingSibling,
+    Attribute,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NodeTest {
+    Name(String),
+    Any,
+    Text,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, Box<Expr>, bool),
+    Literal(String),
+    Number(f64),
+    Call(String, Vec<Expr>),
+    Path(Path),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            token => Err(format!("Expected {:?} in XPath, found {:?}.", expected, token)),
+        }
+    }
+
+    fn path(&mut self) -> Result<Path, String> {
+        let mut steps = Vec::new();
+        let absolute = matches!(self.peek(), Some(Token::Slash | Token::DoubleSlash));
+        if !absolute {
+            steps.push(self.step()?);
+        }
+        loop {
+            match self.peek() {
+                Some(Token::Slash) => {
+                    self.next();
+                }
+                Some(Token::DoubleSlash) => {
+                    self.next();
+                    steps.push(Step { axis: Axis::DescendantOrSelf, test: NodeTest::Any, predicates: Vec::new() });
+                }
+                _ => break,
+            }
+            steps.push(self.step()?);
+        }
+        Ok(Path { absolute, steps })
+    }
+
+    fn step(&mut self) -> Result<Step, String> {
+        let axis = match self.peek() {
+            Some(Token::Dot) => {
+                self.next();
+                return Ok(Step { axis: Axis::Itself, test: NodeTest::Any, predicates: Vec::new() });
+            }
+            Some(Token::DoubleDot) => {
+                self.next();
+                return Ok(Step { axis: Axis::Parent, test: NodeTest::Any, predicates: Vec::new() });
+            }
+            Some(Token::At) => {
+                self.next();
+                Axis::Attribute
+            }
+            Some(Token::Axis(name)) => {
+                let axis = match name.as_str() {
+                    "child" => Axis::Child,
+                    "descendant" => Axis::Descendant,
+                    "descendant-or-self" => Axis::DescendantOrSelf,
+                    "self" => Axis::Itself,
+                    "parent" => Axis::Parent,
+                    "ancestor" => Axis::Ancestor,
+                    "following-sibling" => Axis::FollowingSibling,
+                    "preceding-sibling" => Axis::PrecedingSibling,
+                    "attribute" => Axis::Attribute,
+                    axis => return Err(format!("Unsupported XPath axis '{}'.", axis)),
+                };
+                self.next();
+                axis
+            }
+            _ => Axis::Child,
+        };
+        let test = match self.next() {
+            Some(Token::Star) => NodeTest::Any,
+            Some(Token::Name(name)) if matches!(self.peek(), Some(Token::OpenParen)) => {
+                self.next();
+                self.expect(Token::CloseParen)?;
+                match name.as_str() {
+                    "text" => NodeTest::Text,
+                    "node" => NodeTest::Any,
+                    test => return Err(format!("Unsupported XPath node test '{}()'.", test)),
+                }
+            }
+            Some(Token::Name(name)) => NodeTest::Name(name.to_lowercase()),
+            token => return Err(format!("Expected a node test in XPath, found {:?}.", token)),
+        };
+        let mut predicates = Vec::new();
+        while matches!(self.peek(), Some(Token::OpenBracket)) {
+            self.next();
+            predicates.push(self.or()?);
+            self.expect(Token::CloseBracket)?;
+        }
+        Ok(Step { axis, test, predicates })
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while matches!(self.peek(), Some(Token::Name(name)) if name == "or") {
+            self.next();
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.comparison()?;
+        while matches!(self.peek(), Some(Token::Name(name)) if name == "and") {
+            self.next();
+            left = Expr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.value()?;
+        let negated = match self.peek() {
+            Some(Token::Equals) => false,
+            Some(Token::NotEquals) => true,
+            _ => return Ok(left),
+        };
+        self.next();
+        Ok(Expr::Compare(Box::new(left), Box::new(self.value()?), negated))
+    }
+
+    fn value(&mut self) -> Result<Expr, String> {
+        match self.peek().cloned() {
+            Some(Token::Literal(literal)) => {
+                self.next();
+                Ok(Expr::Literal(literal))
+            }
+            Some(Token::Number(number)) => {
+                self.next();
+                Ok(Expr::Number(number))
+            }
+            Some(Token::OpenParen) => {
+                self.next();
+                let expr = self.or()?;
+                self.expect(Token::CloseParen)?;
+                Ok(expr)
+            }
+            Some(Token::Name(name))
+                if self.tokens.get(self.position + 1) == Some(&Token::OpenParen) && !matches!(name.as_str(), "text" | "node") =>
+            {
+                self.position += 2;
+                let mut arguments = Vec::new();
+                if !matches!(self.peek(), Some(Token::CloseParen)) {
+                    arguments.push(self.or()?);
+                    while matches!(self.peek(), Some(Token::Comma)) {
+                        self.next();
+                        arguments.push(self.or()?);
+                    }
+                }
+                self.expect(Token::CloseParen)?;
+                match name.as_str() {
+                    "contains" | "starts-with" if arguments.len() == 2 => {}
+                    "not" if arguments.len() == 1 => {}
+                    "normalize-space" | "string" if arguments.len() <= 1 => {}
+                    "last" | "position" if arguments.is_empty() => {}
+                    function => return Err(format!("Unsupported XPath function '{}' with {} arguments.", function, arguments.len())),
+                }
+                Ok(Expr::Call(name, arguments))
+            }
+            _ => Ok(Expr::Path(self.path()?)),
+        }
+    }
+}
+
+/// Nodes selected by a path: elements, or the strings of a final `text()` or `@attr` step.
+enum Nodes<'a> {
+    Elements(Vec<ElementRef<'a>>),
+    Strings(Vec<String>),
+}
+
+/// The value of a predicate expression.
+enum Value<'a> {
+    Nodes(Nodes<'a>),
+    String(String),
+    Number(f64),
+    Boolean(bool),
+}
+
+impl Value<'_> {
+    fn strings(&self) -> Vec<String> {
+        match self {
+            Value::Nodes(Nodes::Elements(elements)) => elements.iter().map(|element| element.text().collect()).collect(),
+            Value::Nodes(Nodes::Strings(strings)) => strings.clone(),
+            Value::String(string) => vec![string.clone()],
+            Value::Number(number) => vec![number.to_string()],
+            Value::Boolean(boolean) => vec![boolean.to_string()],
+        }
+    }
+
+    /// The XPath string value: that of the first node for node sets.
+    fn string(&self) -> String {
+        self.strings().into_iter().next().unwrap_or_default()
+    }
+
+    fn boolean(&self) -> bool {
+        match self {
+            Value::Nodes(Nodes::Elements(elements)) => !elements.is_empty(),
+            Value::Nodes(Nodes::Strings(strings)) => !strings.is_empty(),
+            Value::String(string) => !string.is_empty(),
+            Value::Number(number) => *number != 0.0,
+            Value::Boolean(boolean) => *boolean,
+        }
+    }
+}
+
+struct Context<'a> {
+    document: &'a Html,
+}
+
+impl<'a> Context<'a> {
+    /// Evaluates a path from the context elements; an absolute path starts at the document instead.
+    fn path(&self, path: &Path, context: &[ElementRef<'a>], top_level: bool) -> Nodes<'a> {
+        let mut elements = if path.absolute { vec![self.document.root_element()] } else { context.to_vec() };
+        for (index, step) in path.steps.iter().enumerate() {
+            // The document node itself has no element, so the first step of an absolute path starts from the root
+            // element: `/html` tests the root element itself, `//x` searches it and everything below.
+            let axis = match (path.absolute, index, step.axis) {
+                (true, 0, Axis::Child) => Axis::Itself,
+                (true, 0, Axis::DescendantOrSelf) => Axis::DescendantOrSelf,
+                (_, _, axis) => axis,
+            };
+            let last = index + 1 == path.steps.len();
+            if step.axis == Axis::Attribute || step.test == NodeTest::Text {
+                if !last || top_level {
+                    return Nodes::Elements(Vec::new());
+                }
+                return Nodes::Strings(self.strings(step, &elements));
+            }
+            let mut selected = Vec::new();
+            for element in &elements {
+                let mut candidates: Vec<ElementRef<'a>> = along(axis, *element).into_iter().filter(|e| matches_test(&step.test, e)).collect();
+                for predicate in &step.predicates {
+                    let size = candidates.len();
+                    candidates = candidates
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(position, candidate)| match self.eval(predicate, *candidate, position + 1, size) {
+                            Value::Number(number) => number == (position + 1) as f64,
+                            value => value.boolean(),
+                        })
+                        .map(|(_, candidate)| candidate)
+                        .collect();
+                }
+                selected.extend(candidates);
+            }
+            elements = self.in_document_order(selected);
+        }
+        Nodes::Elements(elements)
+    }
+
+    /// The text nodes or attribute values a final step selects.
+    fn strings(&self, step: &Step, elements: &[ElementRef<'a>]) -> Vec<String> {
+        let mut strings = Vec::new();
+        for element in elements {
+            match (&step.axis, &step.test) {
+                (Axis::Attribute, NodeTest::Name(name)) => strings.extend(element.value().attr(name).map(str::to_string)),
+                (Axis::Attribute, NodeTest::Any) => strings.extend(element.value().attrs().map(|(_, value)| value.to_string())),
+                (Axis::Child, NodeTest::Text) => {
+                    strings.extend(element.children().filter_map(|node| node.value().as_text().map(|text| text.to_string())))
+                }
+                (Axis::Descendant | Axis::DescendantOrSelf, NodeTest::Text) => strings.extend(element.text().map(str::to_string)),
+                _ => {}
+            }
+        }
+        strings
+    }
+
+    fn in_document_order(&self, elements: Vec<ElementRef<'a>>) -> Vec<ElementRef<'a>> {
+        let ids: HashSet<_> = elements.iter().map(|element| element.id()).collect();
+        let root = self.document.root_element();
+        root.descendants().filter_map(ElementRef::wrap).filter(|element| ids.contains(&element.id())).collect()
+    }
+
+    fn eval(&self, expr: &Expr, node: ElementRef<'a>, position: usize, size: usize) -> Value<'a> {
+        match expr {
+            Expr::Or(left, right) => Value::Boolean(self.eval(left, node, position, size).boolean() || self.eval(right, node, position, size).boolean()),
+            Expr::And(left, right) => Value::Boolean(self.eval(left, node, position, size).boolean() && self.eval(right, node, position, size).boolean()),
+            Expr::Compare(left, right, negated) => {
+                let (left, right) = (self.eval(left, node, position, size), self.eval(right, node, position, size));
+                let equal = match (&left, &right) {
+                    (Value::Number(number), other) | (other, Value::Number(number)) => {
+                        other.strings().iter().any(|value| value.trim().parse::<f64>().ok() == Some(*number))
+                    }
+                    _ => {
+                        let right = right.strings();
+                        left.strings().iter().any(|value| right.contains(value))
+                    }
+                };
+                Value::Boolean(equal != *negated)
+            }
+            Expr::Literal(literal) => Value::String(literal.clone()),
+            Expr::Number(number) => Value::Number(*number),
+            Expr::Path(path) => Value::Nodes(self.path(path, &[node], false)),
+            Expr::Call(name, arguments) => {
+                let argument = |index: usize| arguments.get(index).map(|arg| self.eval(arg, node, position, size).string());
+                let string_value = || argument(0).unwrap_or_else(|| node.text().collect());
+                match name.as_str() {
+                    "contains" => Value::Boolean(argument(0).unwrap_or_default().contains(&argument(1).unwrap_or_default())),
+                    "starts-with" => Value::Boolean(argument(0).unwrap_or_default().starts_with(&argument(1).unwrap_or_default())),
+                    "not" => Value::Boolean(!self.eval(&arguments[0], node, position, size).boolean()),
+                    "normalize-space" => Value::String(string_value().split_whitespace().collect::<Vec<_>>().join(" ")),
+                    "string" => Value::String(string_value()),
+                    "last" => Value::Number(size as f64),
+                    _ => Value::Number(position as f64),
+                }
+            }
+        }
+    }
+}
+
+/// The elements along an axis from an element, nearest first for the reverse axes.
+fn along<'a>(axis: Axis, element: ElementRef<'a>) -> Vec<ElementRef<'a>> {
+    match axis {
+        Axis::Child => element.children().filter_map(ElementRef::wrap).collect(),
+        Axis::Descendant => element.descendants().skip(1).filter_map(ElementRef::wrap).collect(),
+        Axis::DescendantOrSelf => element.descendants().filter_map(ElementRef::wrap).collect(),
+        Axis::Itself => vec![element],
+        Axis::Parent => element.parent().and_then(ElementRef::wrap).into_iter().collect(),
+        Axis::Ancestor => element.ancestors().filter_map(ElementRef::wrap).collect(),
+        Axis::FollowingSibling => element.next_siblings().filter_map(ElementRef::wrap).collect(),
+        Axis::PrecedingSibling => element.prev_siblings().filter_map(ElementRef::wrap).collect(),
+        Axis::Attribute => Vec::new(),
+    }
+}
+
+fn matches_test(test: &NodeTest, element: &ElementRef) -> bool {
+    match test {
+        NodeTest::Name(name) => element.value().name() == name,
+        NodeTest::Any => true,
+        NodeTest::Text => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><body>
+        <h2>Bereiding</h2><ol><li>Snij de ui.</li></ol>
+        <h2 class="kop">Ingrediënten</h2><p>Voor 4 personen</p><ul id="lijst"><li>1 ui</li><li data-q="2">2 wortels</li></ul>
+        <ul><li>Extra</li></ul>
+    </body></html>"#;
+
+    fn texts(expression: &str) -> Vec<String> {
+        let document = Html::parse_document(PAGE);
+        XPath::parse(expression).unwrap().select(&document).iter().map(|e| e.text().collect::<String>()).collect()
+    }
+
+    #[test]
+    fn test_xpath_following_sibling_of_heading() {
+        assert_eq!(texts("//h2[contains(., 'Ingrediënten')]/following-sibling::ul[1]"), vec!["1 ui2 wortels"]);
+        assert_eq!(texts("//h2[normalize-space(text())='Bereiding']/following-sibling::*[1]/li"), vec!["Snij de ui."]);
+    }
+
+    #[test]
+    fn test_xpath_predicates() {
+        assert_eq!(texts("//ul[@id='lijst']/li[last()]"), vec!["2 wortels"]);
+        assert_eq!(texts("//li[@data-q]"), vec!["2 wortels"]);
+        assert_eq!(texts("//ul/li[1]"), vec!["1 ui", "Extra"]);
+        assert_eq!(texts("/html/body/h2[@class='kop' and starts-with(., 'Ingr')]"), vec!["Ingrediënten"]);
+        assert_eq!(texts("//li[not(@data-q) and ../@id='lijst']"), vec!["1 ui"]);
+        assert_eq!(texts("//li[contains(text(), 'wortel')]/ancestor::ul/preceding-sibling::p"), vec!["Voor 4 personen"]);
+    }
+
+    #[test]
+    fn test_xpath_parse_errors() {
+        assert!(XPath::parse("//ul[").is_err());
+        assert!(XPath::parse("//ul/namespace::x").is_err());
+        assert!(XPath::parse("//ul[count(li)]").is_err());
+        assert!(looks_like_xpath("//h2") && looks_like_xpath("./ul") && !looks_like_xpath("h2 + ul"));
+    }
+}
@@ -0,0 +1,34 @@
+/*
+ * C interface of the FoodScraper extraction library (build with `--features ffi`).
+ *
+ * Recipes are returned as newly allocated JSON strings with the same fields as the saved recipe
+ * files; release them with foodscraper_string_free(). On failure NULL is returned and
+ * foodscraper_last_error() describes the error.
+ */
+#ifndef FOODSCRAPER_H
+#define FOODSCRAPER_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Fetches the page at `url` and returns the extracted recipe as JSON, or NULL. Blocks until done. */
+char *foodscraper_scrape(const char *url);
+
+/* Like foodscraper_scrape(), sending `user_agent` as the User-Agent header unless it is NULL. */
+char *foodscraper_scrape_with_user_agent(const char *url, const char *user_agent);
+
+/* Extracts the recipe from already fetched `html` with the selectors of the site `url` belongs to. */
+char *foodscraper_extract_from_html(const char *html, const char *url);
+
+/* The message of the last failed call on this thread, or NULL. Valid until the next call. */
+const char *foodscraper_last_error(void);
+
+/* Releases a string returned by this library. NULL is ignored. */
+void foodscraper_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FOODSCRAPER_H */
@@ -0,0 +1,127 @@
+//! Archiving fetched pages next to the extracted recipe, as plain HTML or as WARC.
+//!
+//! Keeping the original page means a recipe can be re-extracted with improved selectors later,
+//! even after the site changed or went offline.
+
+use crate::fetch::FetchedPage;
+use clap::ValueEnum;
+use reqwest::header::HeaderMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How fetched pages are archived.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HtmlArchiveFormat {
+    /// The page body as received, in a `.html` file.
+    Html,
+    /// A WARC file with the request and response records, including headers and timestamp.
+    Warc,
+}
+
+impl HtmlArchiveFormat {
+    /// The extension of archive files in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            HtmlArchiveFormat::Html => "html",
+            HtmlArchiveFormat::Warc => "warc",
+        }
+    }
+}
+
+/// Writes the archived page as `<stem>.html` or `<stem>.warc` in the folder and returns the path.
+pub fn archive_page(page: &FetchedPage, format: HtmlArchiveFormat, folder: &str, stem: &str) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(folder)?;
+    let content = match format {
+        HtmlArchiveFormat::Html => page.raw.clone(),
+        HtmlArchiveFormat::Warc => warc_records(page),
+    };
+    let path = Path::new(folder).join(format!("{}.{}", stem, format.extension()));
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Builds a WARC/1.1 request and response record pair for the exchange.
+pub fn warc_records(page: &FetchedPage) -> Vec<u8> {
+    let date = page.fetched_at.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let url = reqwest::Url::parse(&page.url).ok();
+    let host = url.as_ref().and_then(|url| url.host_str()).unwrap_or_default();
+    let target = url.as_ref().map(|url| &url[url::Position::BeforePath..]).unwrap_or("/");
+
+    let mut request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n", target, host).into_bytes();
+    append_headers(&mut request, &page.request_headers);
+    request.extend_from_slice(b"\r\n");
+
+    let reason = page.status.canonical_reason().unwrap_or_default();
+    let mut response = format!("{:?} {} {}\r\n", page.version, page.status.as_u16(), reason).into_bytes();
+    append_headers(&mut response, &page.headers);
+    response.extend_from_slice(b"\r\n");
+    response.extend_from_slice(&page.raw);
+
+    let request_id = record_id();
+    let response_id = record_id();
+    let mut warc = warc_record("request", &request_id, None, &date, &page.url, &request);
+    warc.extend(warc_record("response", &response_id, Some(&request_id), &date, &page.url, &response));
+    warc
+}
+
+fn append_headers(block: &mut Vec<u8>, headers: &HeaderMap) {
+    for (name, value) in headers {
+        block.extend_from_slice(name.as_str().as_bytes());
+        block.extend_from_slice(b": ");
+        block.extend_from_slice(value.as_bytes());
+        block.extend_from_slice(b"\r\n");
+    }
+}
+
+fn record_id() -> String {
+    format!("<urn:uuid:{}>", uuid::Uuid::new_v4())
+}
+
+fn warc_record(kind: &str, id: &str, concurrent_to: Option<&str>, date: &str, url: &str, block: &[u8]) -> Vec<u8> {
+    let mut record = format!("WARC/1.1\r\nWARC-Type: {}\r\nWARC-Record-ID: {}\r\n", kind, id);
+    if let Some(concurrent_to) = concurrent_to {
+        record.push_str(&format!("WARC-Concurrent-To: {}\r\n", concurrent_to));
+    }
+    record.push_str(&format!(
+        "WARC-Date: {}\r\nWARC-Target-URI: {}\r\nContent-Type: application/http; msgtype={}\r\nContent-Length: {}\r\n\r\n",
+        date,
+        url,
+        kind,
+        block.len()
+    ));
+    let mut record = record.into_bytes();
+    record.extend_from_slice(block);
+    record.extend_from_slice(b"\r\n\r\n");
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{CONTENT_TYPE, HeaderValue};
+    use reqwest::{StatusCode, Version};
+
+    #[test]
+    fn test_warc_records_contain_request_and_response() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html"));
+        let page = FetchedPage {
+            url: "https://15gram.be/recepten/soep?x=1".to_string(),
+            request_headers: HeaderMap::new(),
+            status: StatusCode::OK,
+            version: Version::HTTP_11,
+            headers,
+            raw: b"<html></html>".to_vec(),
+            body: "<html></html>".to_string(),
+            cache: None,
+            fetched_at: chrono::DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z").unwrap().into(),
+        };
+
+        let warc = String::from_utf8(warc_records(&page)).unwrap();
+        assert!(warc.starts_with("WARC/1.1\r\nWARC-Type: request\r\n"));
+        assert!(warc.contains("GET /recepten/soep?x=1 HTTP/1.1\r\nHost: 15gram.be\r\n"));
+        assert!(warc.contains("WARC-Type: response\r\n"));
+        assert!(warc.contains("WARC-Date: 2024-06-01T12:00:00Z\r\n"));
+        assert!(warc.contains("HTTP/1.1 200 OK\r\ncontent-type: text/html\r\n\r\n<html></html>\r\n\r\n"));
+    }
+}
@@ -0,0 +1,209 @@
+//! Recipe cards: the recipe drawn onto a PNG image for sharing, with its photo, title, ingredients and a QR code
+//! linking to the source page. Built with the `cards` feature; the size, colors and fonts come from the `[card]`
+//! section of the config file.
+
+use crate::Recipe;
+use crate::config::CardConfig;
+use ab_glyph::{FontVec, PxScale};
+use image::{ImageFormat, Rgb, RgbImage, imageops};
+use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut, text_size};
+use imageproc::rect::Rect;
+use qrcode::QrCode;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// Regular and bold fonts tried, in order, when the config names none.
+const SYSTEM_FONTS: &[(&str, &str)] = &[
+    ("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf", "/usr/share/fonts/truetype/dejavu/DejaVuSans-Bold.ttf"),
+    ("/usr/share/fonts/TTF/DejaVuSans.ttf", "/usr/share/fonts/TTF/DejaVuSans-Bold.ttf"),
+    ("/usr/share/fonts/dejavu-sans-fonts/DejaVuSans.ttf", "/usr/share/fonts/dejavu-sans-fonts/DejaVuSans-Bold.ttf"),
+    ("/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf", "/usr/share/fonts/truetype/liberation/LiberationSans-Bold.ttf"),
+    ("/System/Library/Fonts/Supplemental/Arial.ttf", "/System/Library/Fonts/Supplemental/Arial Bold.ttf"),
+    ("C:\\Windows\\Fonts\\arial.ttf", "C:\\Windows\\Fonts\\arialbd.ttf"),
+];
+
+const MARGIN: u32 = 60;
+const QR_SIZE: u32 = 200;
+/// The narrowest ingredient list the QR code column may leave; narrower cards leave the QR code out.
+const MIN_LIST_WIDTH: u32 = 200;
+
+/// Draws the recipe card and returns it PNG-encoded. `photo` is the encoded recipe image, if there is one.
+pub fn render_card(recipe: &Recipe, photo: Option<&[u8]>, template: &CardConfig) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let (regular, bold) = load_fonts(template)?;
+    let (background, text_color, accent) = (parse_color(&template.background)?, parse_color(&template.text_color)?, parse_color(&template.accent)?);
+    let muted = Rgb(std::array::from_fn(|channel| ((text_color.0[channel] as u16 + background.0[channel] as u16) / 2) as u8));
+    let (width, height) = (template.width.max(MARGIN * 2 + QR_SIZE), template.height.max(MARGIN * 2 + QR_SIZE));
+    let text_width = width - 2 * MARGIN;
+    let mut card = RgbImage::from_pixel(width, height, background);
+
+    let photo = photo.filter(|_| template.photo_height > 0).and_then(|photo| match image::load_from_memory(photo) {
+        Ok(photo) => Some(photo),
+        Err(e) => {
+            tracing::warn!("Drawing the card without the photo, which could not be decoded: {}", e);
+            None
+        }
+    });
+    let mut y = match photo {
+        Some(photo) => {
+            let photo_height = template.photo_height.min(height / 2);
+            let photo = photo.resize_to_fill(width, photo_height, imageops::FilterType::Triangle).to_rgb8();
+            imageops::overlay(&mut card, &photo, 0, 0);
+            photo_height + 50
+        }
+        None => {
+            draw_filled_rect_mut(&mut card, Rect::at(0, 0).of_size(width, 16), accent);
+            MARGIN + 16
+        }
+    };
+
+    let text = |card: &mut RgbImage, y: &mut u32, font: &FontVec, size: f32, color: Rgb<u8>, line: &str| {
+        draw_text_mut(card, color, MARGIN as i32, *y as i32, PxScale::from(size), font, line);
+        *y += (size * 1.25) as u32;
+    };
+    let title = recipe.title.as_deref().unwrap_or("Recipe");
+    for line in wrap(title, text_width, |line| text_size(PxScale::from(64.0), &bold, line).0).iter().take(3) {
+        text(&mut card, &mut y, &bold, 64.0, accent, line);
+    }
+    let mut about: Vec<String> = crate::library::source_domain(&recipe.source_url).into_iter().collect();
+    about.extend(recipe.total_time.map(|minutes| format!("{} min", minutes)));
+    if !about.is_empty() {
+        text(&mut card, &mut y, &regular, 30.0, muted, &about.join(" · "));
+    }
+    y += 30;
+
+    // The QR code sits in the bottom-right corner, so the ingredients keep clear of its column.
+    let qr_code = template.qr_code && !recipe.source_url.is_empty() && text_width >= QR_SIZE + 40 + MIN_LIST_WIDTH;
+    let list_width = if qr_code { text_width - QR_SIZE - 40 } else { text_width };
+    text(&mut card, &mut y, &bold, 38.0, accent, "Ingredients");
+    let ingredients = recipe.ingredients.as_deref().unwrap_or_default();
+    let line_height = (30.0 * 1.25) as u32;
+    for (index, ingredient) in ingredients.iter().enumerate() {
+        let lines = wrap(&format!("• {}", ingredient), list_width, |line| text_size(PxScale::from(30.0), &regular, line).0);
+        let left = ingredients.len() - index;
+        // Keep a line free for the "and N more" note unless this is the last ingredient.
+        let needed = lines.len() as u32 + if left > 1 { 1 } else { 0 };
+        if y + needed * line_height > height - MARGIN {
+            text(&mut card, &mut y, &regular, 30.0, muted, &format!("… and {} more", left));
+            break;
+        }
+        for (number, line) in lines.iter().enumerate() {
+            let line = if number == 0 { line.clone() } else { format!("   {}", line) };
+            text(&mut card, &mut y, &regular, 30.0, text_color, &line);
+        }
+    }
+
+    if qr_code {
+        let (x, y) = (width - MARGIN - QR_SIZE, height - MARGIN - QR_SIZE - 30);
+        draw_qr_code(&mut card, &recipe.source_url, x, y, QR_SIZE)?;
+        let caption = "Scan for the recipe";
+        let caption_x = x + QR_SIZE.saturating_sub(text_size(PxScale::from(22.0), &regular, caption).0) / 2;
+        draw_text_mut(&mut card, muted, caption_x as i32, (y + QR_SIZE + 6) as i32, PxScale::from(22.0), &regular, caption);
+    }
+
+    let mut png = Vec::new();
+    card.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Draws a QR code of `text` as a `size` pixels square, on white with a quiet zone, at `x`, `y`.
+fn draw_qr_code(card: &mut RgbImage, text: &str, x: u32, y: u32, size: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let code = QrCode::new(text.as_bytes())?;
+    let modules = code.width() as u32;
+    let module = (size / (modules + 4)).max(1);
+    let offset = (size - module * modules) / 2;
+    draw_filled_rect_mut(card, Rect::at(x as i32, y as i32).of_size(size, size), Rgb([255, 255, 255]));
+    for (index, color) in code.to_colors().into_iter().enumerate() {
+        if color == qrcode::Color::Dark {
+            let (column, row) = (index as u32 % modules, index as u32 / modules);
+            let (left, top) = (x + offset + column * module, y + offset + row * module);
+            draw_filled_rect_mut(card, Rect::at(left as i32, top as i32).of_size(module, module), Rgb([0, 0, 0]));
+        }
+    }
+    Ok(())
+}
+
+/// The regular and bold fonts: those configured, or the first system font found.
+fn load_fonts(template: &CardConfig) -> Result<(FontVec, FontVec), Box<dyn std::error::Error>> {
+    let (regular, bold) = match (&template.font, &template.bold_font) {
+        (Some(font), bold) => (font.clone(), bold.clone().unwrap_or_else(|| font.clone())),
+        (None, _) => SYSTEM_FONTS
+            .iter()
+            .find(|(regular, _)| Path::new(regular).exists())
+            .map(|(regular, bold)| {
+                let bold = if Path::new(bold).exists() { bold } else { regular };
+                (PathBuf::from(regular), PathBuf::from(bold))
+            })
+            .ok_or("No font found for the recipe card; set `font` in the [card] section of the config file.")?,
+    };
+    let load = |path: &Path| -> Result<FontVec, Box<dyn std::error::Error>> {
+        FontVec::try_from_vec(std::fs::read(path)?).map_err(|_| format!("'{}' is not a TrueType or OpenType font.", path.display()).into())
+    };
+    Ok((load(&regular)?, load(&bold)?))
+}
+
+/// Parses a `#rrggbb` color.
+fn parse_color(text: &str) -> Result<Rgb<u8>, Box<dyn std::error::Error>> {
+    let hex = text.strip_prefix('#').filter(|hex| hex.len() == 6 && hex.is_ascii());
+    let hex = hex.ok_or_else(|| format!("Invalid card color '{}': use #rrggbb.", text))?;
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).map_err(|_| format!("Invalid card color '{}': use #rrggbb.", text));
+    Ok(Rgb([channel(0..2)?, channel(2..4)?, channel(4..6)?]))
+}
+
+/// Breaks text into lines no wider than `max` pixels at spaces, measuring with `measure`.
+fn wrap(text: &str, max: u32, measure: impl Fn(&str) -> u32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if !line.is_empty() && measure(&candidate) > max {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color_and_wrap() {
+        assert_eq!(parse_color("#b5451b").unwrap(), Rgb([0xb5, 0x45, 0x1b]));
+        assert!(parse_color("b5451b").is_err());
+        assert!(parse_color("#zz451b").is_err());
+        let measure = |line: &str| line.chars().count() as u32 * 10;
+        assert_eq!(wrap("Snij de ui in ringen", 100, measure), vec!["Snij de ui", "in ringen"]);
+    }
+
+    #[test]
+    fn test_render_card() {
+        let template = CardConfig { width: 600, height: 800, ..Default::default() };
+        if load_fonts(&template).is_err() {
+            return; // No system font to draw with.
+        }
+        let recipe = Recipe {
+            title: Some("Pompoensoep met gember en kokosmelk".to_string()),
+            ingredients: Some((1..=40).map(|n| format!("{} pompoen", n)).collect()),
+            source_url: "https://15gram.be/recepten/pompoensoep".to_string(),
+            ..Default::default()
+        };
+        let mut photo = Vec::new();
+        RgbImage::from_pixel(300, 200, Rgb([200, 120, 40])).write_to(&mut Cursor::new(&mut photo), ImageFormat::Png).unwrap();
+        let card = image::load_from_memory(&render_card(&recipe, Some(&photo), &template).unwrap()).unwrap().to_rgb8();
+        assert_eq!(card.dimensions(), (600, 800));
+        assert_eq!(card.get_pixel(10, 10), &Rgb([200, 120, 40]));
+        // The QR code's white quiet zone sits in the bottom-right corner.
+        assert_eq!(card.get_pixel(600 - MARGIN - QR_SIZE + 2, 800 - MARGIN - 32), &Rgb([255, 255, 255]));
+
+        // Too narrow for the QR code column: the card is drawn without it, and an undecodable photo is left out.
+        let narrow = CardConfig { width: 340, height: 500, ..Default::default() };
+        let card = image::load_from_memory(&render_card(&recipe, Some(b"not an image"), &narrow).unwrap()).unwrap().to_rgb8();
+        assert_eq!(card.dimensions(), (340, 500));
+        assert_eq!(card.get_pixel(340 - MARGIN - 10, 500 - MARGIN - 60), &parse_color(&narrow.background).unwrap());
+    }
+}
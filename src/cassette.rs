@@ -0,0 +1,144 @@
+//! Recording and replaying HTTP responses (VCR-style), so site support can be developed and tested offline and
+//! deterministically. Built with the `cassette` feature.
+//!
+//! A cassette is a directory with two files per URL: `<key>.json` with the status and headers, and `<key>.body` with
+//! the body as received, where the key is derived from the URL. The fetcher uses it when `FOODSCRAPER_CASSETTE` names
+//! the directory; `FOODSCRAPER_CASSETTE_MODE` picks what happens:
+//!
+//! - `replay` (the default): responses come from the cassette only, and a URL that was not recorded is an error;
+//! - `record`: every request goes to the network and its response is saved, replacing an earlier recording;
+//! - `auto`: recorded URLs are replayed, the others fetched and recorded.
+
+use crate::fetch::{self, RawResponse};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{StatusCode, Version};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+
+/// What a cassette does with requests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Replay,
+    Record,
+    Auto,
+}
+
+/// The status line and headers of a recorded response.
+#[derive(Debug, Serialize, Deserialize)]
+struct Recording {
+    url: String,
+    status: u16,
+    version: String,
+    headers: Vec<(String, String)>,
+}
+
+/// A directory of recorded responses.
+#[derive(Debug)]
+pub struct Cassette {
+    pub dir: PathBuf,
+    pub mode: Mode,
+}
+
+impl Cassette {
+    /// The cassette configured by `FOODSCRAPER_CASSETTE` and `FOODSCRAPER_CASSETTE_MODE`, if any.
+    pub fn from_env() -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let Some(dir) = std::env::var_os("FOODSCRAPER_CASSETTE") else {
+            return Ok(None);
+        };
+        let mode = match std::env::var("FOODSCRAPER_CASSETTE_MODE").as_deref() {
+            Ok("replay") | Err(_) => Mode::Replay,
+            Ok("record") => Mode::Record,
+            Ok("auto") => Mode::Auto,
+            Ok(other) => return Err(format!("Unknown FOODSCRAPER_CASSETTE_MODE '{}'; expected replay, record or auto.", other).into()),
+        };
+        Ok(Some(Cassette { dir: PathBuf::from(dir), mode }))
+    }
+
+    /// The file names of the recording of a URL, without extension.
+    fn key(url: &str) -> String {
+        let digest = Sha256::digest(url.as_bytes());
+        digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Reads the recorded response for the URL, if there is one.
+    pub fn load(&self, url: &str) -> Result<Option<RawResponse>, Box<dyn std::error::Error>> {
+        let key = Self::key(url);
+        let Ok(recording) = fs::read_to_string(self.dir.join(format!("{}.json", key))) else {
+            return Ok(None);
+        };
+        let recording: Recording = serde_json::from_str(&recording)?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &recording.headers {
+            headers.append(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?);
+        }
+        let version = match recording.version.as_str() {
+            "HTTP/1.0" => Version::HTTP_10,
+            "HTTP/2.0" => Version::HTTP_2,
+            "HTTP/3.0" => Version::HTTP_3,
+            _ => Version::HTTP_11,
+        };
+        Ok(Some(RawResponse {
+            status: StatusCode::from_u16(recording.status)?,
+            version,
+            headers,
+            body: fs::read(self.dir.join(format!("{}.body", key)))?,
+        }))
+    }
+
+    /// Saves the response for the URL, replacing an earlier recording.
+    pub fn save(&self, url: &str, response: &RawResponse) -> Result<(), Box<dyn std::error::Error>> {
+        let headers = response.headers.iter().filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())));
+        let recording = Recording {
+            url: url.to_string(),
+            status: response.status.as_u16(),
+            version: format!("{:?}", response.version),
+            headers: headers.collect(),
+        };
+        let key = Self::key(url);
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(format!("{}.json", key)), serde_json::to_string_pretty(&recording)? + "\n")?;
+        fs::write(self.dir.join(format!("{}.body", key)), &response.body)?;
+        Ok(())
+    }
+
+    /// Answers a GET request for the URL according to the mode.
+    pub async fn send(&self, client: &reqwest::Client, url: &str, request_headers: &HeaderMap) -> Result<RawResponse, Box<dyn std::error::Error>> {
+        if self.mode != Mode::Record
+            && let Some(response) = self.load(url)?
+        {
+            return Ok(response);
+        }
+        if self.mode == Mode::Replay {
+            return Err(format!("{} is not recorded in the cassette {}.", url, self.dir.display()).into());
+        }
+        let response = fetch::send_network(client, url, request_headers).await?;
+        self.save(url, &response)?;
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::CONTENT_TYPE;
+
+    #[tokio::test]
+    async fn test_replay_recorded_response() {
+        let dir = std::env::temp_dir().join(format!("foodscraper-cassette-{}", std::process::id()));
+        let cassette = Cassette { dir: dir.clone(), mode: Mode::Replay };
+        let url = "https://15gram.be/recepten/soep";
+        let client = reqwest::Client::new();
+        assert!(cassette.send(&client, url, &HeaderMap::new()).await.is_err());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html; charset=ISO-8859-1"));
+        let body = b"<h1>Cr\xe8me</h1>".to_vec();
+        cassette.save(url, &RawResponse { status: StatusCode::OK, version: Version::HTTP_2, headers, body: body.clone() }).unwrap();
+        let response = cassette.send(&client, url, &HeaderMap::new()).await.unwrap();
+        assert_eq!((response.status, response.version, response.body), (StatusCode::OK, Version::HTTP_2, body));
+        assert_eq!(fetch::decode_body(b"\xe8", &response.headers), "è");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,40 @@
+//! `watch-clipboard`: picking up recipe URLs copied to the system clipboard.
+
+/// Returns the clipboard text as a URL if it is nothing but a single http(s) link.
+pub fn clipboard_url(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() || text.contains(char::is_whitespace) {
+        return None;
+    }
+    let url = url::Url::parse(text).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+/// Reads the current clipboard text, or `None` when the clipboard is empty or holds something else.
+pub fn read_text() -> Result<Option<String>, Box<dyn std::error::Error>> {
+    match arboard::Clipboard::new()?.get_text() {
+        Ok(text) => Ok(Some(text)),
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Shows a desktop notification. Failing to show it is not an error worth stopping for.
+pub fn notify(summary: &str, body: &str) {
+    if let Err(error) = notify_rust::Notification::new().appname("FoodScraper").summary(summary).body(body).show() {
+        tracing::warn!("Could not show notification: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_url() {
+        assert_eq!(clipboard_url("  https://15gram.be/recepten/lasagne\n"), Some("https://15gram.be/recepten/lasagne".to_string()));
+        assert_eq!(clipboard_url("see https://15gram.be/recepten/lasagne"), None);
+        assert_eq!(clipboard_url("ftp://15gram.be/file"), None);
+        assert_eq!(clipboard_url("lasagne"), None);
+    }
+}
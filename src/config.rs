@@ -0,0 +1,249 @@
+//! The user configuration file, `~/.config/foodscraper/config.toml`.
+//!
+//! Settings that are too sensitive or too long to pass on every invocation live here, such as the
+//! SMTP account used by `--email` or the MQTT broker scrape results are published to. The top-level keys and the
+//! `[crawl]` and `[push]` sections give defaults for command-line options, which win when they are given:
+//!
+//! ```toml
+//! output = "~/Recipes"
+//! store = "both"
+//! user_agent = "FoodScraper (me@example.com)"
+//!
+//! [crawl]
+//! delay = 3.0
+//!
+//! [push]
+//! mealie_url = "https://mealie.example.com"
+//! mealie_token = "..."
+//! ```
+
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// The parsed configuration file; every section is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The output folder (`--output`); a leading `~/` stands for the home directory.
+    pub output: Option<String>,
+    /// The SQLite database file (`--db`).
+    pub db: Option<String>,
+    /// Where scraped recipes are stored (`--store`).
+    pub store: Option<crate::StoreMode>,
+    /// What happens when a recipe's JSON file already exists (`--on-conflict`).
+    pub on_conflict: Option<crate::OnConflict>,
+    /// The formats written next to each recipe's JSON file (`--format`).
+    pub format: Vec<crate::render::RecipeFormat>,
+    /// The `User-Agent` header of every request (`--user-agent`).
+    pub user_agent: Option<String>,
+    /// Defaults for `crawl`.
+    pub crawl: CrawlSettings,
+    /// Recipe manager credentials for `--push` and `plan shopping --send`.
+    pub push: PushConfig,
+    /// The mail account `--email` sends through.
+    pub smtp: Option<SmtpConfig>,
+    /// The MQTT broker scrape results are published to (needs the `mqtt` feature).
+    pub mqtt: Option<MqttConfig>,
+    /// The jobs `daemon` runs.
+    pub daemon: crate::daemon::DaemonConfig,
+    /// Where `selectors update` downloads selectors from.
+    pub selectors: SelectorsConfig,
+    /// The look of `--format card` images (needs the `cards` feature).
+    pub card: CardConfig,
+}
+
+/// The `[selectors]` section.
+#[derive(Debug, Default, Deserialize)]
+pub struct SelectorsConfig {
+    /// The URL of the selectors file to install; defaults to [`crate::selectors_update::DEFAULT_UPDATE_URL`].
+    pub update_url: Option<String>,
+}
+
+/// The `[crawl]` section.
+#[derive(Debug, Default, Deserialize)]
+pub struct CrawlSettings {
+    /// Seconds between requests to the site (`crawl --delay`).
+    pub delay: Option<f64>,
+}
+
+/// The `[push]` section, with the same names as the command-line options.
+#[derive(Debug, Default, Deserialize)]
+pub struct PushConfig {
+    pub mealie_url: Option<String>,
+    pub mealie_token: Option<String>,
+    pub tandoor_url: Option<String>,
+    pub tandoor_token: Option<String>,
+    pub grocy_url: Option<String>,
+    pub grocy_api_key: Option<String>,
+    pub grocy_shopping_list: Option<i64>,
+    pub nextcloud_url: Option<String>,
+    pub nextcloud_user: Option<String>,
+    pub nextcloud_password: Option<String>,
+    pub nextcloud_folder: Option<String>,
+    pub notion_token: Option<String>,
+    pub notion_database: Option<String>,
+    pub webhook_secret: Option<String>,
+    pub home_assistant_url: Option<String>,
+    pub home_assistant_token: Option<String>,
+}
+
+/// The `[card]` section: the size, colors and fonts of recipe cards. Colors are `#rrggbb`; without fonts, a common
+/// system font such as DejaVu Sans or Arial is used.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[cfg_attr(not(feature = "cards"), allow(dead_code))]
+pub struct CardConfig {
+    pub width: u32,
+    pub height: u32,
+    /// The height of the photo across the top; 0 leaves the photo out.
+    pub photo_height: u32,
+    pub background: String,
+    pub text_color: String,
+    /// The color of the title and headings.
+    pub accent: String,
+    /// A TrueType or OpenType font file for the text.
+    pub font: Option<PathBuf>,
+    /// The font of the title and headings; defaults to `font`.
+    pub bold_font: Option<PathBuf>,
+    /// Draw a QR code linking to the source page in the bottom corner.
+    pub qr_code: bool,
+}
+
+impl Default for CardConfig {
+    fn default() -> Self {
+        CardConfig {
+            width: 1080,
+            height: 1350,
+            photo_height: 560,
+            background: "#fdf8f0".to_string(),
+            text_color: "#2b2b2b".to_string(),
+            accent: "#b5451b".to_string(),
+            font: None,
+            bold_font: None,
+            qr_code: true,
+        }
+    }
+}
+
+/// How to connect to the SMTP server.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    /// Defaults to 587 for STARTTLS, 465 for TLS and 25 without encryption.
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// The sender address, e.g. `FoodScraper <me@example.com>`.
+    pub from: String,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+}
+
+/// How to connect to the MQTT broker and where to publish.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+pub struct MqttConfig {
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Base topic; recipes go to `<topic>/recipe`, refresh summaries to `<topic>/summary`.
+    #[serde(default = "default_mqtt_topic")]
+    pub topic: String,
+    /// Defaults to `foodscraper-<pid>`, so instances on the same broker do not disconnect each other.
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    /// Publish retained messages, so dashboards show the latest result when they connect.
+    #[serde(default)]
+    pub retain: bool,
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_topic() -> String {
+    "foodscraper".to_string()
+}
+
+fn default_mqtt_client_id() -> String {
+    format!("foodscraper-{}", std::process::id())
+}
+
+/// The encryption used for the SMTP connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    #[default]
+    Starttls,
+    Tls,
+    None,
+}
+
+impl Config {
+    /// The configuration file location, honouring `XDG_CONFIG_HOME`.
+    pub fn path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("foodscraper").join("config.toml"))
+    }
+
+    /// Loads the configuration file, or the defaults when there is none.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        match Self::path().filter(|path| path.exists()) {
+            Some(path) => {
+                let content = fs::read_to_string(&path)?;
+                toml::from_str(&content).map_err(|e| format!("Invalid config file '{}': {}", path.display(), e).into())
+            }
+            None => Ok(Config::default()),
+        }
+    }
+}
+
+/// Replaces a leading `~/` with the home directory.
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest).display().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_smtp_section() {
+        let config: Config =
+            toml::from_str("[smtp]\nhost = \"smtp.example.com\"\nfrom = \"me@example.com\"\nsecurity = \"tls\"\n").unwrap();
+        let smtp = config.smtp.unwrap();
+        assert_eq!(smtp.host, "smtp.example.com");
+        assert_eq!(smtp.security, SmtpSecurity::Tls);
+        assert!(toml::from_str::<Config>("").unwrap().smtp.is_none());
+    }
+
+    #[test]
+    fn test_parse_mqtt_defaults() {
+        let mqtt = toml::from_str::<Config>("[mqtt]\nhost = \"broker.local\"\n").unwrap().mqtt.unwrap();
+        assert_eq!((mqtt.port, mqtt.topic.as_str(), mqtt.retain), (1883, "foodscraper", false));
+        assert_eq!(mqtt.client_id, format!("foodscraper-{}", std::process::id()));
+        let named = toml::from_str::<Config>("[mqtt]\nhost = \"broker.local\"\nclient_id = \"kitchen\"\n").unwrap().mqtt.unwrap();
+        assert_eq!((named.client_id.as_str(), named.topic.as_str()), ("kitchen", "foodscraper"));
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let config: Config =
+            toml::from_str("store = \"both\"\nformat = [\"markdown\", \"html\"]\nuser_agent = \"FoodScraper\"\n\n[crawl]\ndelay = 3.0\n\n[push]\nmealie_token = \"secret\"\n").unwrap();
+        assert_eq!(config.store, Some(crate::StoreMode::Both));
+        assert_eq!(config.format, [crate::render::RecipeFormat::Markdown, crate::render::RecipeFormat::Html]);
+        assert_eq!(config.crawl.delay, Some(3.0));
+        assert_eq!(config.push.mealie_token.as_deref(), Some("secret"));
+        assert!(config.output.is_none());
+        assert_eq!((config.card.width, config.card.qr_code), (1080, true));
+        assert!(toml::from_str::<Config>("store = \"csv\"\n").is_err());
+    }
+}
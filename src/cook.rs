@@ -0,0 +1,240 @@
+//! The `cook` subcommand: a full-screen, step-by-step view of a saved recipe for use while cooking. It shows one
+//! instruction at a time with the ingredients it mentions pinned beside it, and runs countdown timers for the
+//! durations found in the steps.
+
+use crate::Recipe;
+use crate::ingredients::parse_ingredient;
+use crate::steps::{StepTimer, extract_timers};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// A countdown started from one of the recipe's timers.
+struct Countdown {
+    /// The step the timer belongs to, zero-based.
+    step: usize,
+    text: String,
+    ends: Instant,
+    /// Whether the terminal bell was rung when it ran out.
+    rung: bool,
+}
+
+/// The state of a cooking session.
+struct Cook {
+    recipe: Recipe,
+    steps: Vec<String>,
+    timers: Vec<StepTimer>,
+    step: usize,
+    countdowns: Vec<Countdown>,
+}
+
+/// Runs the cooking view for the recipe until the user quits.
+pub fn run(recipe: Recipe) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cook = Cook::new(recipe)?;
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut cook);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, cook: &mut Cook) -> Result<(), Box<dyn std::error::Error>> {
+    loop {
+        let now = Instant::now();
+        terminal.draw(|frame| cook.draw(frame, now))?;
+        if cook.ring_finished(now) {
+            std::io::stdout().write_all(b"\x07")?;
+            std::io::stdout().flush()?;
+        }
+        // Redraw at least every quarter second so the countdowns tick.
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !cook.handle_key(key.code, Instant::now())
+        {
+            return Ok(());
+        }
+    }
+}
+
+impl Cook {
+    fn new(recipe: Recipe) -> Result<Self, Box<dyn std::error::Error>> {
+        let steps = recipe.steps.clone().unwrap_or_default();
+        if steps.is_empty() {
+            return Err(format!("'{}' has no steps to cook.", recipe.title.as_deref().unwrap_or("(untitled)")).into());
+        }
+        // Recipes saved before timers were extracted get them from their steps.
+        let timers = recipe.timers.clone().unwrap_or_else(|| extract_timers(&steps));
+        Ok(Cook { recipe, steps, timers, step: 0, countdowns: Vec::new() })
+    }
+
+    /// The timers mentioned in the current step.
+    fn step_timers(&self) -> Vec<&StepTimer> {
+        self.timers.iter().filter(|timer| timer.step == self.step).collect()
+    }
+
+    /// Handles a key press; returns `false` to quit.
+    fn handle_key(&mut self, key: KeyCode, now: Instant) -> bool {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Right | KeyCode::Char('l' | 'n' | ' ') => self.step = (self.step + 1).min(self.steps.len() - 1),
+            KeyCode::Left | KeyCode::Char('h' | 'p') => self.step = self.step.saturating_sub(1),
+            KeyCode::Enter => self.start_timer(0, now),
+            KeyCode::Char(digit @ '1'..='9') => self.start_timer(digit as usize - '1' as usize, now),
+            KeyCode::Char('c') => self.countdowns.retain(|countdown| countdown.ends > now),
+            _ => {}
+        }
+        true
+    }
+
+    /// Starts the nth timer of the current step, unless it is already counting down.
+    fn start_timer(&mut self, nth: usize, now: Instant) {
+        let Some(timer) = self.step_timers().get(nth).copied() else {
+            return;
+        };
+        let running = self.countdowns.iter().any(|countdown| countdown.step == timer.step && countdown.text == timer.text && countdown.ends > now);
+        if !running {
+            let (step, text) = (timer.step, timer.text.clone());
+            self.countdowns.push(Countdown { step, text, ends: now + Duration::from_secs(timer.seconds), rung: false });
+        }
+    }
+
+    /// Marks the countdowns that ran out as rung, returning whether any did since the last call.
+    fn ring_finished(&mut self, now: Instant) -> bool {
+        let mut any = false;
+        for countdown in self.countdowns.iter_mut().filter(|countdown| !countdown.rung && countdown.ends <= now) {
+            countdown.rung = true;
+            any = true;
+        }
+        any
+    }
+
+    fn draw(&self, frame: &mut Frame, now: Instant) {
+        let timer_rows = if self.countdowns.is_empty() { 0 } else { self.countdowns.len() as u16 + 2 };
+        let [header, main, countdowns, help] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(timer_rows), Constraint::Length(1)]).areas(frame.area());
+        let [instruction, pinned] = Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)]).areas(main);
+
+        let title = self.recipe.title.as_deref().unwrap_or("(untitled)");
+        frame.render_widget(Line::from(format!("{} — step {} of {}", title, self.step + 1, self.steps.len())).bold(), header);
+
+        let mut lines = vec![Line::from(self.steps[self.step].clone())];
+        let timers = self.step_timers();
+        if !timers.is_empty() {
+            lines.push(Line::default());
+            lines.extend(timers.iter().enumerate().map(|(nth, timer)| Line::from(format!("[{}] ⏲ {}", nth + 1, timer.text)).cyan()));
+        }
+        let step = Paragraph::new(lines).block(Block::bordered().title(format!(" Step {} ", self.step + 1))).wrap(Wrap { trim: false });
+        frame.render_widget(step, instruction);
+
+        let all_ingredients = self.recipe.ingredients.as_deref().unwrap_or_default();
+        let mentioned = step_ingredients(all_ingredients, &self.steps[self.step]);
+        let (heading, shown) = if mentioned.is_empty() { (" Ingredients ", all_ingredients.iter().collect()) } else { (" For this step ", mentioned) };
+        let ingredients: Vec<Line> = shown.into_iter().map(|ingredient| Line::from(format!("• {}", ingredient))).collect();
+        frame.render_widget(Paragraph::new(ingredients).block(Block::bordered().title(heading)).wrap(Wrap { trim: false }), pinned);
+
+        if !self.countdowns.is_empty() {
+            let lines: Vec<Line> = self
+                .countdowns
+                .iter()
+                .map(|countdown| {
+                    let label = format!("Step {}, {}", countdown.step + 1, countdown.text);
+                    match countdown.ends.checked_duration_since(now).filter(|left| !left.is_zero()) {
+                        Some(left) => Line::from(format!("{}  {}", format_remaining(left), label)),
+                        None => Line::from(format!("DONE   {}", label)).bold().red(),
+                    }
+                })
+                .collect();
+            frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" Timers ")), countdowns);
+        }
+
+        frame.render_widget(Line::from("←→ previous/next step  Enter or 1-9 start a timer  c clear finished timers  q quit").dim(), help);
+    }
+}
+
+/// The ingredient lines whose name is mentioned in the step, e.g. `2 courgettes` for "Snij de courgette in blokjes".
+fn step_ingredients<'a>(ingredients: &'a [String], step: &str) -> Vec<&'a String> {
+    let step_words = words(step);
+    ingredients
+        .iter()
+        .filter(|ingredient| {
+            let names = words(&parse_ingredient(ingredient).key());
+            // Short words of longer names ("en" in "peper en zout") say nothing about the ingredient.
+            let single = names.len() == 1;
+            names.iter().filter(|name| single || name.chars().count() >= 3).any(|name| step_words.iter().any(|word| mentions(word, name)))
+        })
+        .collect()
+}
+
+/// Whether a word of a step refers to an ingredient name: the same word, its plural, or the head of a compound, as
+/// "bouillon" for "groentebouillon". Names under three letters, like "ui", only match themselves.
+fn mentions(word: &str, name: &str) -> bool {
+    if name.chars().count() < 3 {
+        return word == name;
+    }
+    word.starts_with(name) || word.chars().count() >= 4 && (name.starts_with(word) || name.ends_with(word))
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).map(str::to_string).collect()
+}
+
+/// Formats a remaining duration as `mm:ss`, or `h:mm:ss` from an hour up.
+fn format_remaining(left: Duration) -> String {
+    let seconds = left.as_secs_f64().ceil() as u64;
+    match seconds / 3600 {
+        0 => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_ingredients() {
+        let ingredients: Vec<String> = ["2 courgettes", "1 ui", "1 l groentebouillon", "peper en zout"].map(String::from).to_vec();
+        assert_eq!(step_ingredients(&ingredients, "Snij de courgette en de ui in blokjes."), vec!["2 courgettes", "1 ui"]);
+        assert_eq!(step_ingredients(&ingredients, "Giet de bouillon erbij en kruid met peper."), vec!["1 l groentebouillon", "peper en zout"]);
+        assert!(step_ingredients(&ingredients, "Laat uitlekken.").is_empty());
+    }
+
+    #[test]
+    fn test_timers_count_down() {
+        let recipe = Recipe {
+            steps: Some(vec!["Snij de groenten.".to_string(), "Kook 20 minuten, laat dan 5 minuten rusten.".to_string()]),
+            ..Default::default()
+        };
+        let mut cook = Cook::new(recipe).unwrap();
+        let now = Instant::now();
+        cook.handle_key(KeyCode::Enter, now);
+        assert!(cook.countdowns.is_empty());
+
+        cook.handle_key(KeyCode::Right, now);
+        cook.handle_key(KeyCode::Right, now);
+        assert_eq!(cook.step, 1);
+        cook.handle_key(KeyCode::Char('2'), now);
+        cook.handle_key(KeyCode::Char('2'), now);
+        assert_eq!(cook.countdowns.len(), 1);
+        assert_eq!(cook.countdowns[0].text, "5 minuten");
+        assert!(!cook.ring_finished(now + Duration::from_secs(299)));
+        assert!(cook.ring_finished(now + Duration::from_secs(300)));
+        assert!(!cook.ring_finished(now + Duration::from_secs(301)));
+        cook.handle_key(KeyCode::Char('c'), now + Duration::from_secs(301));
+        assert!(cook.countdowns.is_empty());
+        assert!(!cook.handle_key(KeyCode::Char('q'), now));
+    }
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(Duration::from_millis(65_200)), "01:06");
+        assert_eq!(format_remaining(Duration::from_secs(5400)), "1:30:00");
+    }
+}
@@ -0,0 +1,263 @@
+//! Crawling whole sites with `crawl`: discovering recipe URLs from sitemaps or category listings and
+//! batch-scraping them.
+//!
+//! Which URLs are recipes, and how listing pages link to recipes and to their next page, comes from the
+//! site's `[<site>.crawl]` table in `selectors.toml`:
+//!
+//! ```toml
+//! [15gram.crawl]
+//! recipe_urls = ["^https://15gram\\.be/recepten/[^/]+$"]
+//! recipe_link = ".recipe-card a"
+//! next_page = "a[rel=next]"
+//! ```
+//!
+//! The URLs scraped per crawl are kept in `crawl-state.json` in the output folder, so an interrupted
+//! crawl resumes where it stopped.
+
+use flate2::read::GzDecoder;
+use quick_xml::events::Event;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// File name of the crawl state inside the output folder.
+pub const STATE_FILE: &str = "crawl-state.json";
+
+/// How deep nested sitemap indexes are followed.
+const MAX_SITEMAP_DEPTH: usize = 3;
+
+/// The crawl settings of a site.
+#[derive(Debug, Default, Deserialize)]
+pub struct CrawlConfig {
+    /// Regexes a URL must match to count as a recipe; every URL on the site counts when empty.
+    #[serde(default)]
+    pub recipe_urls: Vec<String>,
+    /// Selector for the links to recipes on a listing page; all links are considered when unset.
+    pub recipe_link: Option<String>,
+    /// Selector for the link to the next listing page.
+    pub next_page: Option<String>,
+}
+
+impl CrawlConfig {
+    /// Reads the `[<site>.crawl]` table of the site the URL belongs to, or the defaults if it has none.
+    pub fn for_url(selectors: &str, url: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let Some(site) = crate::site_for_url(selectors, url) else {
+            return Ok(CrawlConfig::default());
+        };
+        let sites: toml::Table = toml::from_str(selectors)?;
+        match sites.get(&site).and_then(|site| site.get("crawl")) {
+            Some(crawl) => Ok(crawl.clone().try_into()?),
+            None => Ok(CrawlConfig::default()),
+        }
+    }
+
+    /// Compiles the recipe URL patterns.
+    pub fn patterns(&self) -> Result<Vec<Regex>, regex::Error> {
+        self.recipe_urls.iter().map(|pattern| Regex::new(pattern)).collect()
+    }
+}
+
+/// Whether the URL matches one of the patterns, or there are none.
+pub fn is_recipe_url(patterns: &[Regex], url: &str) -> bool {
+    patterns.is_empty() || patterns.iter().any(|pattern| pattern.is_match(url))
+}
+
+/// The URLs scraped so far, per crawl start URL.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CrawlState {
+    pub crawls: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl CrawlState {
+    /// Loads the state from the output folder, starting empty if there is no state file yet.
+    pub fn load(output_folder: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = Path::new(output_folder).join(STATE_FILE);
+        if !path.exists() {
+            return Ok(CrawlState::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the state to the output folder.
+    pub fn save(&self, output_folder: &str) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(output_folder)?;
+        fs::write(Path::new(output_folder).join(STATE_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Whether the URL was already scraped by this crawl.
+    pub fn is_done(&self, start: &str, url: &str) -> bool {
+        self.crawls.get(start).is_some_and(|done| done.contains(url))
+    }
+
+    /// Marks a URL of the crawl as scraped.
+    pub fn mark_done(&mut self, start: &str, url: &str) {
+        self.crawls.entry(start.to_string()).or_default().insert(url.to_string());
+    }
+}
+
+/// What a sitemap file lists.
+#[derive(Debug, PartialEq)]
+pub enum Sitemap {
+    /// A sitemap index, listing further sitemaps.
+    Index(Vec<String>),
+    /// A sitemap listing page URLs.
+    Urls(Vec<String>),
+}
+
+/// Downloads the sitemap, following sitemap indexes, and returns every page URL it lists.
+pub async fn sitemap_urls(client: &reqwest::Client, sitemap_url: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut pending = vec![(sitemap_url.to_string(), 0)];
+    let mut urls = Vec::new();
+    while let Some((url, depth)) = pending.pop() {
+        let body = client.get(&url).send().await?.error_for_status()?.bytes().await?;
+        match parse_sitemap(&decompress(&body)?)? {
+            Sitemap::Urls(found) => urls.extend(found),
+            Sitemap::Index(sitemaps) if depth < MAX_SITEMAP_DEPTH => {
+                pending.extend(sitemaps.into_iter().rev().map(|sitemap| (sitemap, depth + 1)));
+            }
+            Sitemap::Index(_) => tracing::warn!("Skipping sitemaps nested deeper than {} levels under {}.", MAX_SITEMAP_DEPTH, url),
+        }
+    }
+    Ok(urls)
+}
+
+/// Collects the recipe links of a category listing, following its "next page" links for up to `depth` pages
+/// in total and waiting `delay` seconds between pages.
+pub async fn listing_urls(
+    client: &reqwest::Client,
+    start: &str,
+    depth: usize,
+    config: &CrawlConfig,
+    delay: f64,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut urls = Vec::new();
+    let mut visited = BTreeSet::new();
+    let mut next = Some(start.to_string());
+    while let Some(page_url) = next.take().filter(|url| visited.len() < depth && visited.insert(url.clone())) {
+        if visited.len() > 1 {
+            tokio::time::sleep(std::time::Duration::from_secs_f64(delay.max(0.0))).await;
+        }
+        let body = crate::fetch::fetch_text(client, &page_url).await?;
+        let listing = parse_listing(&body, &page_url, config)?;
+        tracing::info!("Listing page {}: {} links.", page_url, listing.links.len());
+        urls.extend(listing.links);
+        next = listing.next_page;
+    }
+    Ok(urls)
+}
+
+/// The links found on one listing page.
+#[derive(Debug, PartialEq)]
+pub struct Listing {
+    /// Candidate recipe links, as absolute URLs in page order.
+    pub links: Vec<String>,
+    pub next_page: Option<String>,
+}
+
+/// Reads the recipe links and the next page link from a listing page.
+pub fn parse_listing(html: &str, page_url: &str, config: &CrawlConfig) -> Result<Listing, Box<dyn std::error::Error>> {
+    let parse = |selector: &str| Selector::parse(selector).map_err(|e| format!("Invalid selector '{}': {}", selector, e));
+    let link_selector = parse(config.recipe_link.as_deref().unwrap_or("a[href]"))?;
+    let next_selector = config.next_page.as_deref().map(parse).transpose()?;
+
+    static ANCHOR: std::sync::LazyLock<Selector> = std::sync::LazyLock::new(|| Selector::parse("a[href]").unwrap());
+    let document = Html::parse_document(html);
+    let base = url::Url::parse(page_url)?;
+    let href = |element: scraper::ElementRef| {
+        let element = if element.value().name() == "a" { Some(element) } else { element.select(&ANCHOR).next() };
+        element?.value().attr("href").and_then(|href| base.join(href.trim()).ok()).map(String::from)
+    };
+
+    let links = document.select(&link_selector).filter_map(href).collect();
+    let next_page = next_selector.and_then(|selector| document.select(&selector).find_map(href));
+    Ok(Listing { links, next_page })
+}
+
+/// Unpacks gzipped sitemaps (`sitemap.xml.gz`); other bodies are returned as they are.
+fn decompress(body: &[u8]) -> std::io::Result<Vec<u8>> {
+    if !body.starts_with(&[0x1f, 0x8b]) {
+        return Ok(body.to_vec());
+    }
+    let mut xml = Vec::new();
+    GzDecoder::new(body).read_to_end(&mut xml)?;
+    Ok(xml)
+}
+
+/// Reads the `<loc>` entries of a sitemap or sitemap index.
+pub fn parse_sitemap(xml: &[u8]) -> Result<Sitemap, quick_xml::Error> {
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    let mut index = false;
+    let mut in_loc = false;
+    let mut locations = Vec::new();
+    let mut buffer = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"sitemapindex" => index = true,
+                b"loc" => in_loc = true,
+                _ => {}
+            },
+            Event::End(element) if element.local_name().as_ref() == b"loc" => in_loc = false,
+            Event::Text(text) if in_loc => locations.push(text.unescape()?.trim().to_string()),
+            Event::CData(text) if in_loc => locations.push(String::from_utf8_lossy(&text).trim().to_string()),
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+    locations.retain(|location| !location.is_empty());
+    Ok(if index { Sitemap::Index(locations) } else { Sitemap::Urls(locations) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sitemap() {
+        let urls = br#"<?xml version="1.0" encoding="UTF-8"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc>https://15gram.be/recepten/soep</loc><lastmod>2024-06-01</lastmod></url>
+              <url><loc> https://15gram.be/zoeken?q=a&amp;p=2 </loc></url>
+            </urlset>"#;
+        assert_eq!(
+            parse_sitemap(urls).unwrap(),
+            Sitemap::Urls(vec!["https://15gram.be/recepten/soep".to_string(), "https://15gram.be/zoeken?q=a&p=2".to_string()])
+        );
+
+        let index = br#"<sitemapindex><sitemap><loc>https://15gram.be/sitemap-recipes.xml</loc></sitemap></sitemapindex>"#;
+        assert_eq!(parse_sitemap(index).unwrap(), Sitemap::Index(vec!["https://15gram.be/sitemap-recipes.xml".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let html = r#"
+            <div class="card"><a href="/recepten/soep">Soep</a></div>
+            <div class="card"><h3><a href="https://15gram.be/recepten/stoofvlees">Stoofvlees</a></h3></div>
+            <a href="/over-ons">Over ons</a>
+            <nav><a class="next" href="?page=2">Volgende</a></nav>"#;
+        let config =
+            CrawlConfig { recipe_link: Some(".card".to_string()), next_page: Some("a.next".to_string()), ..Default::default() };
+        let listing = parse_listing(html, "https://15gram.be/soepen", &config).unwrap();
+        assert_eq!(listing.links, vec!["https://15gram.be/recepten/soep", "https://15gram.be/recepten/stoofvlees"]);
+        assert_eq!(listing.next_page.as_deref(), Some("https://15gram.be/soepen?page=2"));
+
+        let all_links = parse_listing(html, "https://15gram.be/soepen", &CrawlConfig::default()).unwrap();
+        assert_eq!(all_links.links.len(), 4);
+        assert_eq!(all_links.next_page, None);
+    }
+
+    #[test]
+    fn test_crawl_config_patterns() {
+        let selectors = "[15gram]\ndomain = \"15gram.be\"\n\n[15gram.crawl]\nrecipe_urls = [\"^https://15gram\\\\.be/recepten/\"]\n";
+        let patterns = CrawlConfig::for_url(selectors, "https://15gram.be/sitemap.xml").unwrap().patterns().unwrap();
+        assert!(is_recipe_url(&patterns, "https://15gram.be/recepten/soep"));
+        assert!(!is_recipe_url(&patterns, "https://15gram.be/over-ons"));
+        assert!(is_recipe_url(&[], "https://15gram.be/over-ons"));
+    }
+}
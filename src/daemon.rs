@@ -0,0 +1,100 @@
+//! `daemon`: running library jobs on a cron-style schedule from the `[daemon]` config section.
+//!
+//! ```toml
+//! [[daemon.job]]
+//! task = "refresh"
+//! schedule = "0 3 * * *"
+//!
+//! [[daemon.job]]
+//! task = "feeds"
+//! schedule = "@hourly"
+//! feeds = ["https://15gram.be/feed"]
+//! ```
+
+use chrono::{DateTime, Local};
+use croner::Cron;
+use serde::Deserialize;
+
+/// The scheduled jobs.
+#[derive(Debug, Default, Deserialize)]
+pub struct DaemonConfig {
+    #[serde(rename = "job", default)]
+    pub jobs: Vec<JobConfig>,
+}
+
+/// One scheduled job.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobConfig {
+    pub task: Task,
+    /// A five-field cron expression in local time, or a shortcut such as `@hourly` or `@daily`.
+    pub schedule: String,
+    /// The feeds polled by a `feeds` job.
+    #[serde(default)]
+    pub feeds: Vec<String>,
+}
+
+/// What a job does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Task {
+    /// Re-scrape the library, like `refresh`.
+    Refresh,
+    /// Poll the job's feeds, like `watch-feed --once`.
+    Feeds,
+}
+
+impl Task {
+    pub fn name(self) -> &'static str {
+        match self {
+            Task::Refresh => "refresh",
+            Task::Feeds => "feeds",
+        }
+    }
+}
+
+/// A job with its parsed schedule.
+#[derive(Debug)]
+pub struct ScheduledJob {
+    pub config: JobConfig,
+    cron: Cron,
+}
+
+impl ScheduledJob {
+    /// Parses the job's schedule.
+    pub fn new(config: JobConfig) -> Result<Self, String> {
+        let cron = Cron::new(&config.schedule)
+            .parse()
+            .map_err(|e| format!("Invalid schedule '{}' for the {} job: {}", config.schedule, config.task.name(), e))?;
+        Ok(ScheduledJob { config, cron })
+    }
+
+    /// The first time strictly after `after` the job is due.
+    pub fn next_run(&self, after: &DateTime<Local>) -> Option<DateTime<Local>> {
+        self.cron.find_next_occurrence(after, false).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_daemon_config() {
+        let config: DaemonConfig = toml::from_str(
+            "[[job]]\ntask = \"refresh\"\nschedule = \"0 3 * * *\"\n\n[[job]]\ntask = \"feeds\"\nschedule = \"@hourly\"\nfeeds = [\"https://15gram.be/feed\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.jobs.len(), 2);
+        assert_eq!(config.jobs[1].task, Task::Feeds);
+        assert_eq!(config.jobs[1].feeds, vec!["https://15gram.be/feed"]);
+    }
+
+    #[test]
+    fn test_next_run() {
+        let job = ScheduledJob::new(JobConfig { task: Task::Refresh, schedule: "0 3 * * *".to_string(), feeds: Vec::new() }).unwrap();
+        let now = Local.with_ymd_and_hms(2024, 6, 1, 12, 30, 0).unwrap();
+        assert_eq!(job.next_run(&now), Some(Local.with_ymd_and_hms(2024, 6, 2, 3, 0, 0).unwrap()));
+        assert!(ScheduledJob::new(JobConfig { task: Task::Refresh, schedule: "nightly".to_string(), feeds: Vec::new() }).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! Emailing a scraped recipe as an HTML message with `--email`.
+
+use crate::Recipe;
+use crate::config::{SmtpConfig, SmtpSecurity};
+use crate::render::render_html;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::path::Path;
+
+/// Content id the HTML body uses to show the attached image.
+const IMAGE_CONTENT_ID: &str = "recipe-image";
+
+/// An image to attach to the email.
+pub struct EmailImage {
+    pub file_name: String,
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Sends the recipe to `to` through the configured SMTP server.
+///
+/// The image is attached and shown inline; local images (from `--download-images`) are read from the
+/// output folder, remote ones are downloaded.
+pub async fn send_recipe(
+    client: &reqwest::Client,
+    smtp: &SmtpConfig,
+    to: &str,
+    recipe: &Recipe,
+    output_folder: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let image = match &recipe.image_link {
+        Some(link) => match load_image(client, link, output_folder).await {
+            Ok(image) => Some(image),
+            Err(e) => {
+                tracing::warn!("Sending the email without the image {}: {}", link, e);
+                None
+            }
+        },
+        None => None,
+    };
+    let message = build_message(smtp, to, recipe, image)?;
+
+    let builder = match smtp.security {
+        SmtpSecurity::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)?.port(smtp.port.unwrap_or(587)),
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)?.port(smtp.port.unwrap_or(465)),
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host).port(smtp.port.unwrap_or(25)),
+    };
+    let builder = match (&smtp.username, &smtp.password) {
+        (Some(username), Some(password)) => builder.credentials(Credentials::new(username.clone(), password.clone())),
+        _ => builder,
+    };
+    builder.build().send(message).await?;
+    Ok(())
+}
+
+/// Reads a local image from the output folder, or downloads a remote one.
+pub async fn load_image(client: &reqwest::Client, link: &str, output_folder: &str) -> Result<EmailImage, Box<dyn std::error::Error>> {
+    if url::Url::parse(link).is_err() {
+        let path = Path::new(output_folder).join(link);
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+        let content_type = match extension.as_str() {
+            "png" => "image/png",
+            "webp" => "image/webp",
+            "gif" => "image/gif",
+            _ => "image/jpeg",
+        };
+        let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        return Ok(EmailImage { file_name, content_type: content_type.to_string(), bytes: std::fs::read(&path)? });
+    }
+
+    let response = client.get(link).send().await?.error_for_status()?;
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("image/jpeg")
+        .to_string();
+    let extension = crate::images::image_extension(&content_type).ok_or("The image link does not serve an image.")?;
+    Ok(EmailImage { file_name: format!("image.{}", extension), content_type, bytes: response.bytes().await?.to_vec() })
+}
+
+/// Builds the message: the HTML rendering of the recipe, with the image attached inline when given.
+pub fn build_message(smtp: &SmtpConfig, to: &str, recipe: &Recipe, image: Option<EmailImage>) -> Result<Message, Box<dyn std::error::Error>> {
+    let builder = Message::builder()
+        .from(smtp.from.parse()?)
+        .to(to.parse()?)
+        .subject(recipe.title.clone().unwrap_or_else(|| "Recipe".to_string()));
+    let image_src = image.as_ref().map(|_| format!("cid:{}", IMAGE_CONTENT_ID));
+    let html = SinglePart::html(render_html(recipe, image_src.as_deref(), false));
+
+    let message = match image {
+        Some(image) => {
+            let content_type = ContentType::parse(&image.content_type)?;
+            let inline = Attachment::new_inline(IMAGE_CONTENT_ID.to_string()).body(image.bytes.clone(), content_type.clone());
+            let attached = Attachment::new(image.file_name).body(image.bytes, content_type);
+            builder.multipart(MultiPart::mixed().multipart(MultiPart::related().singlepart(html).singlepart(inline)).singlepart(attached))?
+        }
+        None => builder.singlepart(html)?,
+    };
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_message_attaches_image() {
+        let smtp = SmtpConfig {
+            host: "smtp.example.com".to_string(),
+            port: None,
+            username: None,
+            password: None,
+            from: "FoodScraper <me@example.com>".to_string(),
+            security: SmtpSecurity::Starttls,
+        };
+        let recipe = Recipe { title: Some("Soep".to_string()), ..Default::default() };
+        let image = EmailImage { file_name: "soep.jpg".to_string(), content_type: "image/jpeg".to_string(), bytes: vec![0xff, 0xd8] };
+        let message = String::from_utf8(build_message(&smtp, "oma@example.com", &recipe, Some(image)).unwrap().formatted()).unwrap();
+        assert!(message.contains("Subject: Soep"));
+        assert!(message.contains("To: oma@example.com"));
+        assert!(message.contains("Content-ID: <recipe-image>"));
+        assert!(message.contains("filename=\"soep.jpg\""));
+    }
+}
@@ -0,0 +1,179 @@
+//! The errors of scraping a recipe, by cause, so that callers can tell "the site is down" from "the selectors are
+//! broken". The command-line tool exits with a distinct code per cause:
+//!
+//! | Code | Meaning                                                          |
+//! |------|------------------------------------------------------------------|
+//! | 0    | Success                                                          |
+//! | 1    | Any other error                                                  |
+//! | 2    | Invalid command-line arguments                                   |
+//! | 3    | The recipe was saved, but scored below `--min-confidence`        |
+//! | 4    | Network: the site could not be reached or answered with an error |
+//! | 5    | The recipe page is gone (404/410)                                |
+//! | 6    | Invalid URL, or no configured site covers it                     |
+//! | 7    | Invalid selectors file or selector                               |
+//! | 8    | The page was fetched, but no recipe was found on it              |
+//! | 9    | Reading or writing a file failed                                 |
+//!
+//! With `--error-format json` the error is written to stderr as an [`ErrorReport`] instead.
+
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::fmt;
+
+/// The exit code of a scrape whose recipe scored below `--min-confidence`.
+pub const LOW_CONFIDENCE_EXIT_CODE: u8 = 3;
+
+/// Why a recipe could not be scraped.
+#[derive(Debug)]
+pub enum ScrapeError {
+    /// The page could not be fetched: the site is down or unreachable, or answered with an error status.
+    Network(String),
+    /// The page no longer exists (`404 Not Found` or `410 Gone`).
+    PageGone(StatusCode),
+    /// The URL is invalid, or no site in the selectors file covers it.
+    UnsupportedSite(String),
+    /// The selectors file, or a selector in it, is invalid.
+    Selectors(String),
+    /// The page was fetched, but none of the title, ingredients and steps were found on it.
+    EmptyExtraction(String),
+    /// Reading or writing a file failed.
+    Io(std::io::Error),
+    /// The recipe was saved, but its overall confidence score is below `--min-confidence`.
+    LowConfidence { overall: f64, min: f64 },
+}
+
+impl ScrapeError {
+    /// The exit code the command-line tool reports the error with.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ScrapeError::Network(_) => 4,
+            ScrapeError::PageGone(_) => 5,
+            ScrapeError::UnsupportedSite(_) => 6,
+            ScrapeError::Selectors(_) => 7,
+            ScrapeError::EmptyExtraction(_) => 8,
+            ScrapeError::Io(_) => 9,
+            ScrapeError::LowConfidence { .. } => LOW_CONFIDENCE_EXIT_CODE,
+        }
+    }
+
+    /// The machine-readable name of the cause, e.g. `page_gone`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScrapeError::Network(_) => "network",
+            ScrapeError::PageGone(_) => "page_gone",
+            ScrapeError::UnsupportedSite(_) => "unsupported_site",
+            ScrapeError::Selectors(_) => "selectors",
+            ScrapeError::EmptyExtraction(_) => "empty_extraction",
+            ScrapeError::Io(_) => "io",
+            ScrapeError::LowConfidence { .. } => "low_confidence",
+        }
+    }
+
+    /// What the user can do about the error, if there is something to suggest.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ScrapeError::Network(_) => Some("Check the connection and that the site is up, then try again."),
+            ScrapeError::PageGone(_) => Some("Pass --wayback to extract from an archived copy of the page."),
+            ScrapeError::UnsupportedSite(_) => Some("Add the site to selectors.toml; see \"Adding sites\" in the README."),
+            ScrapeError::Selectors(_) => Some("Fix the selectors file, or get the latest with `FoodJSON selectors update`."),
+            ScrapeError::EmptyExtraction(_) => Some("The site's layout may have changed; check it with `FoodJSON validate --site <site>`."),
+            ScrapeError::Io(_) => None,
+            ScrapeError::LowConfidence { .. } => Some("Check the saved recipe, or lower --min-confidence to accept it."),
+        }
+    }
+}
+
+impl fmt::Display for ScrapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScrapeError::Network(error) => write!(f, "Could not fetch the page: {}", error),
+            ScrapeError::PageGone(status) => write!(f, "Recipe page not found ({}).", status),
+            ScrapeError::UnsupportedSite(reason) => write!(f, "Unsupported URL: {}", reason),
+            ScrapeError::Selectors(error) => write!(f, "{}", error),
+            ScrapeError::EmptyExtraction(url) => write!(f, "No recipe found on {}; the site's selectors may be out of date.", url),
+            ScrapeError::Io(error) => write!(f, "{}", error),
+            ScrapeError::LowConfidence { overall, min } => {
+                write!(f, "Low confidence ({:.2} < {:.2}): check the recipe before relying on it.", overall, min)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScrapeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScrapeError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ScrapeError {
+    fn from(error: std::io::Error) -> Self {
+        ScrapeError::Io(error)
+    }
+}
+
+/// The exit code for an error returned by any command: its [`ScrapeError::exit_code`] when it is a scrape error, 1
+/// otherwise.
+pub fn exit_code(error: &(dyn std::error::Error + 'static)) -> u8 {
+    error.downcast_ref::<ScrapeError>().map_or(1, ScrapeError::exit_code)
+}
+
+/// An error as written by `--error-format json`.
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    /// The [`ScrapeError::kind`], or `other`.
+    pub kind: &'static str,
+    pub message: String,
+    /// The URL being scraped, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<&'static str>,
+    pub exit_code: u8,
+}
+
+impl ErrorReport {
+    /// The report of an error returned by any command while scraping `url`.
+    pub fn new(error: &(dyn std::error::Error + 'static), url: Option<&str>) -> Self {
+        let scrape_error = error.downcast_ref::<ScrapeError>();
+        ErrorReport {
+            kind: scrape_error.map_or("other", ScrapeError::kind),
+            message: error.to_string(),
+            url: url.map(str::to_string),
+            hint: scrape_error.and_then(ScrapeError::hint),
+            exit_code: exit_code(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code() {
+        let gone: Box<dyn std::error::Error> = Box::new(ScrapeError::PageGone(StatusCode::GONE));
+        assert_eq!(exit_code(gone.as_ref()), 5);
+        assert_eq!(gone.to_string(), "Recipe page not found (410 Gone).");
+        let other: Box<dyn std::error::Error> = "Unknown site 'x'.".into();
+        assert_eq!(exit_code(other.as_ref()), 1);
+        assert_eq!(ScrapeError::from(std::io::Error::other("disk full")).exit_code(), 9);
+        assert_eq!(ScrapeError::LowConfidence { overall: 0.42, min: 0.5 }.exit_code(), LOW_CONFIDENCE_EXIT_CODE);
+    }
+
+    #[test]
+    fn test_error_report() {
+        let gone: Box<dyn std::error::Error> = Box::new(ScrapeError::PageGone(StatusCode::NOT_FOUND));
+        let report = serde_json::to_value(ErrorReport::new(gone.as_ref(), Some("https://15gram.be/recepten/soep"))).unwrap();
+        assert_eq!(report["kind"], "page_gone");
+        assert_eq!(report["url"], "https://15gram.be/recepten/soep");
+        assert_eq!(report["exit_code"], 5);
+        assert!(report["hint"].as_str().unwrap().contains("--wayback"));
+
+        let other: Box<dyn std::error::Error> = "Unknown site 'x'.".into();
+        let report = serde_json::to_value(ErrorReport::new(other.as_ref(), None)).unwrap();
+        assert_eq!(report, serde_json::json!({ "kind": "other", "message": "Unknown site 'x'.", "exit_code": 1 }));
+    }
+}
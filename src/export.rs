@@ -0,0 +1,108 @@
+//! Exporting the whole library as a single archive.
+
+use crate::Recipe;
+use crate::config::CardConfig;
+use crate::render::{self, RecipeFormat};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::{Seek, Write};
+use zip::write::SimpleFileOptions;
+
+/// Archive formats the library can be exported to.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// A zip archive.
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// File extension used for this archive format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+        }
+    }
+}
+
+/// One entry of the archive's `index.json`.
+#[derive(Serialize)]
+struct IndexEntry<'a> {
+    file: String,
+    title: Option<&'a str>,
+    source_url: &'a str,
+    scraped_at: Option<&'a str>,
+    tags: &'a [String],
+}
+
+/// Writes the recipes, rendered in `format` (with QR codes if `qr_code`, cards drawn on `card`), into a zip archive
+/// together with an `index.json` listing them.
+pub fn write_zip_archive<W: Write + Seek>(
+    writer: W,
+    recipes: &[Recipe],
+    format: RecipeFormat,
+    qr_code: bool,
+    card: &CardConfig,
+) -> Result<W, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipWriter::new(writer);
+    let options = SimpleFileOptions::default().last_modified_time(archive_timestamp());
+    let mut used_names = HashSet::new();
+    let mut index = Vec::new();
+
+    for recipe in recipes {
+        let stem = foodscraper::slug::recipe_stem(recipe.title.as_deref());
+        let stem = foodscraper::slug::unique_stem(&stem, |candidate| used_names.contains(candidate));
+        used_names.insert(stem.clone());
+        let file = format!("{}.{}", stem, format.extension());
+        archive.start_file(format!("recipes/{}", file), options)?;
+        archive.write_all(&render::render(recipe, format, qr_code, card)?)?;
+        index.push(IndexEntry {
+            file: format!("recipes/{}", file),
+            title: recipe.title.as_deref(),
+            source_url: &recipe.source_url,
+            scraped_at: recipe.scraped_at.as_deref(),
+            tags: &recipe.tags,
+        });
+    }
+
+    archive.start_file("index.json", options)?;
+    archive.write_all(serde_json::to_string_pretty(&index)?.as_bytes())?;
+    Ok(archive.finish()?)
+}
+
+/// The current local time as a zip timestamp (zip entries otherwise default to 1980).
+fn archive_timestamp() -> zip::DateTime {
+    use chrono::{Datelike, Timelike};
+    let now = chrono::Local::now();
+    zip::DateTime::from_date_and_time(
+        now.year() as u16,
+        now.month() as u8,
+        now.day() as u8,
+        now.hour() as u8,
+        now.minute() as u8,
+        now.second() as u8,
+    )
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+
+    #[test]
+    fn test_write_zip_archive_contains_recipes_and_index() {
+        let recipe = Recipe { title: Some("Soep".to_string()), ..Default::default() };
+        let recipes = [recipe.clone(), recipe];
+        let cursor = write_zip_archive(Cursor::new(Vec::new()), &recipes, RecipeFormat::Markdown, false, &CardConfig::default()).unwrap();
+
+        let mut archive = zip::ZipArchive::new(cursor).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(names, vec!["index.json", "recipes/recipe_soep-2.md", "recipes/recipe_soep.md"]);
+
+        let mut index = String::new();
+        archive.by_name("index.json").unwrap().read_to_string(&mut index).unwrap();
+        assert!(index.contains("\"file\": \"recipes/recipe_soep-2.md\""));
+    }
+}
@@ -0,0 +1,94 @@
+//! Watching recipe RSS/Atom feeds for new entries with `watch-feed`.
+//!
+//! The links already handled per feed are kept in `feed-state.json` in the output folder, so restarts
+//! do not scrape the same entries again.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// File name of the feed state inside the output folder.
+pub const STATE_FILE: &str = "feed-state.json";
+
+/// The entry links seen so far, per feed URL.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FeedState {
+    pub feeds: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl FeedState {
+    /// Loads the state from the output folder, starting empty if there is no state file yet.
+    pub fn load(output_folder: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = Path::new(output_folder).join(STATE_FILE);
+        if !path.exists() {
+            return Ok(FeedState::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the state to the output folder.
+    pub fn save(&self, output_folder: &str) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(output_folder)?;
+        fs::write(Path::new(output_folder).join(STATE_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The links of the feed that have not been seen yet, in feed order.
+    pub fn unseen<'a>(&self, feed_url: &str, links: &'a [String]) -> Vec<&'a String> {
+        let seen = self.feeds.get(feed_url);
+        links.iter().filter(|link| seen.is_none_or(|seen| !seen.contains(*link))).collect()
+    }
+
+    /// Marks an entry link of the feed as handled.
+    pub fn mark_seen(&mut self, feed_url: &str, link: &str) {
+        self.feeds.entry(feed_url.to_string()).or_default().insert(link.to_string());
+    }
+}
+
+/// Downloads the feed and returns the entry links, oldest first.
+pub async fn fetch_entry_links(client: &reqwest::Client, feed_url: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let body = client.get(feed_url).send().await?.error_for_status()?.bytes().await?;
+    parse_entry_links(&body, feed_url)
+}
+
+/// Parses an RSS or Atom document into its entry links, oldest first.
+pub fn parse_entry_links(body: &[u8], feed_url: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let feed = feed_rs::parser::Builder::new().base_uri(Some(feed_url)).build().parse(body)?;
+    let mut entries: Vec<_> = feed
+        .entries
+        .into_iter()
+        .filter_map(|entry| {
+            let link = entry.links.first().map(|link| link.href.clone())?;
+            Some((entry.published.or(entry.updated), link))
+        })
+        .collect();
+    entries.sort_by_key(|(date, _)| *date);
+    Ok(entries.into_iter().map(|(_, link)| link).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+        <rss version="2.0"><channel><title>15gram</title>
+            <item><title>Lasagne</title><link>https://15gram.be/recepten/lasagne</link><pubDate>Tue, 04 Jun 2024 10:00:00 GMT</pubDate></item>
+            <item><title>Soep</title><link>https://15gram.be/recepten/soep</link><pubDate>Mon, 03 Jun 2024 10:00:00 GMT</pubDate></item>
+        </channel></rss>"#;
+
+    #[test]
+    fn test_parse_entry_links_oldest_first() {
+        let links = parse_entry_links(RSS.as_bytes(), "https://15gram.be/feed").unwrap();
+        assert_eq!(links, vec!["https://15gram.be/recepten/soep", "https://15gram.be/recepten/lasagne"]);
+    }
+
+    #[test]
+    fn test_feed_state_unseen() {
+        let links = vec!["https://15gram.be/recepten/soep".to_string(), "https://15gram.be/recepten/lasagne".to_string()];
+        let mut state = FeedState::default();
+        state.mark_seen("https://15gram.be/feed", &links[0]);
+        assert_eq!(state.unseen("https://15gram.be/feed", &links), vec![&links[1]]);
+        assert_eq!(state.unseen("https://other.example/feed", &links).len(), 2);
+    }
+}
@@ -0,0 +1,200 @@
+//! HTTP fetching of recipe pages, with support for conditional requests.
+
+use chrono::{DateTime, Utc};
+use encoding_rs::Encoding;
+use regex::bytes::Regex;
+use reqwest::header::{CONTENT_TYPE, ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{StatusCode, Version};
+use std::sync::LazyLock;
+
+pub use foodscraper_core::HttpCache;
+
+/// A `<meta charset>` or `<meta http-equiv="Content-Type" content="...; charset=...">` declaration.
+static META_CHARSET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)<meta\s[^>]*charset\s*=\s*["']?\s*([a-z0-9_:.-]+)"#).unwrap());
+
+/// Reads the caching validators from response headers, returning `None` if the server sent neither.
+fn cache_from_headers(headers: &HeaderMap) -> Option<HttpCache> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+    let cache = HttpCache { etag: header(ETAG), last_modified: header(LAST_MODIFIED) };
+    (cache.etag.is_some() || cache.last_modified.is_some()).then_some(cache)
+}
+
+/// A downloaded page, with enough of the exchange kept to archive it.
+#[derive(Debug)]
+pub struct FetchedPage {
+    /// The URL that was requested.
+    pub url: String,
+    /// The request headers that were sent besides `Host`.
+    pub request_headers: HeaderMap,
+    pub status: StatusCode,
+    pub version: Version,
+    pub headers: HeaderMap,
+    /// The response body as received.
+    pub raw: Vec<u8>,
+    /// The response body decoded to text.
+    pub body: String,
+    /// Validators for a later conditional request, if the server sent any.
+    pub cache: Option<HttpCache>,
+    /// When the response was received.
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// The result of fetching a page.
+#[derive(Debug)]
+pub enum FetchOutcome {
+    /// The page was downloaded.
+    Fetched(Box<FetchedPage>),
+    /// The server confirmed the page has not changed since the cached validators were issued.
+    NotModified,
+    /// The page no longer exists (`404 Not Found` or `410 Gone`).
+    Missing(StatusCode),
+}
+
+/// A response as the fetcher sees it, whether it came from the network or, with the `cassette` feature, from disk.
+#[derive(Debug)]
+pub struct RawResponse {
+    pub status: StatusCode,
+    pub version: Version,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// Sends a GET request for the URL, through the cassette configured in the environment if there is one (see
+/// [`crate::cassette`]).
+#[tracing::instrument(level = "debug", skip(client, request_headers))]
+async fn send(client: &reqwest::Client, url: &str, request_headers: &HeaderMap) -> Result<RawResponse, Box<dyn std::error::Error>> {
+    let started = std::time::Instant::now();
+    #[cfg(feature = "cassette")]
+    let cassette = crate::cassette::Cassette::from_env()?;
+    #[cfg(feature = "cassette")]
+    let response = match cassette {
+        Some(cassette) => cassette.send(client, url, request_headers).await?,
+        None => send_network(client, url, request_headers).await?,
+    };
+    #[cfg(not(feature = "cassette"))]
+    let response = send_network(client, url, request_headers).await?;
+    tracing::debug!(status = %response.status, bytes = response.body.len(), elapsed_ms = started.elapsed().as_millis() as u64, "response");
+    Ok(response)
+}
+
+/// Sends a GET request for the URL over the network.
+pub(crate) async fn send_network(client: &reqwest::Client, url: &str, request_headers: &HeaderMap) -> Result<RawResponse, reqwest::Error> {
+    let response = client.get(url).headers(request_headers.clone()).send().await?;
+    let (status, version, headers) = (response.status(), response.version(), response.headers().clone());
+    Ok(RawResponse { status, version, headers, body: response.bytes().await?.to_vec() })
+}
+
+/// The error for a status other than a success, a redirect or the ones the callers handle.
+fn error_status(url: &str, status: StatusCode) -> Box<dyn std::error::Error> {
+    format!("HTTP status {} for {}", status, url).into()
+}
+
+/// Fetches a page, sending `If-None-Match`/`If-Modified-Since` when cached validators are given.
+///
+/// Other error statuses are returned as errors.
+pub async fn fetch_page(
+    client: &reqwest::Client,
+    url: &str,
+    cache: Option<&HttpCache>,
+) -> Result<FetchOutcome, Box<dyn std::error::Error>> {
+    let mut request_headers = HeaderMap::new();
+    if let Some(cache) = cache {
+        if let Some(etag) = cache.etag.as_deref().and_then(|etag| etag.parse().ok()) {
+            request_headers.insert(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = cache.last_modified.as_deref().and_then(|date| date.parse().ok()) {
+            request_headers.insert(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = send(client, url, &request_headers).await?;
+    match response.status {
+        StatusCode::NOT_MODIFIED => Ok(FetchOutcome::NotModified),
+        status @ (StatusCode::NOT_FOUND | StatusCode::GONE) => Ok(FetchOutcome::Missing(status)),
+        status if status.is_client_error() || status.is_server_error() => Err(error_status(url, status)),
+        status => Ok(FetchOutcome::Fetched(Box::new(FetchedPage {
+            url: url.to_string(),
+            request_headers,
+            status,
+            version: response.version,
+            cache: cache_from_headers(&response.headers),
+            body: decode_body(&response.body, &response.headers),
+            headers: response.headers,
+            raw: response.body,
+            fetched_at: Utc::now(),
+        }))),
+    }
+}
+
+/// Decodes a response body, taking the encoding from, in order: a byte order mark, the charset of the
+/// `Content-Type` header, and a `<meta>` charset declaration in the first 1024 bytes. Undeclared bodies that are not
+/// valid UTF-8 are read as windows-1252, which is what older European sites serve.
+pub fn decode_body(raw: &[u8], headers: &HeaderMap) -> String {
+    let header_charset = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_type| {
+            // Parameter names are case-insensitive, and some servers put spaces around the `=`.
+            content_type.split(';').filter_map(|part| part.split_once('=')).find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        })
+        .map(|(_, charset)| charset.trim().trim_matches('"').as_bytes());
+    let meta_charset = || META_CHARSET.captures(&raw[..raw.len().min(1024)]).map(|captures| captures.get(1).unwrap().as_bytes());
+    let declared = Encoding::for_bom(raw).map(|(encoding, _)| encoding).or_else(|| {
+        let encoding = header_charset.and_then(Encoding::for_label).or_else(|| meta_charset().and_then(Encoding::for_label))?;
+        // A page that made it to us as bytes is not UTF-16, whatever it declares (the HTML spec's rule).
+        Some(if encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE { encoding_rs::UTF_8 } else { encoding })
+    });
+    let encoding = declared.unwrap_or(if std::str::from_utf8(raw).is_ok() { encoding_rs::UTF_8 } else { encoding_rs::WINDOWS_1252 });
+    encoding.decode(raw).0.into_owned()
+}
+
+/// Fetches a page as text, decoded like recipe pages (see [`decode_body`]); error statuses are returned as errors.
+pub async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let response = send(client, url, &HeaderMap::new()).await?;
+    if response.status.is_client_error() || response.status.is_server_error() {
+        return Err(error_status(url, response.status));
+    }
+    Ok(decode_body(&response.body, &response.headers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_http_cache_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        let cache = cache_from_headers(&headers).unwrap();
+        assert_eq!(cache.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(cache.last_modified, None);
+        assert!(cache_from_headers(&HeaderMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_decode_body_uses_header_charset() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html; charset=ISO-8859-1"));
+        assert_eq!(decode_body(b"cr\xe8me br\xfbl\xe9e", &headers), "crème brûlée");
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html; Charset=ISO-8859-1"));
+        assert_eq!(decode_body(b"cr\xe8me", &headers), "crème");
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html; CHARSET = \"iso-8859-1\""));
+        assert_eq!(decode_body(b"cr\xe8me", &headers), "crème");
+        assert_eq!(decode_body("crème".as_bytes(), &HeaderMap::new()), "crème");
+    }
+
+    #[test]
+    fn test_decode_body_sniffs_the_charset() {
+        let meta = b"<html><head><meta charset=\"iso-8859-1\"><title>Cr\xe8me</title>";
+        assert!(decode_body(meta, &HeaderMap::new()).contains("Crème"));
+        let http_equiv = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1252\"><p>\x80 5</p>";
+        assert!(decode_body(http_equiv, &HeaderMap::new()).contains("€ 5"));
+        assert_eq!(decode_body(b"<p>Cr\xe8me</p>", &HeaderMap::new()), "<p>Crème</p>");
+        assert_eq!(decode_body(b"\xef\xbb\xbf<p>Cr\xc3\xa8me</p>", &HeaderMap::new()), "<p>Crème</p>");
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html; charset=utf-8"));
+        assert_eq!(decode_body("<meta charset=\"iso-8859-1\"><p>Crème</p>".as_bytes(), &headers), "<meta charset=\"iso-8859-1\"><p>Crème</p>");
+    }
+}
@@ -0,0 +1,113 @@
+//! The C interface of the library, declared in `include/foodscraper.h`.
+//!
+//! Functions return a newly allocated, NUL-terminated JSON string that the caller releases with
+//! [`foodscraper_string_free`], or `NULL` on failure, in which case [`foodscraper_last_error`]
+//! describes what went wrong on the calling thread.
+
+use crate::Recipe;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Fetches the page at `url` and returns the extracted recipe as JSON.
+///
+/// # Safety
+///
+/// `url` must be a valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn foodscraper_scrape(url: *const c_char) -> *mut c_char {
+    // SAFETY: the caller passes a valid C string.
+    unsafe { foodscraper_scrape_with_user_agent(url, std::ptr::null()) }
+}
+
+/// Like [`foodscraper_scrape`], sending `user_agent` as the `User-Agent` header unless it is `NULL`.
+///
+/// # Safety
+///
+/// `url` must be a valid NUL-terminated string, and `user_agent` one or `NULL`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn foodscraper_scrape_with_user_agent(url: *const c_char, user_agent: *const c_char) -> *mut c_char {
+    // SAFETY: the caller passes a valid C string, and a valid one or NULL as the user agent.
+    let user_agent = if user_agent.is_null() { Ok(None) } else { unsafe { read_str(user_agent) }.map(Some) };
+    let result = unsafe { read_str(url) }.and_then(|url| {
+        let client = crate::http_client(user_agent?);
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        Ok(runtime.block_on(crate::scrape_page(&client, url))?.0)
+    });
+    recipe_json(result)
+}
+
+/// Extracts the recipe from already fetched `html`, using the selectors of the site `url` belongs to.
+///
+/// # Safety
+///
+/// `html` and `url` must be valid NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn foodscraper_extract_from_html(html: *const c_char, url: *const c_char) -> *mut c_char {
+    // SAFETY: the caller passes valid C strings.
+    let result = unsafe { read_str(html) }.and_then(|html| Ok(crate::extract_from_html(html, unsafe { read_str(url) }?)?));
+    recipe_json(result)
+}
+
+/// The message of the last failed call on this thread, or `NULL`. The string stays valid until the next call.
+#[unsafe(no_mangle)]
+pub extern "C" fn foodscraper_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(std::ptr::null(), |message| message.as_ptr()))
+}
+
+/// Releases a string returned by this library. Passing `NULL` is allowed.
+///
+/// # Safety
+///
+/// `string` must come from this library and must not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn foodscraper_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        // SAFETY: the string was allocated by `CString::into_raw` in this module.
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+/// Borrows a C string as UTF-8.
+unsafe fn read_str<'a>(string: *const c_char) -> Result<&'a str, Box<dyn std::error::Error>> {
+    if string.is_null() {
+        return Err("Unexpected NULL string.".into());
+    }
+    // SAFETY: checked for NULL; the caller guarantees NUL termination.
+    Ok(unsafe { CStr::from_ptr(string) }.to_str()?)
+}
+
+/// Serializes the recipe for the caller, or records the error and returns `NULL`.
+fn recipe_json(result: Result<Recipe, Box<dyn std::error::Error>>) -> *mut c_char {
+    let json = result.and_then(|recipe| Ok(CString::new(serde_json::to_string(&recipe)?)?));
+    let (value, error) = match json {
+        Ok(json) => (json.into_raw(), None),
+        Err(error) => (std::ptr::null_mut(), CString::new(error.to_string().replace('\0', "")).ok()),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = error);
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_from_html_reports_errors() {
+        let html = CString::new("<h1>Soep</h1>").unwrap();
+        let url = CString::new("https://www.example.com/soep").unwrap();
+        let json = unsafe { foodscraper_extract_from_html(html.as_ptr(), url.as_ptr()) };
+        assert!(json.is_null());
+        let error = unsafe { CStr::from_ptr(foodscraper_last_error()) };
+        assert_eq!(error.to_str().unwrap(), "Unsupported URL: no site in the selectors file covers https://www.example.com/soep.");
+
+        assert!(unsafe { foodscraper_scrape(std::ptr::null()) }.is_null());
+        let user_agent = CString::new("FoodScraper tests").unwrap();
+        assert!(unsafe { foodscraper_scrape_with_user_agent(std::ptr::null(), user_agent.as_ptr()) }.is_null());
+        assert_eq!(unsafe { CStr::from_ptr(foodscraper_last_error()) }.to_str().unwrap(), "Unexpected NULL string.");
+        unsafe { foodscraper_string_free(std::ptr::null_mut()) };
+    }
+}
@@ -0,0 +1,96 @@
+//! Searching a site for recipes with `find`, through the site's own search page.
+//!
+//! The search page and how to read its results come from the site's `[<site>.search]` table in `selectors.toml`:
+//!
+//! ```toml
+//! [15gram.search]
+//! url = "https://15gram.be/zoeken?q={query}"
+//! result = ".search-result a"
+//! ```
+
+use crate::crawl::{self, CrawlConfig};
+use serde::Deserialize;
+
+/// The search settings of a site.
+#[derive(Debug, Deserialize)]
+pub struct SearchConfig {
+    /// The search page URL, with `{query}` where the search words go.
+    pub url: String,
+    /// Selector for the result links on the search page.
+    pub result: String,
+}
+
+impl SearchConfig {
+    /// Reads the `[<site>.search]` table of a site, given by its name in `selectors.toml` or by its domain.
+    pub fn for_site(selectors: &str, site: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let sites: toml::Table = toml::from_str(selectors)?;
+        let Some(key) = foodscraper::site_key(selectors, site) else {
+            return Err(format!("Unknown site '{}'.", site).into());
+        };
+        match sites.get(&key).and_then(|site| site.get("search")) {
+            Some(search) => Ok(search.clone().try_into()?),
+            None => Err(format!("Site '{}' has no [{}.search] settings.", key, key).into()),
+        }
+    }
+
+    /// The search page URL for the query.
+    pub fn search_url(&self, query: &str) -> String {
+        let query: String = url::form_urlencoded::byte_serialize(query.as_bytes()).collect();
+        self.url.replace("{query}", &query)
+    }
+}
+
+/// Reads the result links from a search page, in the order the site ranks them, without duplicates.
+pub fn parse_results(html: &str, page_url: &str, config: &SearchConfig) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let listing = CrawlConfig { recipe_link: Some(config.result.clone()), ..Default::default() };
+    let mut seen = std::collections::HashSet::new();
+    let links = crawl::parse_listing(html, page_url, &listing)?.links;
+    Ok(links.into_iter().map(|link| crate::urls::normalize_url(&link)).filter(|link| seen.insert(link.clone())).collect())
+}
+
+/// Runs the search on the site and returns the matching recipe URLs.
+pub async fn find_recipes(
+    client: &reqwest::Client,
+    config: &SearchConfig,
+    query: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let page_url = config.search_url(query);
+    let body = crate::fetch::fetch_text(client, &page_url).await?;
+    parse_results(&body, &page_url, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SELECTORS: &str = r#"
+        [15gram]
+        domain = "15gram.be"
+        [15gram.search]
+        url = "https://15gram.be/zoeken?q={query}"
+        result = ".search-result a"
+
+        [dagelijksekost]
+        domain = "dagelijksekost.vrt.be"
+    "#;
+
+    #[test]
+    fn test_search_config_for_site() {
+        let config = SearchConfig::for_site(SELECTORS, "15gram").unwrap();
+        assert_eq!(config.search_url("courgette soep"), "https://15gram.be/zoeken?q=courgette+soep");
+        assert!(SearchConfig::for_site(SELECTORS, "www.15gram.be").is_ok());
+        assert!(SearchConfig::for_site(SELECTORS, "dagelijksekost").is_err());
+        assert!(SearchConfig::for_site(SELECTORS, "example.com").is_err());
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let html = r#"
+            <div class="search-result"><a href="/recepten/courgettesoep?utm_source=search">Courgettesoep</a></div>
+            <div class="search-result"><a href="/recepten/courgettesoep">Courgettesoep</a></div>
+            <div class="search-result"><a href="/recepten/gevulde-courgette">Gevulde courgette</a></div>"#;
+        let config = SearchConfig::for_site(SELECTORS, "15gram").unwrap();
+        let results = parse_results(html, "https://15gram.be/zoeken?q=courgette", &config).unwrap();
+        assert_eq!(results, vec!["https://15gram.be/recepten/courgettesoep", "https://15gram.be/recepten/gevulde-courgette"]);
+    }
+}
@@ -0,0 +1,176 @@
+//! A GraphQL schema over the recipe library, served by `serve` at `/graphql` with the `graphql` feature.
+
+use crate::ingredients::parse_ingredient;
+use crate::server::{RecipesQuery, SavedRecipe, ServerState, list_recipes};
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Result, Schema, SimpleObject};
+use std::sync::Arc;
+
+pub type LibrarySchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Builds the schema, reading the library described by `state`.
+pub fn schema(state: Arc<ServerState>) -> LibrarySchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription).data(state).finish()
+}
+
+fn load(ctx: &Context<'_>, query: &RecipesQuery) -> Result<Vec<RecipeNode>> {
+    let state = ctx.data::<Arc<ServerState>>()?;
+    let recipes = list_recipes(state, query).map_err(|e| async_graphql::Error::new(e.to_string()))?;
+    Ok(recipes.into_iter().map(RecipeNode).collect())
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The saved recipes, optionally filtered on site, tag or favorites.
+    async fn recipes(
+        &self,
+        ctx: &Context<'_>,
+        site: Option<String>,
+        tag: Option<String>,
+        #[graphql(default)] favorites: bool,
+    ) -> Result<Vec<RecipeNode>> {
+        load(ctx, &RecipesQuery { site, tag, favorites, q: None })
+    }
+
+    /// A single recipe by its location (`#id` or file path).
+    async fn recipe(&self, ctx: &Context<'_>, location: String) -> Result<Option<RecipeNode>> {
+        Ok(load(ctx, &RecipesQuery::default())?.into_iter().find(|node| node.0.location == location))
+    }
+
+    /// Full-text search over titles, ingredients and steps.
+    async fn search(&self, ctx: &Context<'_>, query: String) -> Result<Vec<RecipeNode>> {
+        load(ctx, &RecipesQuery { q: Some(query), ..Default::default() })
+    }
+
+    /// Every tag in the library with the number of recipes carrying it, most used first.
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<TagCount>> {
+        let mut counts: Vec<TagCount> = Vec::new();
+        for node in load(ctx, &RecipesQuery::default())? {
+            for tag in &node.0.recipe.tags {
+                match counts.iter_mut().find(|count| count.name.eq_ignore_ascii_case(tag)) {
+                    Some(count) => count.count += 1,
+                    None => counts.push(TagCount { name: tag.clone(), count: 1 }),
+                }
+            }
+        }
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        Ok(counts)
+    }
+}
+
+/// A tag and how many recipes carry it.
+#[derive(SimpleObject)]
+pub struct TagCount {
+    name: String,
+    count: i32,
+}
+
+/// An ingredient line with the quantity, unit and name parsed out of it.
+#[derive(SimpleObject)]
+pub struct Ingredient {
+    /// The line as scraped.
+    text: String,
+    quantity: Option<f64>,
+    unit: Option<String>,
+    name: String,
+}
+
+/// A saved recipe.
+pub struct RecipeNode(SavedRecipe);
+
+#[Object(name = "Recipe")]
+impl RecipeNode {
+    /// Where the recipe is stored: `#id` in the database or a JSON file path.
+    async fn location(&self) -> &str {
+        &self.0.location
+    }
+
+    async fn title(&self) -> Option<&str> {
+        self.0.recipe.title.as_deref()
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.recipe.description.as_deref()
+    }
+
+    async fn ingredients(&self) -> Vec<Ingredient> {
+        let lines = self.0.recipe.ingredients.iter().flatten();
+        lines
+            .map(|line| {
+                let parsed = parse_ingredient(line);
+                Ingredient { text: line.clone(), quantity: parsed.quantity, unit: parsed.unit, name: parsed.name }
+            })
+            .collect()
+    }
+
+    async fn steps(&self) -> Vec<String> {
+        self.0.recipe.steps.clone().unwrap_or_default()
+    }
+
+    async fn image_link(&self) -> Option<&str> {
+        self.0.recipe.image_link.as_deref()
+    }
+
+    async fn thumbnail(&self) -> Option<&str> {
+        self.0.recipe.thumbnail.as_deref()
+    }
+
+    /// The total time in minutes: the scraped total time, or else the sum of the step timers.
+    async fn total_time(&self) -> Option<u32> {
+        crate::plan::recipe_minutes(&self.0.recipe)
+    }
+
+    async fn language(&self) -> Option<&str> {
+        self.0.recipe.language.as_deref()
+    }
+
+    async fn source_url(&self) -> &str {
+        &self.0.recipe.source_url
+    }
+
+    /// When the recipe was scraped, as an RFC 3339 timestamp.
+    async fn scraped_at(&self) -> Option<&str> {
+        self.0.recipe.scraped_at.as_deref()
+    }
+
+    async fn tags(&self) -> &[String] {
+        &self.0.recipe.tags
+    }
+
+    async fn favorite(&self) -> bool {
+        self.0.recipe.favorite
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OnConflict, Recipe, StoreMode};
+
+    #[tokio::test]
+    async fn test_schema_queries_recipes_and_tags() {
+        let folder = std::env::temp_dir().join(format!("foodscraper-graphql-{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let recipe = Recipe {
+            title: Some("Soep".to_string()),
+            ingredients: Some(vec!["500 gr wortelen".to_string()]),
+            source_url: "https://15gram.be/recepten/soep".to_string(),
+            tags: vec!["winter".to_string()],
+            ..Default::default()
+        };
+        std::fs::write(folder.join("recipe_Soep.json"), serde_json::to_string(&recipe).unwrap()).unwrap();
+
+        let output_folder = folder.to_str().unwrap().to_string();
+        let state = ServerState { db_path: format!("{}/recipes.db", output_folder), output_folder, store: StoreMode::Json, on_conflict: OnConflict::Overwrite, client: reqwest::Client::new() };
+        let response = schema(Arc::new(state))
+            .execute("{ recipes(tag: \"winter\") { title ingredients { quantity unit name } } tags { name count } }")
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["recipes"][0]["title"], "Soep");
+        assert_eq!(data["recipes"][0]["ingredients"][0]["unit"], "g");
+        assert_eq!(data["tags"][0]["count"], 1);
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+}
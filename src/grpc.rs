@@ -0,0 +1,146 @@
+//! The gRPC scraping service defined in `proto/foodscraper.proto`, started by `serve --grpc-port`.
+
+use crate::Recipe;
+use proto::scraper_server::{Scraper, ScraperServer};
+use proto::{BatchScrapeResult, ScrapeRequest, batch_scrape_result};
+use std::pin::Pin;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
+
+/// Types generated from the proto file by the build script.
+#[allow(clippy::large_enum_variant)]
+pub mod proto {
+    tonic::include_proto!("foodscraper");
+}
+
+/// Serves the gRPC service on `address` until the process is stopped, scraping with `client`.
+pub async fn serve(address: &str, client: reqwest::Client) -> Result<(), Box<dyn std::error::Error>> {
+    status!("Serving the gRPC scraping service on {}", address);
+    tonic::transport::Server::builder()
+        .add_service(ScraperServer::new(ScraperService { client }))
+        .serve(address.parse()?)
+        .await?;
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct ScraperService {
+    client: reqwest::Client,
+}
+
+/// The message both RPCs report an invalid or unsupported URL with.
+const INVALID_URL: &str = "Invalid URL or unsupported domain.";
+
+/// Whether the URL is valid and a site in the selectors covers it.
+fn is_supported(selectors: &str, url: &str) -> bool {
+    crate::validate_url(url).is_ok() && crate::site_for_url(selectors, url).is_some()
+}
+
+/// Scrapes one URL, turning errors into their message so the result can cross task boundaries.
+async fn scrape(client: &reqwest::Client, selectors: &str, url: &str) -> Result<proto::Recipe, String> {
+    match crate::scrape_page_with_selectors(client, url, selectors).await {
+        Ok((recipe, _)) => Ok(recipe.into()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[tonic::async_trait]
+impl Scraper for ScraperService {
+    async fn scrape(&self, request: Request<ScrapeRequest>) -> Result<Response<proto::Recipe>, Status> {
+        let url = request.into_inner().url;
+        let selectors = crate::read_selectors_file().map_err(|e| Status::internal(e.to_string()))?;
+        if !is_supported(&selectors, &url) {
+            return Err(Status::invalid_argument(INVALID_URL));
+        }
+        scrape(&self.client, &selectors, &url).await.map(Response::new).map_err(Status::unavailable)
+    }
+
+    type BatchScrapeStream = Pin<Box<dyn Stream<Item = Result<BatchScrapeResult, Status>> + Send>>;
+
+    async fn batch_scrape(&self, request: Request<Streaming<ScrapeRequest>>) -> Result<Response<Self::BatchScrapeStream>, Status> {
+        let mut requests = request.into_inner();
+        let client = self.client.clone();
+        // Read once for the whole stream, so its URLs are all checked and scraped with the same selectors.
+        let selectors = crate::read_selectors_file().map_err(|e| Status::internal(e.to_string()))?;
+        let (sender, receiver) = tokio::sync::mpsc::channel(8);
+        tokio::spawn(async move {
+            while let Some(request) = requests.next().await {
+                let result = match request {
+                    Ok(ScrapeRequest { url }) => {
+                        let result = if !is_supported(&selectors, &url) {
+                            batch_scrape_result::Result::Error(INVALID_URL.to_string())
+                        } else {
+                            match scrape(&client, &selectors, &url).await {
+                                Ok(recipe) => batch_scrape_result::Result::Recipe(recipe),
+                                Err(error) => batch_scrape_result::Result::Error(error),
+                            }
+                        };
+                        Ok(BatchScrapeResult { url, result: Some(result) })
+                    }
+                    Err(status) => Err(status),
+                };
+                if sender.send(result).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(receiver)) as Self::BatchScrapeStream))
+    }
+}
+
+impl From<Recipe> for proto::Recipe {
+    fn from(recipe: Recipe) -> Self {
+        proto::Recipe {
+            title: recipe.title,
+            description: recipe.description,
+            ingredients: recipe.ingredients.unwrap_or_default(),
+            steps: recipe.steps.unwrap_or_default(),
+            timers: recipe
+                .timers
+                .unwrap_or_default()
+                .into_iter()
+                .map(|timer| proto::StepTimer {
+                    step: timer.step as u32,
+                    text: timer.text,
+                    seconds: timer.seconds,
+                    max_seconds: timer.max_seconds,
+                })
+                .collect(),
+            image_link: recipe.image_link,
+            total_time: recipe.total_time,
+            language: recipe.language,
+            source_url: recipe.source_url,
+            scraped_at: recipe.scraped_at,
+            tags: recipe.tags,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::steps::StepTimer;
+
+    #[test]
+    fn test_recipe_into_proto() {
+        let recipe = Recipe {
+            title: Some("Soep".to_string()),
+            steps: Some(vec!["Kook 20 minuten.".to_string()]),
+            timers: Some(vec![StepTimer { step: 0, text: "20 minuten".to_string(), seconds: 1200, max_seconds: None }]),
+            source_url: "https://15gram.be/recepten/soep".to_string(),
+            ..Default::default()
+        };
+        let message = proto::Recipe::from(recipe);
+        assert_eq!(message.title.as_deref(), Some("Soep"));
+        assert!(message.ingredients.is_empty());
+        assert_eq!(message.timers[0].seconds, 1200);
+    }
+
+    #[test]
+    fn test_is_supported() {
+        assert!(is_supported(foodscraper::DEFAULT_SELECTORS, "https://15gram.be/recepten/soep"));
+        assert!(!is_supported(foodscraper::DEFAULT_SELECTORS, "https://example.com/soep"));
+        assert!(!is_supported(foodscraper::DEFAULT_SELECTORS, "15gram.be/recepten/soep"));
+    }
+}
@@ -0,0 +1,64 @@
+//! Downloading recipe images next to the saved recipe.
+
+use reqwest::header::CONTENT_TYPE;
+use std::fs;
+use std::path::Path;
+use url::Url;
+
+pub use foodscraper_core::images::{DEFAULT_IMAGE_ATTRIBUTES, image_link_from, largest_srcset_candidate, resolve_link};
+
+/// Downloads the image at `image_link` into `folder` as `<stem>.<ext>` and returns the file name.
+///
+/// Relative links are resolved against the page URL. Redirects are followed, and the response must
+/// be served with an `image/*` content type.
+pub async fn download_image(
+    client: &reqwest::Client,
+    image_link: &str,
+    page_url: &str,
+    folder: &str,
+    stem: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let url = Url::parse(page_url)?.join(image_link)?;
+    let response = client.get(url.clone()).send().await?.error_for_status()?;
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let extension = image_extension(&content_type)
+        .ok_or_else(|| format!("'{}' is not an image (content type '{}').", url, content_type))?;
+
+    let bytes = response.bytes().await?;
+    fs::create_dir_all(folder)?;
+    let file_name = format!("{}.{}", stem, extension);
+    fs::write(Path::new(folder).join(&file_name), &bytes)?;
+    Ok(file_name)
+}
+
+/// The file extension for an image content type, or `None` if it is not an image.
+pub fn image_extension(content_type: &str) -> Option<&'static str> {
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    match mime.strip_prefix("image/")? {
+        "jpeg" | "jpg" | "pjpeg" => Some("jpg"),
+        "png" => Some("png"),
+        "webp" => Some("webp"),
+        "gif" => Some("gif"),
+        "avif" => Some("avif"),
+        "svg+xml" => Some("svg"),
+        _ => Some("img"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_extension() {
+        assert_eq!(image_extension("image/jpeg"), Some("jpg"));
+        assert_eq!(image_extension("image/webp; charset=binary"), Some("webp"));
+        assert_eq!(image_extension("image/x-icon"), Some("img"));
+        assert_eq!(image_extension("text/html; charset=utf-8"), None);
+    }
+}
@@ -0,0 +1,249 @@
+//! Importing recipe exports into the library.
+//!
+//! Besides our own recipe JSON files this understands Mealie exports (which follow the schema.org
+//! Recipe shape) and Paprika `.paprikarecipes` archives (a zip of gzipped JSON recipes).
+
+use crate::Recipe;
+use flate2::read::GzDecoder;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Collects the importable files at the given paths, descending one level into directories.
+pub fn collect_import_files(paths: &[PathBuf]) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| is_importable(path))
+                .collect();
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Whether the file extension is one `read_import_file` understands.
+fn is_importable(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json" || ext == "paprikarecipes")
+}
+
+/// Reads every recipe contained in an export file.
+pub fn read_import_file(path: &Path) -> Result<Vec<Recipe>, Box<dyn std::error::Error>> {
+    if path.extension().is_some_and(|ext| ext == "paprikarecipes") {
+        return read_paprika_archive(path);
+    }
+
+    let value: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let items = match value {
+        Value::Array(items) => items,
+        item => vec![item],
+    };
+    items.iter().map(|item| parse_recipe_value(item).ok_or_else(|| unknown_format(path))).collect()
+}
+
+fn unknown_format(path: &Path) -> Box<dyn std::error::Error> {
+    format!("'{}' is not a recognised recipe export.", path.display()).into()
+}
+
+/// Reads a Paprika archive: every entry is a gzip-compressed JSON recipe.
+fn read_paprika_archive(path: &Path) -> Result<Vec<Recipe>, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    let mut recipes = Vec::new();
+    for index in 0..archive.len() {
+        let mut json = String::new();
+        GzDecoder::new(archive.by_index(index)?).read_to_string(&mut json)?;
+        let value: Value = serde_json::from_str(&json)?;
+        recipes.push(parse_paprika(&value).ok_or_else(|| unknown_format(path))?);
+    }
+    Ok(recipes)
+}
+
+/// Detects the export format of a single JSON recipe and converts it, normalizing its source URL.
+pub fn parse_recipe_value(value: &Value) -> Option<Recipe> {
+    let mut recipe = if value.get("source_url").is_some() && value.get("title").is_some() {
+        serde_json::from_value(value.clone()).ok()
+    } else if value.get("recipeIngredient").is_some() || value.get("recipeInstructions").is_some() {
+        parse_schema_org(value)
+    } else if value.get("directions").is_some() {
+        parse_paprika(value)
+    } else {
+        None
+    }?;
+    recipe.source_url = crate::urls::normalize_url(&recipe.source_url);
+    Some(recipe)
+}
+
+/// Converts a Mealie or other schema.org-shaped recipe.
+fn parse_schema_org(value: &Value) -> Option<Recipe> {
+    let ingredients = value.get("recipeIngredient").and_then(Value::as_array).map(|items| {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(text) => Some(text.clone()),
+                _ => ["display", "originalText", "note"]
+                    .iter()
+                    .find_map(|key| item.get(key).and_then(Value::as_str).filter(|text| !text.is_empty()))
+                    .map(str::to_string),
+            })
+            .collect()
+    });
+    let steps = value.get("recipeInstructions").map(|instructions| match instructions {
+        Value::String(text) => split_lines(text),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().or_else(|| item.get("text").and_then(Value::as_str)))
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    });
+    let image_link = match value.get("image") {
+        Some(Value::String(url)) => Some(url.clone()),
+        Some(Value::Array(urls)) => urls.first().and_then(Value::as_str).map(str::to_string),
+        Some(image) => image.get("url").and_then(Value::as_str).map(str::to_string),
+        None => None,
+    };
+    let tags = value
+        .get("tags")
+        .or_else(|| value.get("keywords"))
+        .and_then(Value::as_array)
+        .map(|tags| {
+            tags.iter()
+                .filter_map(|tag| tag.as_str().or_else(|| tag.get("name").and_then(Value::as_str)))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(Recipe {
+        title: Some(value.get("name")?.as_str()?.to_string()),
+        description: string_field(value, "description"),
+        ingredients,
+        steps,
+        image_link,
+        source_url: string_field(value, "orgURL").or_else(|| string_field(value, "url")).unwrap_or_default(),
+        total_time: string_field(value, "totalTime").and_then(|duration| parse_iso_duration_minutes(&duration)),
+        tags,
+        ..Default::default()
+    })
+}
+
+/// Converts a Paprika recipe, whose ingredients and directions are newline-separated strings.
+fn parse_paprika(value: &Value) -> Option<Recipe> {
+    Some(Recipe {
+        title: Some(value.get("name")?.as_str()?.to_string()),
+        description: string_field(value, "description"),
+        ingredients: string_field(value, "ingredients").map(|text| split_lines(&text)),
+        steps: string_field(value, "directions").map(|text| split_lines(&text)),
+        image_link: string_field(value, "image_url"),
+        source_url: string_field(value, "source_url").unwrap_or_default(),
+        total_time: string_field(value, "total_time").and_then(|duration| crate::steps::parse_duration_minutes(&duration)),
+        tags: value
+            .get("categories")
+            .and_then(Value::as_array)
+            .map(|tags| tags.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default(),
+        ..Default::default()
+    })
+}
+
+/// Parses an ISO 8601 duration such as `PT1H30M` into whole minutes.
+pub fn parse_iso_duration_minutes(duration: &str) -> Option<u32> {
+    let rest = duration.trim().strip_prefix('P')?;
+    let (days, time) = rest.split_once('T').unwrap_or((rest, ""));
+    const DATE_UNITS: &[(char, f64)] = &[('W', 604_800.0), ('D', 86_400.0)];
+    const TIME_UNITS: &[(char, f64)] = &[('H', 3600.0), ('M', 60.0), ('S', 1.0)];
+
+    let mut seconds = 0.0;
+    for (part, units) in [(days, DATE_UNITS), (time, TIME_UNITS)] {
+        let mut number = String::new();
+        for c in part.chars() {
+            match units.iter().find(|(unit, _)| *unit == c.to_ascii_uppercase()) {
+                Some((_, factor)) => seconds += number.parse::<f64>().ok()? * factor,
+                None if c.is_ascii_digit() || c == '.' => {
+                    number.push(c);
+                    continue;
+                }
+                None => return None,
+            }
+            number.clear();
+        }
+        if !number.is_empty() {
+            return None;
+        }
+    }
+    Some((seconds / 60.0).ceil() as u32)
+}
+
+/// A non-empty string field of a JSON object.
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).filter(|text| !text.trim().is_empty()).map(str::to_string)
+}
+
+/// Splits text into trimmed, non-empty lines.
+fn split_lines(text: &str) -> Vec<String> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_recipe_value_native() {
+        let value = json!({"title": "Soep", "source_url": "https://15gram.be/recepten/soep", "steps": ["Kook."]});
+        let recipe = parse_recipe_value(&value).unwrap();
+        assert_eq!(recipe.title.as_deref(), Some("Soep"));
+        assert_eq!(recipe.steps, Some(vec!["Kook.".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_recipe_value_mealie() {
+        let value = json!({
+            "name": "Lasagne",
+            "orgURL": "https://dagelijksekost.vrt.be/gerechten/lasagne",
+            "recipeIngredient": [{"display": "500 g gehakt", "note": ""}, "1 ui"],
+            "recipeInstructions": [{"text": "Bak het gehakt."}],
+            "tags": [{"name": "pasta"}]
+        });
+        let recipe = parse_recipe_value(&value).unwrap();
+        assert_eq!(recipe.ingredients, Some(vec!["500 g gehakt".to_string(), "1 ui".to_string()]));
+        assert_eq!(recipe.steps, Some(vec!["Bak het gehakt.".to_string()]));
+        assert_eq!(recipe.source_url, "https://dagelijksekost.vrt.be/gerechten/lasagne");
+        assert_eq!(recipe.tags, vec!["pasta".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_recipe_value_paprika() {
+        let value = json!({
+            "name": "Pannenkoeken",
+            "ingredients": "250 g bloem\n\n3 eieren",
+            "directions": "Meng alles.\nBak de pannenkoeken.",
+            "categories": ["ontbijt"],
+            "source_url": ""
+        });
+        let recipe = parse_recipe_value(&value).unwrap();
+        assert_eq!(recipe.ingredients, Some(vec!["250 g bloem".to_string(), "3 eieren".to_string()]));
+        assert_eq!(recipe.steps.map(|steps| steps.len()), Some(2));
+        assert_eq!(recipe.source_url, "");
+    }
+
+    #[test]
+    fn test_parse_recipe_value_unknown() {
+        assert!(parse_recipe_value(&json!({"foo": "bar"})).is_none());
+    }
+
+    #[test]
+    fn test_parse_iso_duration_minutes() {
+        assert_eq!(parse_iso_duration_minutes("PT45M"), Some(45));
+        assert_eq!(parse_iso_duration_minutes("PT1H30M"), Some(90));
+        assert_eq!(parse_iso_duration_minutes("P1DT2H"), Some(1560));
+        assert_eq!(parse_iso_duration_minutes("45 minutes"), None);
+    }
+}
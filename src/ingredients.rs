@@ -0,0 +1,162 @@
+//! Ingredient line parsing and shopping list merging.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Units recognised after a quantity, with the canonical unit and the factor converting into it.
+const UNITS: &[(&[&str], &str, f64)] = &[
+    (&["kg", "kilo", "kilogram"], "g", 1000.0),
+    (&["g", "gr", "gram", "grams"], "g", 1.0),
+    (&["l", "liter", "litre", "liters"], "ml", 1000.0),
+    (&["dl", "deciliter"], "ml", 100.0),
+    (&["cl", "centiliter"], "ml", 10.0),
+    (&["ml", "milliliter"], "ml", 1.0),
+    (&["el", "eetlepel", "eetlepels", "tbsp", "c.à.s"], "el", 1.0),
+    (&["kl", "tl", "koffielepel", "koffielepels", "theelepel", "theelepels", "tsp", "c.à.c"], "kl", 1.0),
+    (&["teentje", "teentjes", "teen", "tenen"], "teentje", 1.0),
+    (&["blik", "blikje", "blikken", "blikjes"], "blik", 1.0),
+    (&["takje", "takjes"], "takje", 1.0),
+    (&["snuifje", "snuifjes"], "snuifje", 1.0),
+    (&["stuk", "stuks", "stukken"], "stuk", 1.0),
+];
+
+/// A leading quantity: a number, a fraction, a vulgar fraction or a range like `2-3`.
+static QUANTITY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(\d+(?:[.,]\d+)?(?:\s*/\s*\d+)?|[½¼¾⅓⅔])(?:\s*(?:-|–|à|a|tot)\s*(\d+(?:[.,]\d+)?))?\s*").unwrap()
+});
+
+/// An ingredient line split into quantity, unit and name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedIngredient {
+    /// The amount in `unit` (the upper bound for ranges), if the line starts with one.
+    pub quantity: Option<f64>,
+    /// The canonical unit, if one of the known units follows the quantity.
+    pub unit: Option<String>,
+    /// The ingredient itself, e.g. `verse oregano, of gedroogde`.
+    pub name: String,
+}
+
+impl ParsedIngredient {
+    /// The part of the name used to recognise the same ingredient across recipes.
+    pub fn key(&self) -> String {
+        self.name.split(',').next().unwrap_or_default().trim().to_lowercase()
+    }
+}
+
+/// Parses an ingredient line such as `500 gr gehakt` or `1/2 ui`.
+pub fn parse_ingredient(line: &str) -> ParsedIngredient {
+    let line = line.trim();
+    let Some(captures) = QUANTITY.captures(line) else {
+        return ParsedIngredient { quantity: None, unit: None, name: line.to_string() };
+    };
+    let amount = captures.get(2).map_or(&captures[1], |upper| upper.as_str());
+    let mut quantity = parse_number(amount);
+    let rest = &line[captures.get(0).unwrap().end()..];
+
+    let (first_word, remainder) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let first_word_lower = first_word.trim_end_matches('.').to_lowercase();
+    let unit = UNITS.iter().find(|(aliases, _, _)| aliases.contains(&first_word_lower.as_str()));
+    let (unit, name) = match unit {
+        Some((_, canonical, factor)) if !remainder.trim().is_empty() => {
+            quantity = quantity.map(|q| q * factor);
+            (Some(canonical.to_string()), remainder.trim())
+        }
+        _ => (None, rest.trim()),
+    };
+    ParsedIngredient { quantity, unit, name: name.to_string() }
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    match text {
+        "½" => Some(0.5),
+        "¼" => Some(0.25),
+        "¾" => Some(0.75),
+        "⅓" => Some(1.0 / 3.0),
+        "⅔" => Some(2.0 / 3.0),
+        _ => match text.split_once('/') {
+            Some((numerator, denominator)) => {
+                let denominator: f64 = denominator.trim().parse().ok()?;
+                (denominator != 0.0).then_some(numerator.trim().parse::<f64>().ok()? / denominator)
+            }
+            None => text.replace(',', ".").parse().ok(),
+        },
+    }
+}
+
+/// One line of a combined shopping list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingItem {
+    /// The summed quantity, if every merged line had one.
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub name: String,
+}
+
+impl std::fmt::Display for ShoppingItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(quantity) = self.quantity {
+            let rounded = (quantity * 100.0).round() / 100.0;
+            write!(f, "{} ", rounded)?;
+        }
+        if let Some(unit) = &self.unit {
+            write!(f, "{} ", unit)?;
+        }
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Merges parsed ingredients from several recipes, summing quantities of the same ingredient and unit.
+///
+/// Items keep the order in which they first appear.
+pub fn merge_parsed(ingredients: impl IntoIterator<Item = ParsedIngredient>) -> Vec<ShoppingItem> {
+    let mut items: Vec<(String, ShoppingItem)> = Vec::new();
+    for parsed in ingredients {
+        let key = parsed.key();
+        if key.is_empty() {
+            continue;
+        }
+        let existing = items.iter_mut().find(|(k, item)| *k == key && item.unit == parsed.unit);
+        match existing {
+            Some((_, item)) => {
+                item.quantity = match (item.quantity, parsed.quantity) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+            }
+            None => {
+                let name = parsed.name.split(',').next().unwrap_or_default().trim().to_string();
+                items.push((key, ShoppingItem { quantity: parsed.quantity, unit: parsed.unit, name }));
+            }
+        }
+    }
+    items.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ingredient_with_unit() {
+        let parsed = parse_ingredient("500 gr gehakt");
+        assert_eq!(parsed, ParsedIngredient { quantity: Some(500.0), unit: Some("g".to_string()), name: "gehakt".to_string() });
+        assert_eq!(parse_ingredient("1,5 kg aardappelen").quantity, Some(1500.0));
+        assert_eq!(parse_ingredient("2 el olijfolie").unit.as_deref(), Some("el"));
+    }
+
+    #[test]
+    fn test_parse_ingredient_without_unit_or_quantity() {
+        assert_eq!(parse_ingredient("1 witte ui"), ParsedIngredient { quantity: Some(1.0), unit: None, name: "witte ui".to_string() });
+        assert_eq!(parse_ingredient("½ citroen").quantity, Some(0.5));
+        assert_eq!(parse_ingredient("2-3 wortels").quantity, Some(3.0));
+        assert_eq!(parse_ingredient("olijfolie"), ParsedIngredient { quantity: None, unit: None, name: "olijfolie".to_string() });
+    }
+
+    #[test]
+    fn test_merge_parsed_sums_same_unit() {
+        let lines = ["500 gr gehakt", "1 ui", "0,5 kg gehakt", "2 uien", "olijfolie", "Olijfolie"];
+        let merged = merge_parsed(lines.into_iter().map(parse_ingredient));
+        let lines: Vec<String> = merged.iter().map(ToString::to_string).collect();
+        assert_eq!(lines, vec!["1000 g gehakt", "1 ui", "2 uien", "olijfolie"]);
+    }
+}
@@ -0,0 +1,168 @@
+//! The local recipe library: the recipe JSON files saved in the output folder.
+
+use crate::{Recipe, storage};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Criteria for selecting recipes from the library; unset criteria match everything.
+#[derive(Debug, Default)]
+pub struct LibraryFilter {
+    /// Part of the source domain, e.g. `15gram`.
+    pub site: Option<String>,
+    /// A tag the recipe must carry (case-insensitive).
+    pub tag: Option<String>,
+    /// Earliest scrape date to include.
+    pub since: Option<NaiveDate>,
+    /// Only include favorites.
+    pub favorites_only: bool,
+}
+
+impl LibraryFilter {
+    /// Returns whether the recipe satisfies every criterion of the filter.
+    pub fn matches(&self, recipe: &Recipe) -> bool {
+        let site_matches = self.site.as_ref().is_none_or(|site| {
+            source_domain(&recipe.source_url).is_some_and(|domain| domain.contains(&site.to_lowercase()))
+        });
+        let tag_matches =
+            self.tag.as_ref().is_none_or(|tag| recipe.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+        let since_matches = self.since.is_none_or(|since| {
+            recipe
+                .scraped_at
+                .as_deref()
+                .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+                .is_some_and(|date| date.date_naive() >= since)
+        });
+        site_matches && tag_matches && since_matches && (recipe.favorite || !self.favorites_only)
+    }
+}
+
+/// Loads every recipe JSON file in the folder, sorted by path. Files that are not recipes are skipped.
+///
+/// Recipes saved before `scraped_at` was recorded get the file's modification time instead.
+pub fn load_library(folder: &str) -> std::io::Result<Vec<(PathBuf, Recipe)>> {
+    let mut recipes = Vec::new();
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json")
+            && let Some(mut recipe) = load_recipe(&path)
+        {
+            if recipe.scraped_at.is_none() {
+                let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+                recipe.scraped_at = modified.map(|time| DateTime::<Utc>::from(time).to_rfc3339());
+            }
+            recipes.push((path, recipe));
+        }
+    }
+    recipes.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(recipes)
+}
+
+/// Reads a single recipe JSON file, returning `None` if it cannot be read or parsed.
+pub fn load_recipe(path: &Path) -> Option<Recipe> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Full-text searches the JSON files in the folder by indexing them into an in-memory database.
+pub fn search_folder(folder: &str, query: &str) -> Result<Vec<(PathBuf, Recipe)>, Box<dyn std::error::Error>> {
+    let mut connection = storage::open_database(":memory:")?;
+    let mut by_id = std::collections::HashMap::new();
+    for (path, recipe) in load_library(folder)? {
+        // Keyed by path rather than source URL, so copies of a recipe (`--on-conflict rename`) each get their own row.
+        let id = storage::store_recipe(&mut connection, &Recipe { source_url: path.display().to_string(), ..recipe.clone() })?;
+        by_id.insert(id, (path, recipe));
+    }
+    let hits = storage::search_recipes(&connection, query)?;
+    Ok(hits.into_iter().filter_map(|hit| by_id.remove(&hit.id)).collect())
+}
+
+/// The host of a recipe's source URL, without a leading `www.`.
+pub fn source_domain(source_url: &str) -> Option<String> {
+    let url = Url::parse(source_url).ok()?;
+    let host = url.host_str()?;
+    Some(host.strip_prefix("www.").unwrap_or(host).to_lowercase())
+}
+
+/// Formats rows as a left-aligned text table with a header line.
+pub fn format_table<const N: usize>(headers: &[&str; N], rows: &[[String; N]]) -> String {
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell)).collect();
+        padded.join("  ").trim_end().to_string() + "\n"
+    };
+    let mut table = format_row(headers.to_vec());
+    for row in rows {
+        table.push_str(&format_row(row.iter().map(String::as_str).collect()));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(source_url: &str, scraped_at: &str, tags: &[&str]) -> Recipe {
+        Recipe {
+            source_url: source_url.to_string(),
+            scraped_at: Some(scraped_at.to_string()),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_library_filter_matches() {
+        let soup = recipe("https://www.15gram.be/recepten/soep", "2024-06-03T10:00:00+00:00", &["Soep"]);
+        let filter = LibraryFilter {
+            site: Some("15gram".to_string()),
+            tag: Some("soep".to_string()),
+            since: NaiveDate::from_ymd_opt(2024, 6, 1),
+            favorites_only: false,
+        };
+        assert!(filter.matches(&soup));
+        assert!(!LibraryFilter { favorites_only: true, ..Default::default() }.matches(&soup));
+        assert!(!LibraryFilter { site: Some("dagelijksekost".to_string()), ..Default::default() }.matches(&soup));
+        assert!(!LibraryFilter { since: NaiveDate::from_ymd_opt(2024, 7, 1), ..Default::default() }.matches(&soup));
+    }
+
+    #[test]
+    fn test_search_folder_finds_every_copy() {
+        let folder = std::env::temp_dir().join(format!("foodscraper-library-{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        // `recipe_soep-2.json` is a renamed copy of the same page.
+        let files = [("recipe_soep", "soep", "Pompoensoep"), ("recipe_soep-2", "soep", "Pompoensoep met gember"), ("recipe_pasta", "pasta", "Pasta")];
+        for (file, page, title) in files {
+            let source_url = format!("https://15gram.be/recepten/{}", page);
+            let recipe = Recipe { title: Some(title.to_string()), ..recipe(&source_url, "2024-06-03T10:00:00+00:00", &[]) };
+            fs::write(folder.join(format!("{}.json", file)), serde_json::to_string(&recipe).unwrap()).unwrap();
+        }
+
+        let hits = search_folder(folder.to_str().unwrap(), "pompoensoep").unwrap();
+        let mut names: Vec<String> = hits.iter().map(|(path, _)| path.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        names.sort();
+        assert_eq!(names, ["recipe_soep-2.json", "recipe_soep.json"]);
+        assert!(hits.iter().all(|(_, recipe)| recipe.source_url == "https://15gram.be/recepten/soep"));
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_source_domain_strips_www() {
+        assert_eq!(source_domain("https://www.15gram.be/recepten/soep"), Some("15gram.be".to_string()));
+        assert_eq!(source_domain("not a url"), None);
+    }
+
+    #[test]
+    fn test_format_table_aligns_columns() {
+        let rows = [["Soep".to_string(), "15gram.be".to_string()], ["Lasagne met spinazie".to_string(), "vrt.be".to_string()]];
+        let table = format_table(&["TITLE", "SITE"], &rows);
+        assert_eq!(table, "TITLE                 SITE\nSoep                  15gram.be\nLasagne met spinazie  vrt.be\n");
+    }
+}
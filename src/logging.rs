@@ -0,0 +1,65 @@
+//! Diagnostics through `tracing`, on stderr so they never mix with recipe output.
+//!
+//! The level of FoodScraper's own events follows `-v`/`-q`: warnings by default, `-v` adds progress, `-vv` the
+//! per-field extraction details and request timings, `-vvv` everything. Other crates only log warnings until `-vvv`.
+//! `RUST_LOG` replaces the filter altogether.
+
+use clap::ValueEnum;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// How log events are written.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One human-readable line per event.
+    Text,
+    /// One JSON object per event, with its span fields.
+    Json,
+}
+
+/// The level of FoodScraper's own events for the `-v` count, or with `-q`.
+fn level(verbosity: u8, quiet: bool) -> LevelFilter {
+    match (quiet, verbosity) {
+        (true, _) => LevelFilter::ERROR,
+        (false, 0) => LevelFilter::WARN,
+        (false, 1) => LevelFilter::INFO,
+        (false, 2) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    }
+}
+
+/// The filter directives for the `-v` count, or with `-q`. Targets match by prefix, so `foodscraper` also covers
+/// `foodscraper_core`.
+fn directives(verbosity: u8, quiet: bool) -> String {
+    let own = level(verbosity, quiet);
+    let others = if verbosity >= 3 { own } else { own.min(LevelFilter::WARN) };
+    format!("{},FoodJSON={},foodscraper={}", others, own, own)
+}
+
+/// Installs the global subscriber. In JSON, request spans also report their busy and idle time when they close,
+/// from `-vv` on.
+pub fn init(verbosity: u8, quiet: bool, format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(directives(verbosity, quiet)));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => builder.without_time().with_target(false).init(),
+        LogFormat::Json => {
+            let span_events = if level(verbosity, quiet) >= LevelFilter::DEBUG { FmtSpan::CLOSE } else { FmtSpan::NONE };
+            builder.json().with_span_events(span_events).with_current_span(true).init()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directives() {
+        assert_eq!(directives(0, false), "warn,FoodJSON=warn,foodscraper=warn");
+        assert_eq!(directives(2, false), "warn,FoodJSON=debug,foodscraper=debug");
+        assert_eq!(directives(3, false), "trace,FoodJSON=trace,foodscraper=trace");
+        assert_eq!(directives(2, true), "error,FoodJSON=error,foodscraper=error");
+    }
+}
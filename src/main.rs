@@ -1,249 +1,1717 @@
-use clap::Parser;
-use reqwest;
-use scraper::{ElementRef, Html, Selector};
-use serde::Serialize;
+/// Like `println!`, for informational messages that `--quiet` suppresses.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !crate::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+mod archive;
+#[cfg(feature = "cards")]
+mod card;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod config;
+mod cook;
+mod crawl;
+mod daemon;
+mod email;
+mod export;
+mod feeds;
+mod find;
+#[cfg(feature = "graphql")]
+mod graphql;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod import;
+mod ingredients;
+mod library;
+mod logging;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod native_host;
+mod obsidian;
+mod plan;
+mod progress;
+mod push;
+mod refresh;
+mod render;
+mod selectors_update;
+mod server;
+mod shopping;
+mod storage;
+mod strict;
+#[cfg(feature = "thumbnails")]
+mod thumbnail;
+mod tui;
+mod validate;
+
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use foodscraper::error::ScrapeError;
+use foodscraper::{
+    Recipe, extract_from_html, extract_recipe, fetch, http_client, images, read_selectors_file, scrape_archived_page, scrape_page,
+    scrape_page_with_selectors, selectors_for_url, site_for_url, steps, text, urls, validate_supported_url, validate_url,
+};
 use std::env;
-use std::fs;
 use std::fs::File;
-use std::io::Write;
-use toml::Value;
-use url::Url;
+use std::io::{IsTerminal, Write};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Command-line arguments for the FoodScraper application.
 #[derive(Parser, Debug)]
 #[command(version = "1.0", author = "Maxime Beretvas", about = "Scrapes recipes from supported websites")]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The URL of the recipe to scrape.
     #[arg(short, long)]
-    url: String,
+    url: Option<String>,
 
     /// The output folder to save the recipe JSON. Defaults to the script's directory.
-    #[arg(short, long)]
+    #[arg(short, long, global = true, env = "FOODSCRAPER_OUTPUT")]
     output: Option<String>,
+
+    /// Where to store the scraped recipe.
+    #[arg(long, value_enum, default_value_t = StoreMode::Json, env = "FOODSCRAPER_STORE")]
+    store: StoreMode,
+
+    /// What to do when the recipe's JSON file already exists in the output folder, e.g. from an earlier scrape of the
+    /// same page.
+    #[arg(long, value_enum, default_value_t = OnConflict::Overwrite, global = true, env = "FOODSCRAPER_ON_CONFLICT")]
+    on_conflict: OnConflict,
+
+    /// The SQLite database file used by `--store sqlite`. Defaults to `recipes.db` in the output folder.
+    #[arg(long, global = true, env = "FOODSCRAPER_DB")]
+    db: Option<String>,
+
+    /// Log more: `-v` progress, `-vv` per-field extraction details and request timings, `-vvv` everything.
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only report errors: no informational messages, progress bars or warnings, for cron jobs and scripts.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// The `User-Agent` header sent with every request. Defaults to the config file's `user_agent`.
+    #[arg(long, global = true, env = "FOODSCRAPER_USER_AGENT")]
+    user_agent: Option<String>,
+
+    /// How log events are written to stderr.
+    #[arg(long, value_enum, default_value_t = logging::LogFormat::Text, global = true, env = "FOODSCRAPER_LOG_FORMAT")]
+    log_format: logging::LogFormat,
+
+    /// Also keep the fetched page next to the recipe, as raw HTML or as a WARC record with headers.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "html")]
+    archive_html: Option<archive::HtmlArchiveFormat>,
+
+    /// When the recipe page is gone (404/410), extract from its latest Wayback Machine snapshot instead.
+    #[arg(long)]
+    wayback: bool,
+
+    /// Also save the page and the recipe the built-in selectors extract from it as the snapshot test fixture of the
+    /// site, in `tests/fixtures/<site>/`.
+    #[arg(long)]
+    record_fixture: bool,
+
+    /// Override a selector of the site for this scrape, e.g. `--selector title="h1.recipe-name"`; repeatable. Pages
+    /// of unsupported sites can be scraped this way too.
+    #[arg(long = "selector", value_name = "FIELD=SELECTOR", value_parser = parse_selector_override)]
+    selectors: Vec<(String, String)>,
+
+    /// Download the recipe image next to the JSON file and point `image_link` at the local copy.
+    #[arg(long)]
+    download_images: bool,
+
+    /// With `--download-images`, also write a thumbnail fitting within WIDTHxHEIGHT.
+    #[cfg(feature = "thumbnails")]
+    #[arg(long, value_name = "WIDTHxHEIGHT")]
+    thumbnail: Option<thumbnail::ThumbnailSize>,
+
+    /// The encoding of generated thumbnails.
+    #[cfg(feature = "thumbnails")]
+    #[arg(long, value_enum, default_value_t = thumbnail::ThumbnailFormat::Jpeg)]
+    thumbnail_format: thumbnail::ThumbnailFormat,
+
+    /// Also write the recipe as a note with frontmatter and ingredient links into this Obsidian vault.
+    #[arg(long, value_name = "PATH")]
+    obsidian_vault: Option<std::path::PathBuf>,
+
+    /// Also email the recipe as HTML to this address, using the `[smtp]` settings from the config file.
+    #[arg(long, value_name = "ADDRESS")]
+    email: Option<String>,
+
+    #[command(flatten)]
+    push: push::PushOptions,
+
+    /// Fail instead of saving when the recipe does not satisfy the bundled schema.org Recipe JSON Schema (required
+    /// fields, ISO 8601 durations, web URLs).
+    #[arg(long)]
+    strict: bool,
+
+    /// Also write the scrape's per-field warnings and confidence as JSON to this file. Warnings are printed to stderr
+    /// either way.
+    #[arg(long, value_name = "PATH")]
+    report: Option<std::path::PathBuf>,
+
+    /// Also write the recipe in this format next to its JSON file, e.g. `--format card` for an image to share; can be
+    /// repeated or comma-separated.
+    #[arg(long = "format", value_enum, value_name = "FORMAT", value_delimiter = ',', env = "FOODSCRAPER_FORMAT")]
+    formats: Vec<render::RecipeFormat>,
+
+    /// Embed a QR code of the source URL in HTML and card output (`--format`, `export --recipe-format`), so printed
+    /// recipes link back to the original page.
+    #[arg(long, global = true)]
+    qr_code: bool,
+
+    /// After saving, print the recipe with a boxed ingredient list and numbered steps, to check the extraction at a
+    /// glance. Colored when stdout is a terminal and `NO_COLOR` is not set.
+    #[arg(long)]
+    show: bool,
+
+    /// Fetch and extract, then print what would be written, stored and sent instead of doing it.
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// The overall confidence score below which the scrape exits with code 3, flagging the recipe for review.
+    #[arg(long, value_name = "SCORE", default_value_t = foodscraper_core::confidence::DEFAULT_MIN_CONFIDENCE)]
+    min_confidence: f64,
+
+    /// How a failure is written to stderr: a message, or a JSON object with its kind, URL and a hint.
+    #[arg(long, value_enum, default_value_t = ErrorFormat::Text)]
+    error_format: ErrorFormat,
+
+    /// The `[smtp]` section of the config file, kept by [`apply_config`] for `--email`.
+    #[arg(skip)]
+    smtp: Option<config::SmtpConfig>,
+
+    /// The `[mqtt]` section of the config file, kept by [`apply_config`].
+    #[arg(skip)]
+    mqtt: Option<config::MqttConfig>,
+
+    /// The `[card]` section of the config file, kept by [`apply_config`] for `--format card`.
+    #[arg(skip)]
+    card: config::CardConfig,
+
+    /// The `[daemon]` section of the config file, kept by [`apply_config`] for `daemon`.
+    #[arg(skip)]
+    daemon: daemon::DaemonConfig,
+}
+
+/// Formats a failure can be reported in.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ErrorFormat {
+    /// `Error: <message>`.
+    Text,
+    /// A JSON object on one line; see `foodscraper::error::ErrorReport`.
+    Json,
+}
+
+/// Subcommands working on the local recipe library. Without a subcommand, `--url` is scraped.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Full-text search over the titles, ingredients and steps of all saved recipes.
+    Search {
+        /// The words to search for; all of them must match.
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+    },
+    /// Lists the saved recipes as a table.
+    List {
+        /// Only list recipes from this site, e.g. `15gram` or `dagelijksekost.vrt.be`.
+        #[arg(long)]
+        site: Option<String>,
+        /// Only list recipes carrying this tag.
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only list recipes scraped on or after this date (YYYY-MM-DD).
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Only list favorite recipes.
+        #[arg(long)]
+        favorites: bool,
+    },
+    /// Re-scrapes every saved recipe from its source URL and updates the stored copy.
+    Refresh,
+    /// Polls recipe RSS/Atom feeds and scrapes new entries from supported sites into the library.
+    WatchFeed {
+        /// The feed URLs to watch.
+        #[arg(required = true, num_args = 1..)]
+        feeds: Vec<String>,
+        /// Minutes between polls, e.g. `60` or `1h`.
+        #[arg(long, default_value = "60", value_parser = plan::parse_max_time)]
+        interval: u32,
+        /// Poll once and exit instead of watching.
+        #[arg(long)]
+        once: bool,
+    },
+    /// Watches the clipboard and scrapes every copied recipe URL on a supported site (needs the `clipboard` feature).
+    #[cfg(feature = "clipboard")]
+    WatchClipboard {
+        /// Seconds between clipboard checks.
+        #[arg(long, default_value_t = 1)]
+        interval: u64,
+    },
+    /// Answers scrape requests from a browser extension over native messaging on stdin/stdout.
+    NativeHost {
+        /// The caller the browser passes (extension origin, manifest path); ignored.
+        #[arg(num_args = 0.., allow_hyphen_values = true, hide = true)]
+        caller: Vec<String>,
+    },
+    /// Scrapes every recipe a site's sitemap or a category listing links to, skipping recipes already in the library
+    /// or scraped by an earlier run.
+    #[command(group = clap::ArgGroup::new("source").required(true).args(["sitemap", "from"]))]
+    Crawl {
+        /// The sitemap (or sitemap index) URL, e.g. `https://15gram.be/sitemap.xml`.
+        #[arg(long)]
+        sitemap: Option<String>,
+        /// A category or listing page whose recipe links are scraped, e.g. a section of the site.
+        #[arg(long)]
+        from: Option<String>,
+        /// How many listing pages to visit with `--from`, following the site's "next page" links.
+        #[arg(long, default_value_t = 1, requires = "from")]
+        depth: usize,
+        /// Seconds to wait between requests to the site.
+        #[arg(long, default_value_t = 1.0)]
+        delay: f64,
+        /// Stop after scraping this many recipes.
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Searches a site for recipes through its search page and lists the matching recipe URLs.
+    Find {
+        /// The words to search for.
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+        /// The site to search, by name or domain, e.g. `15gram`.
+        #[arg(long)]
+        site: String,
+        /// Scrape the first N results into the library as well.
+        #[arg(long, value_name = "N")]
+        scrape: Option<usize>,
+    },
+    /// Runs the jobs scheduled in the `[daemon]` section of the config file until stopped.
+    Daemon,
+    /// Imports recipe JSON files, Mealie exports or Paprika archives into the SQLite database.
+    Import {
+        /// Files or folders to import.
+        #[arg(required = true, num_args = 1..)]
+        paths: Vec<std::path::PathBuf>,
+    },
+    /// Re-renders saved recipe JSON files in another format, without fetching the pages again.
+    Convert {
+        /// The recipe JSON files.
+        #[arg(required = true, num_args = 1..)]
+        recipes: Vec<std::path::PathBuf>,
+        /// The format to write.
+        #[arg(long, value_enum)]
+        format: render::RecipeFormat,
+        /// The folder to write to; defaults to the folder of each recipe.
+        #[arg(long, value_name = "DIR")]
+        to: Option<std::path::PathBuf>,
+    },
+    /// Exports saved recipes as a single archive with an index file.
+    Export {
+        /// Export every saved recipe; otherwise at least one filter is required.
+        #[arg(long)]
+        all: bool,
+        /// The archive format.
+        #[arg(long, value_enum, default_value_t = export::ArchiveFormat::Zip)]
+        format: export::ArchiveFormat,
+        /// The format of the recipe files inside the archive.
+        #[arg(long, value_enum, default_value_t = render::RecipeFormat::Json)]
+        recipe_format: render::RecipeFormat,
+        /// The archive to write. Defaults to `library-export-<date>.<format>` in the output folder.
+        #[arg(long)]
+        to: Option<String>,
+        /// Only export recipes from this site.
+        #[arg(long)]
+        site: Option<String>,
+        /// Only export recipes carrying this tag.
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only export recipes scraped on or after this date (YYYY-MM-DD).
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+    },
+    /// Adds or removes tags on a saved recipe.
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+    /// Marks a saved recipe as a favorite.
+    Favorite {
+        /// The recipe: its JSON file name or path, `#id` in the database, source URL or title.
+        recipe: String,
+        /// Remove the favorite mark instead.
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Browses the saved recipes interactively: fuzzy search, a detail pane, tagging and deleting.
+    Tui,
+    /// Cooks a saved recipe step by step: one instruction at a time full-screen, with its ingredients and timers.
+    Cook {
+        /// The recipe: its JSON file name or path, `#id` in the database, source URL or title.
+        recipe: String,
+    },
+    /// Plans saved recipes on dates.
+    Plan {
+        #[command(subcommand)]
+        action: PlanAction,
+    },
+    /// Manages the selector definitions.
+    Selectors {
+        #[command(subcommand)]
+        action: SelectorsAction,
+    },
+    /// Scrapes the `test_urls` of each site and reports which fields its selectors still find.
+    Validate {
+        /// Only check this site, by name or domain.
+        #[arg(long)]
+        site: Option<String>,
+    },
+    /// Serves a REST API for scraping and browsing the library.
+    Serve {
+        /// The port to listen on.
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// The address to bind to; use 0.0.0.0 to accept connections from other machines.
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Also serve the gRPC scraping service on this port.
+        #[cfg(feature = "grpc")]
+        #[arg(long)]
+        grpc_port: Option<u16>,
+    },
+    /// Prints the shell completion script, e.g. `FoodJSON completions bash > /etc/bash_completion.d/FoodJSON`.
+    Completions {
+        /// The shell to complete in.
+        shell: clap_complete::Shell,
+    },
+}
+
+/// Tag operations.
+#[derive(Subcommand, Debug)]
+enum TagAction {
+    /// Adds tags to a saved recipe.
+    Add {
+        /// The recipe: its JSON file name or path, `#id` in the database, source URL or title.
+        recipe: String,
+        /// The tags to add.
+        #[arg(required = true, num_args = 1..)]
+        tags: Vec<String>,
+    },
+    /// Removes tags from a saved recipe.
+    Remove {
+        /// The recipe: its JSON file name or path, `#id` in the database, source URL or title.
+        recipe: String,
+        /// The tags to remove.
+        #[arg(required = true, num_args = 1..)]
+        tags: Vec<String>,
+    },
+}
+
+/// Selector operations.
+#[derive(Subcommand, Debug)]
+enum SelectorsAction {
+    /// Downloads the latest selectors file, checks it and installs it in the config directory.
+    Update {
+        /// Where to download from; defaults to `update_url` in the `[selectors]` config section, or the repository.
+        #[arg(long)]
+        url: Option<String>,
+    },
+}
+
+/// Meal plan operations.
+#[derive(Subcommand, Debug)]
+enum PlanAction {
+    /// Plans a saved recipe on a date.
+    Add {
+        /// The date to plan the recipe on (YYYY-MM-DD).
+        date: chrono::NaiveDate,
+        /// The recipe: its JSON file name or path, `#id` in the database, source URL or title.
+        recipe: String,
+    },
+    /// Removes planned recipes from a date.
+    Remove {
+        /// The date to clear (YYYY-MM-DD).
+        date: chrono::NaiveDate,
+        /// Only remove this recipe instead of every recipe on the date.
+        recipe: Option<String>,
+    },
+    /// Shows the plan for `today`, `week`, `next-week`, `month` or a `YYYY-MM-DD` date.
+    Show {
+        #[arg(default_value = "week")]
+        range: String,
+    },
+    /// Picks saved recipes for the coming days and prints the menu with a combined shopping list.
+    Generate {
+        /// Number of days to plan.
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+        /// The first day to plan (YYYY-MM-DD). Defaults to today.
+        #[arg(long)]
+        start: Option<chrono::NaiveDate>,
+        /// Only pick recipes carrying all of these tags (comma-separated).
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+        /// Only pick recipes that take at most this long, e.g. `45m` or `1h30m`.
+        #[arg(long, value_parser = plan::parse_max_time)]
+        max_time: Option<u32>,
+        /// Do not repeat recipes planned within this many weeks.
+        #[arg(long, default_value_t = 2)]
+        no_repeat_weeks: u32,
+        /// Seed for the random choice between equally suitable recipes.
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Prints the combined shopping list for the recipes planned in a range, optionally sending it on.
+    Shopping {
+        /// `today`, `week`, `next-week`, `month` or a `YYYY-MM-DD` date.
+        #[arg(default_value = "week")]
+        range: String,
+        /// Also add the items to this shopping list.
+        #[arg(long, value_enum)]
+        send: Option<ShoppingTarget>,
+        #[command(flatten)]
+        home_assistant: push::home_assistant::HomeAssistantOptions,
+    },
+}
+
+/// Where `plan shopping --send` delivers the shopping list.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ShoppingTarget {
+    /// A Home Assistant to-do list, via the `todo.add_item` service.
+    HomeAssistant,
+}
+
+/// What happens when the JSON file a recipe is saved to already exists.
+#[derive(ValueEnum, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum OnConflict {
+    /// Replace the existing file.
+    #[default]
+    Overwrite,
+    /// Keep the existing file and leave the recipe unsaved.
+    Skip,
+    /// Save under the first free `-2`, `-3`, ... name.
+    Rename,
+    /// Stop with an error.
+    Fail,
+}
+
+/// Storage backends a scraped recipe can be written to.
+#[derive(ValueEnum, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum StoreMode {
+    /// A JSON file per recipe in the output folder.
+    Json,
+    /// The SQLite recipe database.
+    Sqlite,
+    /// Both a JSON file and the SQLite database.
+    Both,
 }
 
-#[derive(Serialize)]
-struct Recipe {
-    /// The title of the recipe.
-    title: Option<String>,
-    /// A brief description of the recipe.
-    description: Option<String>,
-    /// A list of ingredients required for the recipe.
-    ingredients: Option<Vec<String>>,
-    /// A list of steps to prepare the recipe.
-    steps: Option<Vec<String>>,
-    /// A link to an image of the prepared recipe.
-    image_link: Option<String>,
-    /// The URL source of the recipe.
-    source_url: String,
-}
-
-#[derive(Debug)]
-struct RecipeCssSelectors {
-    /// The CSS selector for the recipe title.
-    title: String,
-    /// The CSS selector for the recipe description.
-    description: String,
-    /// The CSS selector for the recipe ingredients.
-    ingredients: String,
-    /// The CSS selector for the recipe steps.
-    steps: String,
-    /// The CSS selector for the recipe image.
-    image: String,
+/// Set by `--quiet`.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Whether informational output is suppressed (`--quiet`).
+fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     // Parse command-line arguments
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    QUIET.store(args.quiet, Ordering::Relaxed);
+    logging::init(args.verbose, args.quiet, args.log_format);
+    let result = match config::Config::load() {
+        Ok(config) => {
+            apply_config(&mut args, &matches, config);
+            run(&args).await
+        }
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            let report = foodscraper::error::ErrorReport::new(error.as_ref(), args.url.as_deref());
+            match args.error_format {
+                ErrorFormat::Text => eprintln!("Error: {}", error),
+                ErrorFormat::Json => eprintln!("{}", serde_json::to_string(&report).unwrap_or_default()),
+            }
+            ExitCode::from(report.exit_code)
+        }
+    }
+}
 
-    let input_url = &args.url;
-    let output_folder = args.output.unwrap_or_else(|| {
-        env::current_exe()
-            .ok()
-            .and_then(|path| path.parent().map(|p| p.to_str().unwrap_or(".").to_string()))
-            .unwrap_or_else(|| ".".to_string())
-    });
+/// Fills in the options not given on the command line from the config file (see [`config`]).
+fn apply_config(args: &mut Args, matches: &ArgMatches, config: config::Config) {
+    let defaulted = |matches: &ArgMatches, id: &str| matches.value_source(id) == Some(ValueSource::DefaultValue);
+    args.output = args.output.take().or(config.output.map(|output| config::expand_home(&output)));
+    args.db = args.db.take().or(config.db.map(|db| config::expand_home(&db)));
+    if let Some(store) = config.store.filter(|_| defaulted(matches, "store")) {
+        args.store = store;
+    }
+    if let Some(on_conflict) = config.on_conflict.filter(|_| defaulted(matches, "on_conflict")) {
+        args.on_conflict = on_conflict;
+    }
+    if args.formats.is_empty() {
+        args.formats = config.format;
+    }
+    args.user_agent = args.user_agent.take().or(config.user_agent);
+
+    let (push, from) = (&mut args.push, config.push);
+    push.mealie_url = push.mealie_url.take().or(from.mealie_url);
+    push.mealie_token = push.mealie_token.take().or(from.mealie_token);
+    push.tandoor_url = push.tandoor_url.take().or(from.tandoor_url);
+    push.tandoor_token = push.tandoor_token.take().or(from.tandoor_token);
+    push.grocy_url = push.grocy_url.take().or(from.grocy_url);
+    push.grocy_api_key = push.grocy_api_key.take().or(from.grocy_api_key);
+    push.nextcloud_url = push.nextcloud_url.take().or(from.nextcloud_url);
+    push.nextcloud_user = push.nextcloud_user.take().or(from.nextcloud_user);
+    push.nextcloud_password = push.nextcloud_password.take().or(from.nextcloud_password);
+    push.notion_token = push.notion_token.take().or(from.notion_token);
+    push.notion_database = push.notion_database.take().or(from.notion_database);
+    push.webhook_secret = push.webhook_secret.take().or(from.webhook_secret);
+    if let Some(list) = from.grocy_shopping_list.filter(|_| defaulted(matches, "grocy_shopping_list")) {
+        push.grocy_shopping_list = list;
+    }
+    if let Some(folder) = from.nextcloud_folder.filter(|_| defaulted(matches, "nextcloud_folder")) {
+        push.nextcloud_folder = folder;
+    }
+    args.smtp = config.smtp;
+    args.mqtt = config.mqtt;
+    args.card = config.card;
+    args.daemon = config.daemon;
 
-    // Validate the URL
-    if !validate_supported_url(input_url) {
-        return Err("Invalid URL or unsupported domain.".into());
+    match &mut args.command {
+        Some(Command::Crawl { delay, .. }) => {
+            let crawl = matches.subcommand_matches("crawl");
+            if let Some(configured) = config.crawl.delay.filter(|_| crawl.is_some_and(|crawl| defaulted(crawl, "delay"))) {
+                *delay = configured;
+            }
+        }
+        Some(Command::Plan { action: PlanAction::Shopping { home_assistant, .. } }) => {
+            home_assistant.home_assistant_url = home_assistant.home_assistant_url.take().or(from.home_assistant_url);
+            home_assistant.home_assistant_token = home_assistant.home_assistant_token.take().or(from.home_assistant_token);
+        }
+        _ => {}
     }
+}
 
-    let document = fetch_html_document(input_url).await?;
-    let website_name = parse_website_name(input_url).ok_or("Failed to parse website name from URL")?;
-    let selectors = load_selectors("selectors.toml", &website_name)?;
+/// Runs the command given on the command line; see [`foodscraper::error`] for the exit code of each error.
+async fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let output_folder = args.output.clone().unwrap_or_else(default_output_folder);
+    let db_path = args.db.clone().unwrap_or_else(|| format!("{}/recipes.db", output_folder));
+    let client = || http_client(args.user_agent.as_deref());
+
+    match &args.command {
+        Some(Command::Search { query }) => search_library(&query.join(" "), &output_folder, &db_path),
+        Some(Command::List { site, tag, since, favorites }) => {
+            let filter =
+                library::LibraryFilter { site: site.clone(), tag: tag.clone(), since: *since, favorites_only: *favorites };
+            list_library(&filter, &output_folder, &db_path)
+        }
+        Some(Command::Refresh) => refresh_library(&client(), args.mqtt.as_ref(), &output_folder, &db_path).await,
+        Some(Command::WatchFeed { feeds, interval, once }) => loop {
+            let scraped = poll_feeds(&client(), feeds, args.store, args.on_conflict, &output_folder, &db_path).await?;
+            status!("Feed poll finished: {} new recipe(s).", scraped);
+            if *once {
+                break Ok(());
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(*interval as u64 * 60)).await;
+        },
+        #[cfg(feature = "clipboard")]
+        Some(Command::WatchClipboard { interval }) => watch_clipboard(args, *interval, &output_folder, &db_path).await,
+        Some(Command::NativeHost { .. }) => run_native_host(&client(), args.store, args.on_conflict, &output_folder, &db_path).await,
+        Some(Command::Crawl { sitemap, from, depth, delay, limit }) => {
+            let source = match (sitemap, from) {
+                (Some(sitemap), _) => CrawlSource::Sitemap(sitemap),
+                (None, Some(from)) => CrawlSource::Listing(from, *depth),
+                (None, None) => unreachable!("clap requires --sitemap or --from"),
+            };
+            crawl_site(&client(), source, *delay, *limit, args.store, args.on_conflict, &output_folder, &db_path, args.dry_run).await
+        }
+        Some(Command::Find { query, site, scrape }) => {
+            find_recipes(&client(), &query.join(" "), site, *scrape, args.store, args.on_conflict, &output_folder, &db_path, args.dry_run).await
+        }
+        Some(Command::Daemon) => run_daemon(&client(), &args.daemon.jobs, args.store, args.on_conflict, args.mqtt.as_ref(), &output_folder, &db_path).await,
+        Some(Command::Import { paths }) => import_recipes(paths, &db_path),
+        Some(Command::Export { all, format, recipe_format, to, site, tag, since }) => {
+            let filter = library::LibraryFilter { site: site.clone(), tag: tag.clone(), since: *since, favorites_only: false };
+            if !all && site.is_none() && tag.is_none() && since.is_none() {
+                return Err("Pass --all to export the whole library, or filter with --site/--tag/--since.".into());
+            }
+            let archive_path = to.clone().unwrap_or_else(|| {
+                let date = chrono::Local::now().format("%Y-%m-%d");
+                format!("{}/library-export-{}.{}", output_folder, date, format.extension())
+            });
+            export_library(&filter, *recipe_format, args.qr_code, &args.card, &archive_path, &output_folder, &db_path)
+        }
+        Some(Command::Convert { recipes, format, to }) => {
+            convert_recipes(&client(), recipes, *format, to.as_deref(), args.qr_code, &args.card, args.dry_run).await
+        }
+        Some(Command::Tag { action }) => run_tag(action, &output_folder, &db_path),
+        Some(Command::Favorite { recipe, remove }) => {
+            let (location, mut saved) = find_saved_recipe(recipe, &output_folder, &db_path)?;
+            saved.favorite = !remove;
+            update_saved_recipe(&location, &saved, &db_path)?;
+            let verb = if *remove { "Removed favorite mark from" } else { "Marked as favorite:" };
+            status!("{} '{}'.", verb, saved.title.as_deref().unwrap_or("(untitled)"));
+            Ok(())
+        }
+        Some(Command::Tui) => tui::run(load_saved_recipes(&output_folder, &db_path)?, &db_path),
+        Some(Command::Cook { recipe }) => cook::run(find_saved_recipe(recipe, &output_folder, &db_path)?.1),
+        Some(Command::Plan { action }) => run_plan(&client(), action, &output_folder, &db_path).await,
+        Some(Command::Selectors { action: SelectorsAction::Update { url } }) => {
+            let config_url = config::Config::load()?.selectors.update_url;
+            let url = url.clone().or(config_url).unwrap_or_else(|| selectors_update::DEFAULT_UPDATE_URL.to_string());
+            let (path, sites) = selectors_update::update_selectors(&client(), &url).await?;
+            status!("Installed selectors for {} sites from {} to {}.", sites, url, path.display());
+            if std::path::Path::new("selectors.toml").exists() {
+                status!("Note: selectors.toml in the working directory still overrides them.");
+            }
+            Ok(())
+        }
+        Some(Command::Validate { site }) => {
+            validate::validate_sites(&client(), &read_selectors_file()?, site.as_deref()).await
+        }
+        Some(Command::Serve { port, host, .. }) => {
+            let state = server::ServerState { output_folder, db_path, store: args.store, on_conflict: args.on_conflict, client: client() };
+            let address = format!("{}:{}", host, port);
+            let rest = server::serve(&address, state);
+            #[cfg(feature = "grpc")]
+            if let Some(Command::Serve { grpc_port: Some(grpc_port), .. }) = &args.command {
+                let grpc_address = format!("{}:{}", host, grpc_port);
+                return tokio::try_join!(rest, grpc::serve(&grpc_address, client())).map(|_| ());
+            }
+            rest.await
+        }
+        Some(Command::Completions { shell }) => {
+            let mut command = Args::command();
+            let name = command.get_name().to_string();
+            let mut script = Vec::new();
+            clap_complete::generate(*shell, &mut command, name, &mut script);
+            Ok(std::io::stdout().write_all(&script)?)
+        }
+        None => {
+            let input_url = args.url.as_deref().ok_or("A recipe --url is required when no subcommand is given.")?;
+            let recipe = scrape_recipe(input_url, args, &output_folder, &db_path).await?;
+            if args.show {
+                let width = ratatui::crossterm::terminal::size().map_or(80, |(columns, _)| columns as usize).min(100);
+                let color = std::io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+                print!("\n{}", render::render_terminal(&recipe, width, color));
+            }
+            if let Some(confidence) = recipe.confidence.as_ref().filter(|confidence| confidence.is_low(args.min_confidence)) {
+                let low = foodscraper::error::ScrapeError::LowConfidence { overall: confidence.overall, min: args.min_confidence };
+                // Nothing was saved on a dry run, so there is nothing to flag for review: warn and exit 0.
+                if args.dry_run {
+                    tracing::warn!("{}", low);
+                } else {
+                    return Err(low.into());
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The default output folder: the directory containing the executable.
+fn default_output_folder() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|path| path.parent().map(|p| p.to_str().unwrap_or(".").to_string()))
+        .unwrap_or_else(|| ".".to_string())
+}
+
+/// Parses a `--selector FIELD=SELECTOR` value.
+fn parse_selector_override(value: &str) -> Result<(String, String), String> {
+    let (field, selector) = value.split_once('=').ok_or("expected FIELD=SELECTOR, e.g. title=\"h1.recipe-name\"")?;
+    let (field, selector) = (field.trim(), selector.trim().trim_matches('"'));
+    if !foodscraper::OVERRIDABLE_FIELDS.contains(&field) {
+        return Err(format!("unknown field '{}'; expected one of {}", field, foodscraper::OVERRIDABLE_FIELDS.join(", ")));
+    }
+    Ok((field.to_string(), selector.to_string()))
+}
+
+/// Scrapes a single recipe URL and stores the result with the chosen backend.
+async fn scrape_recipe(
+    input_url: &str,
+    args: &Args,
+    output_folder: &str,
+    db_path: &str,
+) -> Result<Recipe, Box<dyn std::error::Error>> {
+    let client = http_client(args.user_agent.as_deref());
+    let selectors = foodscraper::with_selector_overrides(&read_selectors_file()?, input_url, &args.selectors)?;
+    let (mut recipe, page) = match foodscraper::scrape_page_with_selectors(&client, input_url, &selectors).await {
+        Err(error @ ScrapeError::PageGone(_)) if args.wayback => {
+            status!("{} Trying the Wayback Machine.", error);
+            let (recipe, page) = scrape_archived_page(&client, input_url).await?;
+            if let Some(copy) = &recipe.archived_copy {
+                status!("Using the snapshot from {}: {}", copy.archived_at, copy.url);
+            }
+            (recipe, page)
+        }
+        result => result?,
+    };
+    let warnings = foodscraper::RecipeCssSelectors::for_url(&selectors, input_url)
+        .map(|selectors| foodscraper_core::report::warnings(&recipe, &selectors))
+        .unwrap_or_default();
+    for warning in &warnings {
+        tracing::warn!("{}", warning);
+    }
+    if let Some(path) = args.report.as_ref().filter(|_| !args.dry_run) {
+        let report = foodscraper_core::report::Report { url: input_url.to_string(), confidence: recipe.confidence.clone(), warnings };
+        std::fs::write(path, serde_json::to_string_pretty(&report)? + "\n")?;
+    }
+    if args.strict {
+        let errors = strict::schema_errors(&recipe);
+        if !errors.is_empty() {
+            return Err(format!("The recipe does not satisfy the schema.org Recipe schema:\n  {}", errors.join("\n  ")).into());
+        }
+    }
+    if args.dry_run {
+        for action in dry_run_actions(&recipe, args, output_folder, db_path)? {
+            println!("Would {}", action);
+        }
+        return Ok(recipe);
+    }
+    if args.record_fixture {
+        let url = foodscraper::urls::normalize_url(input_url);
+        let dir = foodscraper::snapshot::record_fixture(std::path::Path::new(foodscraper::snapshot::FIXTURES_DIR), &url, &page.body)?;
+        status!("Recorded fixture {}", dir.display());
+    }
+    let stem = match args.store {
+        StoreMode::Sqlite => recipe_file_stem(&recipe, output_folder),
+        _ => match target_file_stem(&recipe, args.on_conflict, output_folder)? {
+            Some(stem) => stem,
+            None => return Ok(recipe),
+        },
+    };
+    if args.download_images
+        && let Some(image_link) = recipe.image_link.clone()
+    {
+        match images::download_image(&client, &image_link, input_url, output_folder, &stem).await {
+            Ok(file_name) => {
+                #[cfg(feature = "thumbnails")]
+                if let Some(size) = args.thumbnail {
+                    match thumbnail::create_thumbnail(output_folder, &file_name, &stem, size, args.thumbnail_format) {
+                        Ok(thumbnail) => recipe.thumbnail = Some(thumbnail),
+                        Err(e) => tracing::warn!("Could not create thumbnail for {}: {}", file_name, e),
+                    }
+                }
+                recipe.image_link = Some(file_name);
+            }
+            Err(e) => tracing::warn!("Could not download image {}: {}", image_link, e),
+        }
+    }
+    save_recipe(&recipe, args.store, args.on_conflict, output_folder, db_path)?;
+    for &format in extra_formats(args) {
+        let path = std::path::Path::new(output_folder).join(format!("{}.{}", stem, format.extension()));
+        let bytes = render_recipe(&client, &recipe, format, args.qr_code, &args.card, output_folder).await?;
+        std::fs::create_dir_all(output_folder)?;
+        std::fs::write(&path, bytes)?;
+        status!("Recipe written to {}", path.display());
+    }
+    if let Some(format) = args.archive_html {
+        let path = archive::archive_page(&page, format, output_folder, &stem)?;
+        status!("Archived page to {}", path.display());
+    }
+    if let Some(vault) = &args.obsidian_vault {
+        let path = obsidian::write_note(&client, vault, &recipe, output_folder).await?;
+        status!("Obsidian note written to {}", path.display());
+    }
+    if let Some(to) = &args.email {
+        let smtp = args.smtp.as_ref().ok_or("--email needs an [smtp] section in the config file.")?;
+        email::send_recipe(&client, smtp, to, &recipe, output_folder).await?;
+        status!("Recipe emailed to {}.", to);
+    }
+    for target in &args.push.targets {
+        let created = push::push_recipe(&client, &recipe, *target, &args.push, output_folder).await?;
+        status!("Pushed recipe: created {}.", created);
+    }
+    #[cfg(feature = "mqtt")]
+    if let Some(mqtt) = &args.mqtt {
+        mqtt::publish(mqtt, "recipe", serde_json::to_vec(&recipe)?).await?;
+        status!("Published recipe to MQTT topic {}.", mqtt::topic(&mqtt.topic, "recipe"));
+    }
+    for webhook in &args.push.webhooks {
+        push::webhook::notify(&client, webhook, args.push.webhook_secret.as_deref(), &recipe).await?;
+        status!("Notified webhook {}.", webhook);
+    }
+
+    status!("Recipe scraping completed successfully.");
+    Ok(recipe)
+}
+
+/// What [`scrape_recipe`] would do with the recipe after extracting it, e.g. `write /recipes/recipe_soep.json`.
+fn dry_run_actions(recipe: &Recipe, args: &Args, output_folder: &str, db_path: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let folder = std::path::Path::new(output_folder);
+    let mut actions = Vec::new();
+    if let Some(path) = &args.report {
+        actions.push(format!("write the report to {}", path.display()));
+    }
+    if args.record_fixture {
+        actions.push(format!("record the page as a fixture in {}/", foodscraper::snapshot::FIXTURES_DIR));
+    }
+    let stem = match args.store {
+        StoreMode::Sqlite => recipe_file_stem(recipe, output_folder),
+        _ => match target_file_stem(recipe, args.on_conflict, output_folder)? {
+            Some(stem) => stem,
+            None => return Ok(vec!["skip the recipe: its JSON file already exists".to_string()]),
+        },
+    };
+    if args.download_images
+        && let Some(image_link) = &recipe.image_link
+    {
+        actions.push(format!("download the image {} to {}", image_link, folder.join(format!("{}.<ext>", stem)).display()));
+        #[cfg(feature = "thumbnails")]
+        if args.thumbnail.is_some() {
+            actions.push(format!("write the thumbnail {}", folder.join(format!("{}.thumb.{}", stem, args.thumbnail_format.extension())).display()));
+        }
+    }
+    if args.store != StoreMode::Sqlite {
+        actions.push(format!("write {}", folder.join(format!("{}.json", stem)).display()));
+    }
+    if args.store != StoreMode::Json {
+        actions.push(format!("store the recipe in the database {}", db_path));
+    }
+    for format in extra_formats(args) {
+        actions.push(format!("write {}", folder.join(format!("{}.{}", stem, format.extension())).display()));
+    }
+    if let Some(format) = args.archive_html {
+        actions.push(format!("archive the page to {}", folder.join(format!("{}.{}", stem, format.extension())).display()));
+    }
+    if let Some(vault) = &args.obsidian_vault {
+        actions.push(format!("write an Obsidian note into {}", vault.display()));
+    }
+    if let Some(to) = &args.email {
+        actions.push(format!("email the recipe to {}", to));
+    }
+    for target in &args.push.targets {
+        let name = target.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default();
+        actions.push(format!("push the recipe to {}", name));
+    }
+    #[cfg(feature = "mqtt")]
+    if let Some(mqtt) = &args.mqtt {
+        actions.push(format!("publish the recipe to the MQTT topic {}", mqtt::topic(&mqtt.topic, "recipe")));
+    }
+    for webhook in &args.push.webhooks {
+        actions.push(format!("notify the webhook {}", webhook));
+    }
+    Ok(actions)
+}
 
-    let recipe = extract_recipe(&document, &selectors, input_url);
-    save_recipe_to_file(&recipe, &output_folder)?;
+/// Renders the recipe in the format like [`render::render`], but draws cards with the recipe's photo: a local one
+/// from `image_folder`, or a downloaded one.
+#[cfg_attr(not(feature = "cards"), allow(unused_variables))]
+async fn render_recipe(
+    client: &reqwest::Client,
+    recipe: &Recipe,
+    format: render::RecipeFormat,
+    qr_code: bool,
+    card: &config::CardConfig,
+    image_folder: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    match format {
+        #[cfg(feature = "cards")]
+        render::RecipeFormat::Card => {
+            let photo = match &recipe.image_link {
+                Some(link) => match email::load_image(client, link, image_folder).await {
+                    Ok(image) => Some(image.bytes),
+                    Err(e) => {
+                        tracing::warn!("Drawing the card without the image {}: {}", link, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+            let template = config::CardConfig { qr_code: card.qr_code || qr_code, ..card.clone() };
+            card::render_card(recipe, photo.as_deref(), &template)
+        }
+        _ => render::render(recipe, format, qr_code, card),
+    }
+}
 
-    println!("Recipe scraping completed successfully.");
+/// Re-renders recipe JSON files in another format, next to each file or into `to`, without fetching the pages.
+async fn convert_recipes(
+    client: &reqwest::Client,
+    paths: &[std::path::PathBuf],
+    format: render::RecipeFormat,
+    to: Option<&std::path::Path>,
+    qr_code: bool,
+    card: &config::CardConfig,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for path in paths {
+        let recipe = library::load_recipe(path).ok_or_else(|| format!("'{}' is not a recipe JSON file.", path.display()))?;
+        let folder = path.parent().unwrap_or(std::path::Path::new(""));
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| "recipe".to_string());
+        let target = to.unwrap_or(folder).join(format!("{}.{}", stem, format.extension()));
+        if dry_run {
+            println!("Would write {}", target.display());
+            continue;
+        }
+        let bytes = render_recipe(client, &recipe, format, qr_code, card, &folder.display().to_string()).await?;
+        if let Some(parent) = target.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, bytes)?;
+        status!("Converted {} to {}", path.display(), target.display());
+    }
     Ok(())
 }
 
-/// Validates if the URL belongs to a supported domain.
-fn validate_supported_url(input_url: &str) -> bool {
-    Url::parse(input_url).is_ok()
-        && (input_url.contains("https://15gram.be/") || input_url.contains("https://dagelijksekost.vrt.be/"))
+/// The `--format`s to write besides the stored recipe; JSON only counts when `--store sqlite` does not write it already.
+fn extra_formats(args: &Args) -> impl Iterator<Item = &render::RecipeFormat> {
+    args.formats.iter().filter(|format| **format != render::RecipeFormat::Json || args.store == StoreMode::Sqlite)
 }
 
-/// Fetches the HTML document from the given URL.
-async fn fetch_html_document(url: &str) -> Result<Html, Box<dyn std::error::Error>> {
-    let html_body = reqwest::get(url).await?.text().await?;
-    Ok(Html::parse_document(&html_body))
+/// Writes the recipe to the backends selected by `store`.
+fn save_recipe(
+    recipe: &Recipe,
+    store: StoreMode,
+    on_conflict: OnConflict,
+    output_folder: &str,
+    db_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for location in save_recipe_silently(recipe, store, on_conflict, output_folder, db_path)? {
+        status!("Recipe saved to {}.", location);
+    }
+    Ok(())
 }
 
-/// Extracts the recipe details from the HTML document using the provided selectors.
-fn extract_recipe(document: &Html, selectors: &RecipeCssSelectors, source_url: &str) -> Recipe {
-    Recipe {
-        title: get_recipe_title(document, &selectors.title, false),
-        description: get_recipe_description(document, &selectors.description, false),
-        ingredients: get_recipe_ingredients(document, &selectors.ingredients, false),
-        steps: get_recipe_steps(document, &selectors.steps, false),
-        image_link: get_recipe_image(document, &selectors.image, false),
-        source_url: source_url.to_string(),
+/// Writes the recipe to the backends selected by `store` without printing, and returns where it was stored
+/// (a file path or `#id`). Nothing is stored when `--on-conflict skip` keeps an existing JSON file.
+fn save_recipe_silently(
+    recipe: &Recipe,
+    store: StoreMode,
+    on_conflict: OnConflict,
+    output_folder: &str,
+    db_path: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut locations = Vec::new();
+    if store != StoreMode::Sqlite {
+        let Some(file_name) = save_recipe_to_file(recipe, on_conflict, output_folder)? else {
+            return Ok(locations);
+        };
+        locations.push(std::path::Path::new(output_folder).join(file_name).display().to_string());
+    }
+    if store != StoreMode::Json {
+        locations.push(format!("#{}", save_recipe_to_database(recipe, db_path)?));
     }
+    Ok(locations)
 }
 
-/// Saves the recipe to a JSON file in the specified output folder.
-fn save_recipe_to_file(recipe: &Recipe, output_folder: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let file_name = match &recipe.title {
-        Some(title) => format!("recipe_{}.json", title),
-        None => "recipe.json".to_string(),
+/// Searches the recipe database, or the JSON files in the output folder when no database exists.
+fn search_library(query: &str, output_folder: &str, db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let hits = if std::path::Path::new(db_path).exists() {
+        let connection = storage::open_database(db_path)?;
+        storage::search_recipes(&connection, query)?
+            .into_iter()
+            .map(|hit| (format!("#{}", hit.id), hit.title, hit.source_url))
+            .collect()
+    } else {
+        library::search_folder(output_folder, query)?
+            .into_iter()
+            .map(|(path, recipe)| (path.display().to_string(), recipe.title, recipe.source_url))
+            .collect::<Vec<_>>()
     };
 
-    let json = serde_json::to_string_pretty(recipe)?;
-    std::fs::create_dir_all(output_folder)?;
-    let file_path = format!("{}/{}", output_folder, file_name);
-    let mut file = File::create(&file_path)?;
-    file.write_all(json.as_bytes())?;
+    if hits.is_empty() {
+        println!("No recipes found matching '{}'.", query);
+    }
+    for (location, title, source_url) in hits {
+        println!("{}\t{}\t{}", location, title.as_deref().unwrap_or("(untitled)"), source_url);
+    }
+    Ok(())
+}
 
-    println!("Recipe JSON file '{}' created successfully in '{}'.", file_name, output_folder);
+/// Prints a table of the saved recipes matching the filter, from the database if it exists or else the output folder.
+fn list_library(filter: &library::LibraryFilter, output_folder: &str, db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let recipes = load_saved_recipes(output_folder, db_path)?;
+    let rows: Vec<[String; 4]> = recipes
+        .iter()
+        .filter(|(_, recipe)| filter.matches(recipe))
+        .map(|(_, recipe)| {
+            [
+                format!("{}{}", if recipe.favorite { "★ " } else { "" }, recipe.title.as_deref().unwrap_or("(untitled)")),
+                library::source_domain(&recipe.source_url).unwrap_or_default(),
+                recipe.scraped_at.as_deref().map(|date| date.chars().take(10).collect()).unwrap_or_default(),
+                recipe.tags.join(", "),
+            ]
+        })
+        .collect();
+
+    if rows.is_empty() {
+        println!("No saved recipes found.");
+        return Ok(());
+    }
+    print!("{}", library::format_table(&["TITLE", "SITE", "SCRAPED", "TAGS"], &rows));
     Ok(())
 }
 
-fn load_selectors(file_path: &str, website: &str) -> Result<RecipeCssSelectors, Box<dyn std::error::Error>> {
-    let content = fs::read_to_string(file_path)?;
-    let value: Value = toml::from_str(&content)?;
+/// Loads the saved recipes with their location (`#id` in the database or the JSON file path).
+fn load_saved_recipes(output_folder: &str, db_path: &str) -> Result<Vec<(String, Recipe)>, Box<dyn std::error::Error>> {
+    if std::path::Path::new(db_path).exists() {
+        let connection = storage::open_database(db_path)?;
+        Ok(storage::load_recipes(&connection)?.into_iter().map(|(id, recipe)| (format!("#{}", id), recipe)).collect())
+    } else {
+        Ok(library::load_library(output_folder)?.into_iter().map(|(path, recipe)| (path.display().to_string(), recipe)).collect())
+    }
+}
 
-    let website_selectors = value.get(website).ok_or("Website not found in selectors file")?;
-    Ok(RecipeCssSelectors {
-        title: website_selectors.get("title").and_then(Value::as_str).unwrap_or_default().to_string(),
-        description: website_selectors.get("description").and_then(Value::as_str).unwrap_or_default().to_string(),
-        ingredients: website_selectors.get("ingredients").and_then(Value::as_str).unwrap_or_default().to_string(),
-        steps: website_selectors.get("steps").and_then(Value::as_str).unwrap_or_default().to_string(),
-        image: website_selectors.get("image").and_then(Value::as_str).unwrap_or_default().to_string(),
-    })
+/// Re-scrapes every saved recipe, writes back the ones that changed and reports the outcome per recipe, publishing
+/// the summary to `mqtt` when it is configured.
+#[cfg_attr(not(feature = "mqtt"), allow(unused_variables))]
+async fn refresh_library(
+    client: &reqwest::Client,
+    mqtt: Option<&config::MqttConfig>,
+    output_folder: &str,
+    db_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let use_database = std::path::Path::new(db_path).exists();
+    let mut connection = if use_database { Some(storage::open_database(db_path)?) } else { None };
+    let saved: Vec<(String, Recipe)> = match &connection {
+        Some(connection) => storage::load_recipes(connection)?.into_iter().map(|(id, r)| (format!("#{}", id), r)).collect(),
+        None => library::load_library(output_folder)?.into_iter().map(|(p, r)| (p.display().to_string(), r)).collect(),
+    };
+
+    let mut summary = refresh::RefreshSummary::default();
+    for (location, recipe) in &saved {
+        let status = match refresh::refresh_recipe(client, recipe).await {
+            Ok(refresh::RefreshStatus::Changed(updated)) => {
+                match &mut connection {
+                    Some(connection) => {
+                        storage::store_recipe(connection, &updated)?;
+                    }
+                    None => write_recipe_json(&updated, std::path::Path::new(location))?,
+                }
+                refresh::RefreshStatus::Changed(updated)
+            }
+            Ok(status) => status,
+            Err(error) => refresh::RefreshStatus::Failed(error.to_string()),
+        };
+        status!("{:<12} {}  {}", status.label(), location, recipe.source_url);
+        summary.record(&status);
+    }
+
+    status!("{}", summary);
+    #[cfg(feature = "mqtt")]
+    if let Some(mqtt) = mqtt {
+        mqtt::publish(mqtt, "summary", serde_json::to_vec(&summary)?).await?;
+    }
+    Ok(())
 }
 
-fn validate_url(input_url: &str) -> Result<Url, String> {
-    let trimmed_url = input_url.trim();
-    match Url::parse(trimmed_url) {
-        Ok(url) => {
-            if url.scheme() == "http" || url.scheme() == "https" {
-                if url.host().is_some() {
-                    Ok(url)
-                } else {
-                    Err("URL must contain a valid host.".to_string())
+/// Scrapes the new entries of every feed that link to supported sites, and returns how many recipes were saved.
+///
+/// Entries are remembered once handled, including ones on unsupported sites or already in the library; entries that
+/// fail to scrape are retried on the next poll.
+async fn poll_feeds(
+    client: &reqwest::Client,
+    feeds: &[String],
+    store: StoreMode,
+    on_conflict: OnConflict,
+    output_folder: &str,
+    db_path: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let selectors = read_selectors_file()?;
+    let mut state = feeds::FeedState::load(output_folder)?;
+    let known: std::collections::HashSet<String> =
+        load_saved_recipes(output_folder, db_path)?.into_iter().map(|(_, recipe)| recipe.source_url).collect();
+    let mut scraped = 0;
+
+    for feed_url in feeds {
+        let links = match feeds::fetch_entry_links(client, feed_url).await {
+            Ok(links) => links,
+            Err(error) => {
+                tracing::warn!("Could not read feed {}: {}", feed_url, error);
+                continue;
+            }
+        };
+        for link in state.unseen(feed_url, &links) {
+            if site_for_url(&selectors, link).is_some() && !known.contains(&urls::normalize_url(link)) {
+                match scrape_page_with_selectors(client, link, &selectors).await {
+                    Ok((recipe, _)) => {
+                        save_recipe(&recipe, store, on_conflict, output_folder, db_path)?;
+                        scraped += 1;
+                    }
+                    Err(error) => {
+                        tracing::warn!("Could not scrape {}: {}", link, error);
+                        continue;
+                    }
                 }
-            } else {
-                Err("URL must use http or https scheme.".to_string())
             }
+            state.mark_seen(feed_url, link);
         }
-        Err(_) => Err("Invalid URL format.".to_string()),
+        state.save(output_folder)?;
     }
+    Ok(scraped)
 }
 
-fn select_elements<'a>(document: &'a Html, selector: &'a str) -> Option<ElementRef<'a>> {
-    let parsed_selector = Selector::parse(selector).ok()?;
-    document.select(&parsed_selector).next()
+/// Polls the clipboard and scrapes each newly copied URL on a supported site with the same options as `--url`,
+/// showing a desktop notification for every result. URLs already in the library are skipped.
+#[cfg(feature = "clipboard")]
+async fn watch_clipboard(args: &Args, interval: u64, output_folder: &str, db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let selectors = read_selectors_file()?;
+    let mut known: std::collections::HashSet<String> =
+        load_saved_recipes(output_folder, db_path)?.into_iter().map(|(_, recipe)| recipe.source_url).collect();
+    // Whatever is on the clipboard when watching starts was not copied for us.
+    let mut last = clipboard::read_text()?;
+    status!("Watching the clipboard for recipe URLs. Press Ctrl+C to stop.");
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+        let text = clipboard::read_text()?;
+        if text == last {
+            continue;
+        }
+        last = text;
+        let Some(url) = last.as_deref().and_then(clipboard::clipboard_url).map(|url| urls::normalize_url(&url)) else {
+            continue;
+        };
+        if site_for_url(&selectors, &url).is_none() || known.contains(&url) {
+            continue;
+        }
+        status!("Scraping {}", url);
+        match scrape_recipe(&url, args, output_folder, db_path).await {
+            Ok(recipe) => {
+                clipboard::notify("Recipe saved", recipe.title.as_deref().unwrap_or(&url));
+                known.insert(recipe.source_url);
+            }
+            Err(error) => {
+                tracing::warn!("Could not scrape {}: {}", url, error);
+                clipboard::notify("Could not scrape recipe", &format!("{}: {}", url, error));
+            }
+        }
+    }
 }
 
-fn get_recipe_title(document: &Html, css_selector: &str, verbose: bool) -> Option<String> {
-    let title = select_elements(document, css_selector).map(|e| e.inner_html());
-    if verbose {
-        println!("Title: {:?}", title);
+/// Answers native messaging requests until the browser closes stdin. Nothing but messages may go to stdout.
+async fn run_native_host(
+    client: &reqwest::Client,
+    store: StoreMode,
+    on_conflict: OnConflict,
+    output_folder: &str,
+    db_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let selectors = read_selectors_file()?;
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    while let Some(message) = native_host::read_message(&mut stdin)? {
+        let response = match native_host_request(client, &selectors, &message, store, on_conflict, output_folder, db_path).await {
+            Ok((recipe, saved)) => native_host::NativeResponse::Recipe { recipe: Box::new(recipe), saved },
+            Err(error) => native_host::NativeResponse::Error { error: error.to_string() },
+        };
+        native_host::write_message(&mut stdout, &response)?;
     }
-    title
+    Ok(())
+}
+
+/// Extracts the recipe from the HTML sent by the extension, or fetches the page when none was sent.
+async fn native_host_request(
+    client: &reqwest::Client,
+    selectors: &str,
+    message: &[u8],
+    store: StoreMode,
+    on_conflict: OnConflict,
+    output_folder: &str,
+    db_path: &str,
+) -> Result<(Recipe, Vec<String>), Box<dyn std::error::Error>> {
+    let request: native_host::NativeRequest = serde_json::from_slice(message)?;
+    let recipe = match &request.html {
+        Some(html) => extract_from_html(html, &request.url)?,
+        None => scrape_page_with_selectors(client, &request.url, selectors).await?.0,
+    };
+    let saved = if request.save { save_recipe_silently(&recipe, store, on_conflict, output_folder, db_path)? } else { Vec::new() };
+    Ok((recipe, saved))
 }
 
-fn get_recipe_description(document: &Html, css_selector: &str, verbose: bool) -> Option<String> {
-    let description = select_elements(document, css_selector).map(|e| e.text().collect::<Vec<_>>().join(" ").trim().to_string());
-    if verbose {
-        println!("Description: {:?}", description);
+/// Where `crawl` finds the recipe URLs.
+enum CrawlSource<'a> {
+    Sitemap(&'a str),
+    /// A listing page, and how many pages of it to visit.
+    Listing(&'a str, usize),
+}
+
+/// Scrapes the recipe URLs of a sitemap or listing one by one, waiting `delay` seconds between requests.
+///
+/// Every scraped URL is recorded in the crawl state right away, so an interrupted crawl resumes where it stopped;
+/// URLs that fail are retried on the next run. With `dry_run`, the recipes are scraped but neither saved nor recorded.
+#[allow(clippy::too_many_arguments)]
+async fn crawl_site(
+    client: &reqwest::Client,
+    source: CrawlSource<'_>,
+    delay: f64,
+    limit: Option<usize>,
+    store: StoreMode,
+    on_conflict: OnConflict,
+    output_folder: &str,
+    db_path: &str,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let start = match source {
+        CrawlSource::Sitemap(url) | CrawlSource::Listing(url, _) => url,
+    };
+    let selectors = read_selectors_file()?;
+    let config = crawl::CrawlConfig::for_url(&selectors, start)?;
+    let patterns = config.patterns()?;
+    let known: std::collections::HashSet<String> =
+        load_saved_recipes(output_folder, db_path)?.into_iter().map(|(_, recipe)| recipe.source_url).collect();
+    let mut state = crawl::CrawlState::load(output_folder)?;
+
+    let found = match source {
+        CrawlSource::Sitemap(sitemap) => crawl::sitemap_urls(client, sitemap).await?,
+        CrawlSource::Listing(from, depth) => crawl::listing_urls(client, from, depth, &config, delay).await?,
+    };
+    let mut seen = std::collections::HashSet::new();
+    let mut urls: Vec<String> = found.iter().map(|url| urls::normalize_url(url)).filter(|url| seen.insert(url.clone())).collect();
+    let found = urls.len();
+    urls.retain(|url| site_for_url(&selectors, url).is_some() && crawl::is_recipe_url(&patterns, url));
+    let recipes = urls.len();
+    urls.retain(|url| !known.contains(url) && !state.is_done(start, url));
+    status!("Found {} URLs, {} recipes, {} not scraped yet.", found, recipes, urls.len());
+
+    let total = limit.map_or(urls.len(), |limit| limit.min(urls.len()));
+    let mut batch = progress::Batch::new(total, recipes - total);
+    for (index, url) in urls.iter().take(total).enumerate() {
+        if index > 0 {
+            tokio::time::sleep(std::time::Duration::from_secs_f64(delay.max(0.0))).await;
+        }
+        batch.start(url);
+        let result = match scrape_page_with_selectors(client, url, &selectors).await {
+            Ok((recipe, _)) if dry_run => {
+                batch.println(format!("Would save '{}' from {}", recipe.title.as_deref().unwrap_or("(untitled)"), url));
+                Ok(())
+            }
+            Ok((recipe, _)) => {
+                save_recipe_silently(&recipe, store, on_conflict, output_folder, db_path)?;
+                state.mark_done(start, url);
+                state.save(output_folder)?;
+                Ok(())
+            }
+            Err(error) => Err(error.to_string()),
+        };
+        batch.record(url, result);
+    }
+    status!("Crawl finished.\n{}", batch.finish());
+    if dry_run {
+        println!("Dry run: nothing was saved.");
     }
-    description
+    Ok(())
 }
 
-fn get_recipe_ingredients(document: &Html, css_selector: &str, verbose: bool) -> Option<Vec<String>> {
-    let ingredients = select_elements(document, css_selector).map(|e| {
-        e.text().collect::<Vec<_>>().iter().map(|&s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
-    });
-    if verbose {
-        println!("Ingredients: {:?}", ingredients);
+/// Lists the recipe URLs the site's search page finds for the query, scraping the first `scrape` of them (without
+/// saving them with `dry_run`).
+#[allow(clippy::too_many_arguments)]
+async fn find_recipes(
+    client: &reqwest::Client,
+    query: &str,
+    site: &str,
+    scrape: Option<usize>,
+    store: StoreMode,
+    on_conflict: OnConflict,
+    output_folder: &str,
+    db_path: &str,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let selectors = read_selectors_file()?;
+    let config = find::SearchConfig::for_site(&selectors, site)?;
+    let results = find::find_recipes(client, &config, query).await?;
+    if results.is_empty() {
+        println!("No recipes found for '{}'.", query);
+        return Ok(());
+    }
+    for url in &results {
+        println!("{}", url);
+    }
+    let to_scrape = scrape.unwrap_or(0).min(results.len());
+    if to_scrape == 0 {
+        return Ok(());
+    }
+    let mut batch = progress::Batch::new(to_scrape, 0);
+    for url in results.iter().take(to_scrape) {
+        batch.start(url);
+        let result = match scrape_page_with_selectors(client, url, &selectors).await {
+            Ok((recipe, _)) if dry_run => {
+                batch.println(format!("Would save '{}' from {}", recipe.title.as_deref().unwrap_or("(untitled)"), url));
+                Ok(())
+            }
+            Ok((recipe, _)) => {
+                save_recipe_silently(&recipe, store, on_conflict, output_folder, db_path)?;
+                Ok(())
+            }
+            Err(error) => Err(error.to_string()),
+        };
+        batch.record(url, result);
+    }
+    status!("{}", batch.finish());
+    if dry_run {
+        println!("Dry run: nothing was saved.");
     }
-    ingredients
+    Ok(())
 }
 
-fn get_recipe_steps(document: &Html, css_selector: &str, verbose: bool) -> Option<Vec<String>> {
-    let steps = select_elements(document, css_selector).map(|e| {
-        e.text().collect::<Vec<_>>().iter().map(|&s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
-    });
-    if verbose {
-        println!("Steps: {:?}", steps);
+/// Runs the scheduled jobs forever, logging each run. A failing job is logged and runs again at its next time.
+async fn run_daemon(
+    client: &reqwest::Client,
+    jobs: &[daemon::JobConfig],
+    store: StoreMode,
+    on_conflict: OnConflict,
+    mqtt: Option<&config::MqttConfig>,
+    output_folder: &str,
+    db_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if jobs.is_empty() {
+        return Err("No jobs configured: add [[daemon.job]] entries to the config file.".into());
+    }
+    let jobs = jobs.iter().cloned().map(daemon::ScheduledJob::new).collect::<Result<Vec<_>, _>>()?;
+    let now = chrono::Local::now();
+    let mut next_runs: Vec<Option<chrono::DateTime<chrono::Local>>> = jobs.iter().map(|job| job.next_run(&now)).collect();
+    for (job, next_run) in jobs.iter().zip(&next_runs) {
+        let next = next_run.map(|time| time.to_rfc3339()).unwrap_or_else(|| "never".to_string());
+        tracing::info!(event = "scheduled", job = job.config.task.name(), next_run = next, "daemon job");
+    }
+
+    loop {
+        let Some(due) = next_runs.iter().flatten().min().copied() else {
+            return Err("None of the scheduled jobs will run again.".into());
+        };
+        let wait = (due - chrono::Local::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        for (job, next_run) in jobs.iter().zip(next_runs.iter_mut()) {
+            if *next_run != Some(due) {
+                continue;
+            }
+            let name = job.config.task.name();
+            tracing::info!(event = "start", job = name, "daemon job");
+            let started = std::time::Instant::now();
+            let result = match job.config.task {
+                daemon::Task::Refresh => refresh_library(client, mqtt, output_folder, db_path).await.map(|_| String::new()),
+                daemon::Task::Feeds => poll_feeds(client, &job.config.feeds, store, on_conflict, output_folder, db_path).await.map(|n| n.to_string()),
+            };
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            match result {
+                Ok(scraped) if scraped.is_empty() => tracing::info!(event = "finish", job = name, elapsed_ms, "daemon job"),
+                Ok(scraped) => tracing::info!(event = "finish", job = name, elapsed_ms, scraped, "daemon job"),
+                Err(error) => tracing::error!(event = "error", job = name, elapsed_ms, %error, "daemon job"),
+            }
+            *next_run = job.next_run(&chrono::Local::now());
+        }
     }
-    steps
 }
 
-fn get_recipe_image(document: &Html, css_selector: &str, verbose: bool) -> Option<String> {
-    let image_link = select_elements(document, css_selector).and_then(|e| e.value().attr("src").map(|src| src.to_string()));
-    if verbose {
-        println!("Image Link: {:?}", image_link);
+/// Imports the recipes in the given export files into the database, skipping recipes that are already present.
+fn import_recipes(paths: &[std::path::PathBuf], db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    create_parent_dir(db_path)?;
+    let mut connection = storage::open_database(db_path)?;
+    let (mut imported, mut skipped, mut failed) = (0, 0, 0);
+
+    for file in import::collect_import_files(paths)? {
+        let recipes = match import::read_import_file(&file) {
+            Ok(recipes) => recipes,
+            Err(error) => {
+                tracing::warn!("Failed to read '{}': {}", file.display(), error);
+                failed += 1;
+                continue;
+            }
+        };
+        for mut recipe in recipes {
+            // The database keys recipes by source URL, so exports without one get a content-derived key.
+            if recipe.source_url.is_empty() {
+                recipe.source_url = format!("urn:sha256:{}", storage::content_hash(&recipe));
+            }
+            let title = recipe.title.clone().unwrap_or_else(|| "(untitled)".to_string());
+            match storage::find_duplicate(&connection, &recipe)? {
+                Some(storage::Duplicate::SourceUrl(id)) => {
+                    status!("Skipped '{}': same source URL as recipe #{}.", title, id);
+                    skipped += 1;
+                }
+                Some(storage::Duplicate::Content(id)) => {
+                    status!("Skipped '{}': same content as recipe #{}.", title, id);
+                    skipped += 1;
+                }
+                None => {
+                    let id = storage::store_recipe(&mut connection, &recipe)?;
+                    status!("Imported '{}' as recipe #{}.", title, id);
+                    imported += 1;
+                }
+            }
+        }
+    }
+
+    status!("Import finished: {} imported, {} skipped, {} files failed.", imported, skipped, failed);
+    Ok(())
+}
+
+/// Writes the saved recipes matching the filter into a single zip archive.
+fn export_library(
+    filter: &library::LibraryFilter,
+    recipe_format: render::RecipeFormat,
+    qr_code: bool,
+    card: &config::CardConfig,
+    archive_path: &str,
+    output_folder: &str,
+    db_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let recipes: Vec<Recipe> =
+        load_saved_recipes(output_folder, db_path)?.into_iter().map(|(_, r)| r).filter(|r| filter.matches(r)).collect();
+    create_parent_dir(archive_path)?;
+    export::write_zip_archive(File::create(archive_path)?, &recipes, recipe_format, qr_code, card)?;
+
+    status!("Exported {} recipes to '{}'.", recipes.len(), archive_path);
+    Ok(())
+}
+
+/// Adds or removes tags on a saved recipe.
+fn run_tag(action: &TagAction, output_folder: &str, db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (TagAction::Add { recipe, tags } | TagAction::Remove { recipe, tags }) = action;
+    let (location, mut saved) = find_saved_recipe(recipe, output_folder, db_path)?;
+    for tag in tags.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+        let existing = saved.tags.iter().position(|t| t.eq_ignore_ascii_case(tag));
+        match (action, existing) {
+            (TagAction::Add { .. }, None) => saved.tags.push(tag.to_string()),
+            (TagAction::Remove { .. }, Some(index)) => {
+                saved.tags.remove(index);
+            }
+            _ => {}
+        }
     }
-    image_link
+    saved.tags.sort();
+    update_saved_recipe(&location, &saved, db_path)?;
+
+    let tags = if saved.tags.is_empty() { "(none)".to_string() } else { saved.tags.join(", ") };
+    status!("Tags of '{}': {}", saved.title.as_deref().unwrap_or("(untitled)"), tags);
+    Ok(())
 }
 
-fn parse_website_name(url: &str) -> Option<String> {
-    let url = Url::parse(url).ok()?;
-    url.host_str()?.split('.').next().map(|s| s.to_string())
+/// Writes a changed saved recipe back where it came from: its JSON file, or the database for `#id` locations.
+fn update_saved_recipe(location: &str, recipe: &Recipe, db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if location.starts_with('#') {
+        let mut connection = storage::open_database(db_path)?;
+        storage::store_recipe(&mut connection, recipe)?;
+        Ok(())
+    } else {
+        write_recipe_json(recipe, std::path::Path::new(location))
+    }
+}
+
+/// Deletes a saved recipe where it came from: its JSON file, or the database for `#id` locations.
+fn delete_saved_recipe(location: &str, db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    match location.strip_prefix('#') {
+        Some(id) => {
+            let mut connection = storage::open_database(db_path)?;
+            storage::delete_recipe(&mut connection, id.parse()?)?;
+            Ok(())
+        }
+        None => Ok(std::fs::remove_file(location)?),
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Runs a meal plan subcommand against the plan file in the output folder.
+async fn run_plan(client: &reqwest::Client, action: &PlanAction, output_folder: &str, db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut meal_plan = plan::MealPlan::load(output_folder)?;
+    match action {
+        PlanAction::Add { date, recipe } => {
+            let (location, saved) = find_saved_recipe(recipe, output_folder, db_path)?;
+            let title = saved.title.clone().unwrap_or_else(|| "(untitled)".to_string());
+            meal_plan.add(plan::PlanEntry { date: *date, recipe: location, title: saved.title, source_url: saved.source_url });
+            meal_plan.save(output_folder)?;
+            status!("Planned '{}' on {}.", title, date);
+        }
+        PlanAction::Remove { date, recipe } => {
+            let removed = meal_plan.remove(*date, recipe.as_deref());
+            meal_plan.save(output_folder)?;
+            status!("Removed {} planned recipe(s) from {}.", removed, date);
+        }
+        PlanAction::Show { range } => {
+            let (start, end) = plan::range_bounds(range, chrono::Local::now().date_naive())?;
+            let rows: Vec<[String; 3]> = meal_plan
+                .between(start, end)
+                .map(|entry| {
+                    [
+                        entry.date.format("%a %Y-%m-%d").to_string(),
+                        entry.title.clone().unwrap_or_else(|| "(untitled)".to_string()),
+                        entry.recipe.clone(),
+                    ]
+                })
+                .collect();
+            if rows.is_empty() {
+                println!("Nothing planned between {} and {}.", start, end);
+            } else {
+                print!("{}", library::format_table(&["DATE", "RECIPE", "LOCATION"], &rows));
+            }
+        }
+        PlanAction::Generate { days, start, tags, max_time, no_repeat_weeks, seed } => {
+            let saved = load_saved_recipes(output_folder, db_path)?;
+            let options = plan::GenerateOptions {
+                start: start.unwrap_or_else(|| chrono::Local::now().date_naive()),
+                days: *days,
+                tags: tags.clone(),
+                max_minutes: *max_time,
+                no_repeat_weeks: *no_repeat_weeks,
+                seed: seed.unwrap_or_else(|| chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64),
+            };
+            let menu = plan::generate_menu(&meal_plan, &saved, &options);
+            if menu.len() < *days as usize {
+                tracing::warn!("Only {} of {} days could be filled with matching recipes.", menu.len(), days);
+            }
+
+            let rows: Vec<[String; 3]> = menu
+                .iter()
+                .map(|(date, (_, recipe))| {
+                    [
+                        date.format("%a %Y-%m-%d").to_string(),
+                        recipe.title.clone().unwrap_or_else(|| "(untitled)".to_string()),
+                        plan::recipe_minutes(recipe).map(|minutes| format!("{} min", minutes)).unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            print!("{}", library::format_table(&["DATE", "RECIPE", "TIME"], &rows));
+
+            println!("\nShopping list:");
+            let lines = menu.iter().flat_map(|(_, (_, recipe))| recipe.ingredients.iter().flatten().map(String::as_str));
+            print_shopping_list(&shopping::StoreLayout::load()?.shopping_list(lines));
 
-    #[test]
-    fn test_validate_supported_url_valid() {
-        assert!(validate_supported_url("https://15gram.be/some-recipe"));
+            for (date, (location, recipe)) in menu {
+                let entry = plan::PlanEntry {
+                    date,
+                    recipe: location.clone(),
+                    title: recipe.title.clone(),
+                    source_url: recipe.source_url.clone(),
+                };
+                meal_plan.add(entry);
+            }
+            meal_plan.save(output_folder)?;
+        }
+        PlanAction::Shopping { range, send, home_assistant } => {
+            let (start, end) = plan::range_bounds(range, chrono::Local::now().date_naive())?;
+            let saved = load_saved_recipes(output_folder, db_path)?;
+            let planned: Vec<&Recipe> = meal_plan
+                .between(start, end)
+                .filter_map(|entry| {
+                    let found = saved.iter().find(|(location, recipe)| *location == entry.recipe || recipe.source_url == entry.source_url);
+                    if found.is_none() {
+                        tracing::warn!("Planned recipe {} is no longer in the library.", entry.recipe);
+                    }
+                    found.map(|(_, recipe)| recipe)
+                })
+                .collect();
+            if planned.is_empty() {
+                println!("Nothing planned between {} and {}.", start, end);
+                return Ok(());
+            }
+
+            let lines = planned.iter().flat_map(|recipe| recipe.ingredients.iter().flatten().map(String::as_str));
+            let sections = shopping::StoreLayout::load()?.shopping_list(lines);
+            print_shopping_list(&sections);
+            if let Some(ShoppingTarget::HomeAssistant) = send {
+                let items: Vec<_> = sections.into_iter().flat_map(|(_, items)| items).collect();
+                let added = push::home_assistant::add_items(client, home_assistant, &items).await?;
+                status!("\nAdded {} items to {}.", added, home_assistant.home_assistant_entity);
+            }
+        }
     }
+    Ok(())
+}
+
+/// Prints a shopping list grouped by store section.
+fn print_shopping_list(sections: &[(String, Vec<ingredients::ShoppingItem>)]) {
+    for (section, items) in sections {
+        println!("\n{}", section);
+        for item in items {
+            println!("- {}", item);
+        }
+    }
+}
+
+/// Finds a saved recipe by location, file name, `#id`, source URL or (case-insensitive) title.
+fn find_saved_recipe(reference: &str, output_folder: &str, db_path: &str) -> Result<(String, Recipe), Box<dyn std::error::Error>> {
+    let reference = reference.trim();
+    load_saved_recipes(output_folder, db_path)?
+        .into_iter()
+        .find(|(location, recipe)| {
+            location == reference
+                || std::path::Path::new(location).file_name().is_some_and(|name| name == reference)
+                || location.strip_prefix('#') == Some(reference)
+                || recipe.source_url == reference
+                || recipe.title.as_deref().is_some_and(|title| title.eq_ignore_ascii_case(reference))
+        })
+        .ok_or_else(|| format!("No saved recipe matches '{}'.", reference).into())
+}
+
+/// Saves the recipe to a JSON file in the specified output folder and returns the file name, or `None` when
+/// `--on-conflict skip` kept an existing file.
+fn save_recipe_to_file(recipe: &Recipe, on_conflict: OnConflict, output_folder: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let Some(stem) = target_file_stem(recipe, on_conflict, output_folder)? else {
+        return Ok(None);
+    };
+    let file_name = format!("{}.json", stem);
+
+    std::fs::create_dir_all(output_folder)?;
+    write_recipe_json(recipe, std::path::Path::new(&format!("{}/{}", output_folder, file_name)))?;
+    Ok(Some(file_name))
+}
 
-    #[test]
-    fn test_validate_supported_url_invalid() {
-        assert!(!validate_supported_url("https://www.unsupportedsite.com"));
+/// The file name (without extension) to save the recipe's JSON under, applying `on_conflict` when
+/// [`recipe_file_stem`] already exists; `None` to skip the recipe.
+fn target_file_stem(recipe: &Recipe, on_conflict: OnConflict, output_folder: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let stem = recipe_file_stem(recipe, output_folder);
+    let exists = |stem: &str| std::path::Path::new(output_folder).join(format!("{}.json", stem)).exists();
+    if !exists(&stem) {
+        return Ok(Some(stem));
+    }
+    match on_conflict {
+        OnConflict::Overwrite => Ok(Some(stem)),
+        OnConflict::Skip => {
+            tracing::warn!("Skipped '{}': {}.json already exists.", recipe.title.as_deref().unwrap_or("(untitled)"), stem);
+            Ok(None)
+        }
+        OnConflict::Rename => Ok(Some(foodscraper::slug::unique_stem(&stem, exists))),
+        OnConflict::Fail => {
+            let message = format!("{}.json already exists in {}; pass --on-conflict to overwrite, skip or rename it.", stem, output_folder);
+            Err(Box::new(ScrapeError::Io(std::io::Error::new(std::io::ErrorKind::AlreadyExists, message))))
+        }
     }
-   
-   #[test]
-   fn test_validate_url_valid() {
-       let result = validate_url("https://www.example.com");
-       assert!(result.is_ok());
-       // you can add more assertions if needed
-   }
+}
 
-   #[test]
-   fn test_validate_url_invalid() {
-       let result = validate_url("invalid url");
-       assert!(result.is_err());
-   }
-     #[test]
-   fn test_parse_website_name_valid() {
-       let url = "https://15gram.be/recipe";
-       let expected_name = Some("15gram".to_string());
-       assert_eq!(parse_website_name(url), expected_name);
-   }
+/// The file name (without extension) a recipe is saved under in the output folder: `recipe_<slug>` (see
+/// [`foodscraper::slug`]), with a `-2`, `-3`, … suffix while the JSON file of that name holds another recipe.
+fn recipe_file_stem(recipe: &Recipe, output_folder: &str) -> String {
+    let stem = foodscraper::slug::recipe_stem(recipe.title.as_deref());
+    foodscraper::slug::unique_stem(&stem, |candidate| {
+        let path = std::path::Path::new(output_folder).join(format!("{}.json", candidate));
+        path.exists() && library::load_recipe(&path).is_none_or(|saved| saved.source_url != recipe.source_url)
+    })
+}
 
-   #[test]
-   fn test_parse_website_name_invalid() {
-       let url = "invalid_url";
-       assert_eq!(parse_website_name(url), None);
-   }
-}
\ No newline at end of file
+/// Writes the recipe as pretty-printed JSON to the given path, replacing any existing file.
+fn write_recipe_json(recipe: &Recipe, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(recipe)?;
+    let mut file = File::create(path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Creates the directory a file will be written to, if the path has one.
+fn create_parent_dir(path: &str) -> std::io::Result<()> {
+    match std::path::Path::new(path).parent().filter(|p| !p.as_os_str().is_empty()) {
+        Some(parent) => std::fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+}
+
+/// Stores the recipe in the SQLite database at `db_path`, creating the database if needed, and returns its id.
+fn save_recipe_to_database(recipe: &Recipe, db_path: &str) -> Result<i64, Box<dyn std::error::Error>> {
+    create_parent_dir(db_path)?;
+    let mut connection = storage::open_database(db_path)?;
+    Ok(storage::store_recipe(&mut connection, recipe)?)
+}
@@ -0,0 +1,46 @@
+//! Publishing scrape results to an MQTT broker configured in the `[mqtt]` section of the config file.
+//!
+//! Every scraped recipe is published as JSON to `<topic>/recipe`, and the outcome of a `refresh` run to
+//! `<topic>/summary`, for home-automation dashboards.
+
+use crate::config::MqttConfig;
+use rumqttc::{AsyncClient, Event, MqttOptions, Outgoing, Packet, QoS};
+use std::time::Duration;
+
+/// Publishes the payload to `<topic>/<subtopic>` and waits until the broker acknowledged it.
+pub async fn publish(config: &MqttConfig, subtopic: &str, payload: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(10));
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        options.set_credentials(username, password);
+    }
+
+    let (client, mut event_loop) = AsyncClient::new(options, 10);
+    client.publish(topic(&config.topic, subtopic), QoS::AtLeastOnce, config.retain, payload).await?;
+    let wait = async {
+        loop {
+            match event_loop.poll().await? {
+                Event::Incoming(Packet::PubAck(_)) => client.disconnect().await?,
+                Event::Outgoing(Outgoing::Disconnect) => return Ok::<_, Box<dyn std::error::Error>>(()),
+                _ => {}
+            }
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(30), wait).await.map_err(|_| "Timed out publishing to the MQTT broker.")?
+}
+
+/// Joins the configured base topic and a subtopic.
+pub fn topic(base: &str, subtopic: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), subtopic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic() {
+        assert_eq!(topic("home/foodscraper/", "recipe"), "home/foodscraper/recipe");
+        assert_eq!(topic("foodscraper", "summary"), "foodscraper/summary");
+    }
+}
@@ -0,0 +1,86 @@
+//! `native-host`: the browser-extension side of Chrome and Firefox native messaging.
+//!
+//! Each message is a JSON document preceded by its length as a 32-bit integer in native byte order,
+//! read from stdin and answered on stdout. A request looks like
+//! `{"url": "https://...", "html": "<html>...", "save": true}`; `html` is the page as the browser
+//! rendered it and is fetched from `url` when left out.
+
+use crate::Recipe;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+
+/// Chrome refuses messages from the host larger than this.
+const MAX_RESPONSE_LEN: usize = 1024 * 1024;
+
+/// A scrape request from the extension.
+#[derive(Debug, Deserialize)]
+pub struct NativeRequest {
+    pub url: String,
+    #[serde(default)]
+    pub html: Option<String>,
+    /// Store the recipe with the `--store` backend.
+    #[serde(default)]
+    pub save: bool,
+}
+
+/// The answer sent back to the extension.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum NativeResponse {
+    Recipe {
+        recipe: Box<Recipe>,
+        /// Where the recipe was stored (file paths or `#id`), when `save` was set.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        saved: Vec<String>,
+    },
+    Error {
+        error: String,
+    },
+}
+
+/// Reads one message, or returns `None` when the browser closed the pipe.
+pub fn read_message(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    let mut message = vec![0u8; u32::from_ne_bytes(length) as usize];
+    reader.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+/// Writes one message and flushes it.
+pub fn write_message(writer: &mut impl Write, response: &NativeResponse) -> io::Result<()> {
+    let mut message = serde_json::to_vec(response)?;
+    if message.len() > MAX_RESPONSE_LEN {
+        message = serde_json::to_vec(&NativeResponse::Error { error: "The recipe is too large to send back.".to_string() })?;
+    }
+    writer.write_all(&(message.len() as u32).to_ne_bytes())?;
+    writer.write_all(&message)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_framing() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &NativeResponse::Error { error: "nope".to_string() }).unwrap();
+        assert_eq!(&buffer[..4], &16u32.to_ne_bytes());
+
+        let mut reader = buffer.as_slice();
+        assert_eq!(read_message(&mut reader).unwrap().unwrap(), br#"{"error":"nope"}"#);
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let request: NativeRequest = serde_json::from_str(r#"{"url": "https://15gram.be/recepten/lasagne"}"#).unwrap();
+        assert!(request.html.is_none());
+        assert!(!request.save);
+    }
+}
@@ -0,0 +1,156 @@
+//! Writing recipes as notes into an Obsidian vault with `--obsidian-vault`.
+//!
+//! A note gets YAML frontmatter (tags, source, times), the image embedded from the vault's attachment
+//! folder, and every ingredient linked as a `[[wiki-link]]` so recipes sharing an ingredient connect.
+
+use crate::Recipe;
+use crate::ingredients::parse_ingredient;
+use crate::shopping::StoreLayout;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Used when the vault does not configure an attachment folder.
+const DEFAULT_ATTACHMENT_FOLDER: &str = "attachments";
+
+/// Writes the recipe note into the vault and returns its path.
+///
+/// The image is downloaded into the attachment folder; a local image (from `--download-images`) is copied from
+/// the output folder instead.
+pub async fn write_note(
+    client: &reqwest::Client,
+    vault: &Path,
+    recipe: &Recipe,
+    output_folder: &str,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let title = note_name(recipe.title.as_deref().unwrap_or("Recipe"));
+    let image = match &recipe.image_link {
+        Some(link) => {
+            let folder = attachment_folder(vault);
+            fs::create_dir_all(&folder)?;
+            let stem = format!("{} image", title);
+            let file_name = match url::Url::parse(link) {
+                Ok(_) => crate::images::download_image(client, link, &recipe.source_url, &folder.to_string_lossy(), &stem).await?,
+                Err(_) => {
+                    let extension = Path::new(link).extension().and_then(|ext| ext.to_str()).unwrap_or("jpg");
+                    let file_name = format!("{}.{}", stem, extension);
+                    fs::copy(Path::new(output_folder).join(link), folder.join(&file_name))?;
+                    file_name
+                }
+            };
+            Some(file_name)
+        }
+        None => None,
+    };
+
+    let path = vault.join(format!("{}.md", title));
+    fs::write(&path, render_note(recipe, image.as_deref(), &StoreLayout::load()?))?;
+    Ok(path)
+}
+
+/// The attachment folder from `.obsidian/app.json`, relative to the vault.
+fn attachment_folder(vault: &Path) -> PathBuf {
+    let configured = fs::read_to_string(vault.join(".obsidian").join("app.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+        .and_then(|config| config.get("attachmentFolderPath").and_then(Value::as_str).map(str::to_string));
+    let folder = configured.as_deref().unwrap_or(DEFAULT_ATTACHMENT_FOLDER);
+    vault.join(folder.trim_start_matches("./").trim_start_matches('/'))
+}
+
+/// Renders the note: frontmatter, embedded image, linked ingredients and numbered steps.
+pub fn render_note(recipe: &Recipe, image: Option<&str>, layout: &StoreLayout) -> String {
+    let mut note = String::from("---\n");
+    if !recipe.tags.is_empty() {
+        note.push_str("tags:\n");
+        for tag in &recipe.tags {
+            note.push_str(&format!("  - {}\n", yaml_string(&tag.replace(' ', "-"))));
+        }
+    }
+    note.push_str(&format!("source: {}\n", yaml_string(&recipe.source_url)));
+    if let Some(minutes) = crate::plan::recipe_minutes(recipe) {
+        note.push_str(&format!("total_time: {}\n", minutes));
+    }
+    if let Some(scraped_at) = &recipe.scraped_at {
+        note.push_str(&format!("scraped: {}\n", yaml_string(scraped_at)));
+    }
+    if let Some(language) = &recipe.language {
+        note.push_str(&format!("language: {}\n", language));
+    }
+    note.push_str("---\n\n");
+
+    note.push_str(&format!("# {}\n\n", recipe.title.as_deref().unwrap_or("Recipe")));
+    if let Some(image) = image {
+        note.push_str(&format!("![[{}]]\n\n", image));
+    }
+    if let Some(description) = &recipe.description {
+        note.push_str(&format!("{}\n\n", description));
+    }
+    if let Some(ingredients) = &recipe.ingredients {
+        note.push_str("## Ingredients\n\n");
+        for ingredient in ingredients {
+            note.push_str(&format!("- {}\n", link_ingredient(ingredient, layout)));
+        }
+        note.push('\n');
+    }
+    if let Some(steps) = &recipe.steps {
+        note.push_str("## Steps\n\n");
+        for (number, step) in steps.iter().enumerate() {
+            note.push_str(&format!("{}. {}\n", number + 1, step));
+        }
+        note.push('\n');
+    }
+    note
+}
+
+/// Wraps the ingredient name in a wiki-link to its canonical name, e.g. `2 [[ui|uien]], gesnipperd`.
+pub fn link_ingredient(line: &str, layout: &StoreLayout) -> String {
+    let parsed = parse_ingredient(line);
+    let food = parsed.name.split(',').next().unwrap_or_default().trim();
+    let Some(start) = line.rfind(&parsed.name).filter(|_| !food.is_empty()) else {
+        return line.to_string();
+    };
+    let canonical = layout.canonical_name(food);
+    let link = if canonical == food.to_lowercase() { format!("[[{}]]", food) } else { format!("[[{}|{}]]", canonical, food) };
+    format!("{}{}{}", &line[..start], link, &line[start + food.len()..])
+}
+
+/// A note file name without the characters Obsidian does not allow in note names.
+fn note_name(title: &str) -> String {
+    let name: String = title.chars().filter(|c| !matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']')).collect();
+    let name = name.trim().to_string();
+    if name.is_empty() { "Recipe".to_string() } else { name }
+}
+
+fn yaml_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_ingredient() {
+        let layout: StoreLayout = toml::from_str("[canonical]\nui = [\"uien\"]\n").unwrap();
+        assert_eq!(link_ingredient("500 gr gehakt", &layout), "500 gr [[gehakt]]");
+        assert_eq!(link_ingredient("2 uien, gesnipperd", &layout), "2 [[ui|uien]], gesnipperd");
+        assert_eq!(link_ingredient("", &layout), "");
+    }
+
+    #[test]
+    fn test_render_note_frontmatter() {
+        let recipe = Recipe {
+            title: Some("Soep".to_string()),
+            source_url: "https://15gram.be/recepten/soep".to_string(),
+            tags: vec!["winter soep".to_string()],
+            total_time: Some(30),
+            ..Default::default()
+        };
+        let layout: StoreLayout = toml::from_str("").unwrap();
+        let note = render_note(&recipe, Some("Soep image.jpg"), &layout);
+        assert!(note.starts_with(
+            "---\ntags:\n  - \"winter-soep\"\nsource: \"https://15gram.be/recepten/soep\"\ntotal_time: 30\n---\n\n# Soep\n\n![[Soep image.jpg]]\n"
+        ));
+    }
+}
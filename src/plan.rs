@@ -0,0 +1,275 @@
+//! Meal planning: assigning saved recipes to dates.
+//!
+//! The plan is a `meal-plan.json` file in the output folder, so it works the same whether recipes
+//! are kept as JSON files or in the SQLite database.
+
+use crate::Recipe;
+use crate::steps::estimated_minutes;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// File name of the meal plan inside the output folder.
+pub const PLAN_FILE: &str = "meal-plan.json";
+
+/// A recipe planned for a date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanEntry {
+    /// The day the recipe is planned for.
+    pub date: NaiveDate,
+    /// Where the recipe is saved: its JSON file path or `#id` in the database.
+    pub recipe: String,
+    /// The recipe title at planning time, so the plan stays readable if the recipe is removed.
+    pub title: Option<String>,
+    /// The recipe's source URL.
+    pub source_url: String,
+}
+
+/// The meal plan, kept sorted by date.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MealPlan {
+    pub entries: Vec<PlanEntry>,
+}
+
+impl MealPlan {
+    /// Loads the plan from the output folder, starting empty if there is no plan file yet.
+    pub fn load(output_folder: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = Path::new(output_folder).join(PLAN_FILE);
+        if !path.exists() {
+            return Ok(MealPlan::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the plan to the output folder.
+    pub fn save(&self, output_folder: &str) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(output_folder)?;
+        fs::write(Path::new(output_folder).join(PLAN_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Adds an entry, keeping entries ordered by date.
+    pub fn add(&mut self, entry: PlanEntry) {
+        let position = self.entries.partition_point(|existing| existing.date <= entry.date);
+        self.entries.insert(position, entry);
+    }
+
+    /// Removes the entries on `date`, or only those for `recipe` when given. Returns how many were removed.
+    pub fn remove(&mut self, date: NaiveDate, recipe: Option<&str>) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.date != date || recipe.is_some_and(|recipe| !entry.matches(recipe)));
+        before - self.entries.len()
+    }
+
+    /// The entries between `start` and `end`, inclusive.
+    pub fn between(&self, start: NaiveDate, end: NaiveDate) -> impl Iterator<Item = &PlanEntry> {
+        self.entries.iter().filter(move |entry| entry.date >= start && entry.date <= end)
+    }
+}
+
+impl PlanEntry {
+    /// Whether the entry refers to the given recipe reference (location, file name, URL or title).
+    pub fn matches(&self, reference: &str) -> bool {
+        self.recipe == reference
+            || Path::new(&self.recipe).file_name().is_some_and(|name| name == reference)
+            || self.source_url == reference
+            || self.title.as_deref().is_some_and(|title| title.eq_ignore_ascii_case(reference))
+    }
+}
+
+/// Resolves a named range (`today`, `week`, `next-week`, `month` or a `YYYY-MM-DD` date) to inclusive bounds.
+pub fn range_bounds(range: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    match range {
+        "today" => Ok((today, today)),
+        "week" => Ok((week_start, week_start + Duration::days(6))),
+        "next-week" => Ok((week_start + Duration::days(7), week_start + Duration::days(13))),
+        "month" => {
+            let start = today.with_day(1).unwrap();
+            let next_month = if start.month() == 12 {
+                NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+            };
+            Ok((start, next_month.unwrap() - Duration::days(1)))
+        }
+        date => date
+            .parse::<NaiveDate>()
+            .map(|date| (date, date))
+            .map_err(|_| format!("Unknown range '{}': use today, week, next-week, month or a YYYY-MM-DD date.", date)),
+    }
+}
+
+/// Constraints for generating a menu with `plan generate`.
+#[derive(Debug)]
+pub struct GenerateOptions {
+    /// The first day of the menu.
+    pub start: NaiveDate,
+    /// How many days to plan.
+    pub days: u32,
+    /// Tags every picked recipe must carry.
+    pub tags: Vec<String>,
+    /// Maximum total time in minutes; recipes without a known time are skipped when set.
+    pub max_minutes: Option<u32>,
+    /// Skip recipes planned within this many weeks before the start or already planned in the range.
+    pub no_repeat_weeks: u32,
+    /// Seed for shuffling equally suitable recipes.
+    pub seed: u64,
+}
+
+/// The total time of a recipe in minutes: the scraped total time, or else the sum of its step timers.
+pub fn recipe_minutes(recipe: &Recipe) -> Option<u32> {
+    recipe.total_time.or_else(|| recipe.timers.as_deref().and_then(estimated_minutes))
+}
+
+/// Picks one saved recipe per day matching the options, preferring recipes that were planned longest ago.
+///
+/// Returns fewer days than requested when not enough recipes qualify.
+pub fn generate_menu<'a>(
+    plan: &MealPlan,
+    saved: &'a [(String, Recipe)],
+    options: &GenerateOptions,
+) -> Vec<(NaiveDate, &'a (String, Recipe))> {
+    let end = options.start + Duration::days(options.days as i64 - 1);
+    let repeat_window_start = options.start - Duration::weeks(options.no_repeat_weeks as i64);
+    let last_planned = |location: &str, recipe: &Recipe| {
+        let planned = plan.entries.iter().filter(|entry| entry.recipe == location || entry.source_url == recipe.source_url);
+        planned.map(|entry| entry.date).max()
+    };
+
+    let mut candidates: Vec<(Option<NaiveDate>, &(String, Recipe))> = saved
+        .iter()
+        .filter(|(_, recipe)| {
+            options.tags.iter().all(|tag| recipe.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+                && options.max_minutes.is_none_or(|max| recipe_minutes(recipe).is_some_and(|minutes| minutes <= max))
+        })
+        .map(|entry| (last_planned(&entry.0, &entry.1), entry))
+        .filter(|(last, _)| last.is_none_or(|date| date < repeat_window_start || date > end))
+        .collect();
+    shuffle(&mut candidates, options.seed);
+    candidates.sort_by_key(|(last, _)| *last);
+
+    (0..options.days as i64)
+        .map(|offset| options.start + Duration::days(offset))
+        .zip(candidates.into_iter().map(|(_, entry)| entry))
+        .collect()
+}
+
+/// Fisher-Yates shuffle driven by a xorshift generator; good enough to vary menus, and reproducible per seed.
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed | 1;
+    for i in (1..items.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        items.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+}
+
+/// Parses a time limit such as `45`, `45m`, `1h` or `1h30m` into minutes.
+pub fn parse_max_time(text: &str) -> Result<u32, String> {
+    let error = || format!("Invalid time '{}': use minutes like 45m or hours like 1h30m.", text);
+    let text = text.trim().to_lowercase();
+    let (hours, minutes) = match text.split_once('h') {
+        Some((hours, minutes)) => (hours.trim().parse::<u32>().map_err(|_| error())?, minutes),
+        None => (0, text.as_str()),
+    };
+    let minutes = minutes.trim().trim_end_matches("min").trim_end_matches('m').trim();
+    let minutes = if minutes.is_empty() { 0 } else { minutes.parse::<u32>().map_err(|_| error())? };
+    Ok(hours * 60 + minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap()
+    }
+
+    fn entry(day: u32, recipe: &str) -> PlanEntry {
+        PlanEntry { date: date(day), recipe: recipe.to_string(), title: None, source_url: String::new() }
+    }
+
+    #[test]
+    fn test_meal_plan_add_keeps_date_order() {
+        let mut plan = MealPlan::default();
+        plan.add(entry(5, "b.json"));
+        plan.add(entry(3, "a.json"));
+        plan.add(entry(5, "c.json"));
+        let recipes: Vec<&str> = plan.entries.iter().map(|e| e.recipe.as_str()).collect();
+        assert_eq!(recipes, vec!["a.json", "b.json", "c.json"]);
+    }
+
+    #[test]
+    fn test_meal_plan_remove() {
+        let mut plan = MealPlan::default();
+        plan.add(entry(3, "out/recipe_lasagne.json"));
+        plan.add(entry(3, "out/recipe_soep.json"));
+        assert_eq!(plan.remove(date(3), Some("recipe_soep.json")), 1);
+        assert_eq!(plan.remove(date(3), None), 1);
+        assert!(plan.entries.is_empty());
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        // 2024-06-05 is a Wednesday.
+        assert_eq!(range_bounds("week", date(5)), Ok((date(3), date(9))));
+        assert_eq!(range_bounds("next-week", date(5)), Ok((date(10), date(16))));
+        assert_eq!(range_bounds("month", date(5)), Ok((date(1), date(30))));
+        assert_eq!(range_bounds("2024-06-07", date(5)), Ok((date(7), date(7))));
+        assert!(range_bounds("someday", date(5)).is_err());
+    }
+
+    fn saved(title: &str, tags: &[&str], total_time: Option<u32>) -> (String, Recipe) {
+        let recipe = Recipe {
+            title: Some(title.to_string()),
+            source_url: format!("https://15gram.be/recepten/{}", title),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            total_time,
+            ..Default::default()
+        };
+        (format!("recipe_{}.json", title), recipe)
+    }
+
+    fn options(days: u32) -> GenerateOptions {
+        GenerateOptions { start: date(10), days, tags: Vec::new(), max_minutes: None, no_repeat_weeks: 2, seed: 7 }
+    }
+
+    #[test]
+    fn test_generate_menu_applies_constraints() {
+        let library = [
+            saved("soep", &["weeknight"], Some(30)),
+            saved("stoofvlees", &["weeknight"], Some(180)),
+            saved("pasta", &[], Some(20)),
+        ];
+        let menu = generate_menu(
+            &MealPlan::default(),
+            &library,
+            &GenerateOptions { tags: vec!["weeknight".to_string()], max_minutes: Some(45), ..options(7) },
+        );
+        assert_eq!(menu.len(), 1);
+        assert_eq!(menu[0].0, date(10));
+        assert_eq!(menu[0].1.1.title.as_deref(), Some("soep"));
+    }
+
+    #[test]
+    fn test_generate_menu_skips_recent_repeats() {
+        let library = [saved("soep", &[], None), saved("pasta", &[], None)];
+        let mut plan = MealPlan::default();
+        plan.add(entry(3, "recipe_soep.json"));
+        let menu = generate_menu(&plan, &library, &options(2));
+        assert_eq!(menu.len(), 1);
+        assert_eq!(menu[0].1.0, "recipe_pasta.json");
+    }
+
+    #[test]
+    fn test_parse_max_time() {
+        assert_eq!(parse_max_time("45m"), Ok(45));
+        assert_eq!(parse_max_time("45"), Ok(45));
+        assert_eq!(parse_max_time("1h30m"), Ok(90));
+        assert_eq!(parse_max_time("2h"), Ok(120));
+        assert!(parse_max_time("soon").is_err());
+    }
+}
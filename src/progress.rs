@@ -0,0 +1,107 @@
+//! Progress of batch scrapes (`crawl`, `find --scrape`): a progress bar on stderr showing the URL being scraped,
+//! failures printed above it as they happen, and a summary table at the end. The bar is hidden when stderr is not a
+//! terminal, and with `--quiet`.
+
+use crate::library;
+use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A batch of URLs being scraped.
+pub struct Batch {
+    bar: ProgressBar,
+    started: Instant,
+    summary: BatchSummary,
+}
+
+/// The outcome of a batch.
+#[derive(Debug, Default)]
+pub struct BatchSummary {
+    pub ok: usize,
+    /// The URLs that failed, with their error.
+    pub failed: Vec<(String, String)>,
+    /// URLs left out, e.g. because they were scraped before.
+    pub skipped: usize,
+    pub elapsed: Duration,
+}
+
+impl Batch {
+    /// Starts the progress bar for `total` URLs, `skipped` more having been left out up front.
+    pub fn new(total: usize, skipped: usize) -> Self {
+        let bar = if crate::is_quiet() { ProgressBar::hidden() } else { ProgressBar::new(total as u64) };
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} [{elapsed_precise}] {bar:30} {pos}/{len} {wide_msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_bar()),
+        );
+        bar.enable_steady_tick(Duration::from_millis(120));
+        Batch { bar, started: Instant::now(), summary: BatchSummary { skipped, ..Default::default() } }
+    }
+
+    /// Shows the URL being scraped.
+    pub fn start(&self, url: &str) {
+        self.bar.set_message(url.to_string());
+    }
+
+    /// Prints a line to stdout above the bar.
+    pub fn println(&self, line: String) {
+        match self.bar.is_hidden() {
+            true => println!("{}", line),
+            false => self.bar.println(line),
+        }
+    }
+
+    /// Counts the outcome of the URL, printing a failure above the bar (on stderr, even with `--quiet`).
+    pub fn record(&mut self, url: &str, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.summary.ok += 1,
+            Err(error) => {
+                match self.bar.is_hidden() {
+                    true => eprintln!("FAILED {}: {}", url, error),
+                    false => self.bar.println(format!("FAILED {}: {}", url, error)),
+                }
+                self.summary.failed.push((url.to_string(), error));
+            }
+        }
+        self.bar.inc(1);
+    }
+
+    /// Removes the bar and returns the outcome.
+    pub fn finish(mut self) -> BatchSummary {
+        self.bar.finish_and_clear();
+        self.summary.elapsed = self.started.elapsed();
+        self.summary
+    }
+}
+
+impl fmt::Display for BatchSummary {
+    /// A one-row table of the counts and the total time, followed by the failed URLs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = [self.ok.to_string(), self.failed.len().to_string(), self.skipped.to_string(), HumanDuration(self.elapsed).to_string()];
+        write!(f, "{}", library::format_table(&["OK", "FAILED", "SKIPPED", "TIME"], &[row]))?;
+        for (url, error) in &self.failed {
+            write!(f, "\nFailed: {}\n  {}", url, error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_summary() {
+        let mut batch = Batch::new(2, 5);
+        batch.start("https://15gram.be/recepten/soep");
+        batch.record("https://15gram.be/recepten/soep", Ok(()));
+        batch.record("https://15gram.be/recepten/taart", Err("HTTP status 500 Internal Server Error".to_string()));
+        let summary = batch.finish();
+        assert_eq!((summary.ok, summary.failed.len(), summary.skipped), (1, 1, 5));
+
+        let summary = BatchSummary { elapsed: Duration::from_secs(75), ..summary };
+        assert_eq!(
+            summary.to_string(),
+            "OK  FAILED  SKIPPED  TIME\n1   1       5        75 seconds\n\nFailed: https://15gram.be/recepten/taart\n  HTTP status 500 Internal Server Error"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Pushing scraped recipes straight into other recipe managers with `--push`.
+
+pub mod grocy;
+pub mod home_assistant;
+pub mod mealie;
+pub mod nextcloud;
+pub mod notion;
+pub mod tandoor;
+pub mod webhook;
+
+use crate::Recipe;
+use clap::ValueEnum;
+
+/// The recipe managers a scraped recipe can be pushed to.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushTarget {
+    /// A Mealie instance, via its create-from-JSON endpoint.
+    Mealie,
+    /// A Tandoor Recipes instance, with steps and their linked ingredients.
+    Tandoor,
+    /// A Grocy recipe with the ingredients and steps in its description.
+    Grocy,
+    /// The recipe's merged ingredient list, added to a Grocy shopping list.
+    GrocyShopping,
+    /// The Nextcloud Cookbook folder, uploaded over WebDAV.
+    Nextcloud,
+    /// A page in a Notion database.
+    Notion,
+}
+
+/// Push targets and their connection settings, given on the command line.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct PushOptions {
+    /// Push the scraped recipe to these recipe managers (comma-separated).
+    #[arg(long = "push", value_enum, value_delimiter = ',')]
+    pub targets: Vec<PushTarget>,
+
+    /// Base URL of the Mealie instance, e.g. `https://mealie.example.com`.
+    #[arg(long, env = "FOODSCRAPER_MEALIE_URL")]
+    pub mealie_url: Option<String>,
+
+    /// Mealie API token.
+    #[arg(long, env = "FOODSCRAPER_MEALIE_TOKEN", hide_env_values = true)]
+    pub mealie_token: Option<String>,
+
+    /// Base URL of the Tandoor instance.
+    #[arg(long, env = "FOODSCRAPER_TANDOOR_URL")]
+    pub tandoor_url: Option<String>,
+
+    /// Tandoor API token.
+    #[arg(long, env = "FOODSCRAPER_TANDOOR_TOKEN", hide_env_values = true)]
+    pub tandoor_token: Option<String>,
+
+    /// Base URL of the Grocy instance.
+    #[arg(long, env = "FOODSCRAPER_GROCY_URL")]
+    pub grocy_url: Option<String>,
+
+    /// Grocy API key.
+    #[arg(long, env = "FOODSCRAPER_GROCY_API_KEY", hide_env_values = true)]
+    pub grocy_api_key: Option<String>,
+
+    /// The Grocy shopping list that `--push grocy-shopping` adds to.
+    #[arg(long, default_value_t = 1)]
+    pub grocy_shopping_list: i64,
+
+    /// Base URL of the Nextcloud server.
+    #[arg(long, env = "FOODSCRAPER_NEXTCLOUD_URL")]
+    pub nextcloud_url: Option<String>,
+
+    /// Nextcloud user name.
+    #[arg(long, env = "FOODSCRAPER_NEXTCLOUD_USER")]
+    pub nextcloud_user: Option<String>,
+
+    /// Nextcloud password; an app password is recommended.
+    #[arg(long, env = "FOODSCRAPER_NEXTCLOUD_PASSWORD", hide_env_values = true)]
+    pub nextcloud_password: Option<String>,
+
+    /// The Cookbook folder in the user's files.
+    #[arg(long, default_value = "Recipes")]
+    pub nextcloud_folder: String,
+
+    /// Notion integration token.
+    #[arg(long, env = "FOODSCRAPER_NOTION_TOKEN", hide_env_values = true)]
+    pub notion_token: Option<String>,
+
+    /// The id of the Notion database recipe pages are created in.
+    #[arg(long, env = "FOODSCRAPER_NOTION_DATABASE")]
+    pub notion_database: Option<String>,
+
+    /// POST the recipe JSON to these URLs after each successful scrape.
+    #[arg(long = "webhook", value_name = "URL")]
+    pub webhooks: Vec<String>,
+
+    /// Sign webhook requests with an HMAC-SHA256 of the body in the `X-FoodScraper-Signature` header.
+    #[arg(long, env = "FOODSCRAPER_WEBHOOK_SECRET", hide_env_values = true)]
+    pub webhook_secret: Option<String>,
+}
+
+/// Pushes the recipe to the target and returns a short description of what was created.
+///
+/// `output_folder` is where local files referenced by the recipe, such as downloaded images, are read from.
+pub async fn push_recipe(
+    client: &reqwest::Client,
+    recipe: &Recipe,
+    target: PushTarget,
+    options: &PushOptions,
+    output_folder: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match target {
+        PushTarget::Mealie => {
+            let url = options.mealie_url.as_deref().ok_or("--push mealie requires --mealie-url.")?;
+            let token = options.mealie_token.as_deref().ok_or("--push mealie requires --mealie-token.")?;
+            let slug = mealie::create_recipe(client, url, token, recipe).await?;
+            Ok(format!("Mealie recipe '{}'", slug))
+        }
+        PushTarget::Tandoor => {
+            let url = options.tandoor_url.as_deref().ok_or("--push tandoor requires --tandoor-url.")?;
+            let token = options.tandoor_token.as_deref().ok_or("--push tandoor requires --tandoor-token.")?;
+            let id = tandoor::create_recipe(client, url, token, recipe).await?;
+            Ok(format!("Tandoor recipe {}", id))
+        }
+        PushTarget::Grocy | PushTarget::GrocyShopping => {
+            let url = options.grocy_url.as_deref().ok_or("--push grocy requires --grocy-url.")?;
+            let api_key = options.grocy_api_key.as_deref().ok_or("--push grocy requires --grocy-api-key.")?;
+            if target == PushTarget::Grocy {
+                let id = grocy::create_recipe(client, url, api_key, recipe).await?;
+                return Ok(format!("Grocy recipe {}", id));
+            }
+            let lines = recipe.ingredients.iter().flatten().map(String::as_str);
+            let items: Vec<_> = crate::shopping::StoreLayout::load()?.shopping_list(lines).into_iter().flat_map(|(_, items)| items).collect();
+            let added = grocy::add_to_shopping_list(client, url, api_key, options.grocy_shopping_list, &items).await?;
+            Ok(format!("{} items on Grocy shopping list {}", added, options.grocy_shopping_list))
+        }
+        PushTarget::Nextcloud => {
+            let account = nextcloud::NextcloudAccount {
+                url: options.nextcloud_url.as_deref().ok_or("--push nextcloud requires --nextcloud-url.")?,
+                user: options.nextcloud_user.as_deref().ok_or("--push nextcloud requires --nextcloud-user.")?,
+                password: options.nextcloud_password.as_deref().ok_or("--push nextcloud requires --nextcloud-password.")?,
+                folder: &options.nextcloud_folder,
+            };
+            let url = nextcloud::upload_recipe(client, &account, recipe, output_folder).await?;
+            Ok(format!("Nextcloud Cookbook folder {}", url))
+        }
+        PushTarget::Notion => {
+            let token = options.notion_token.as_deref().ok_or("--push notion requires --notion-token.")?;
+            let database = options.notion_database.as_deref().ok_or("--push notion requires --notion-database.")?;
+            let url = notion::create_page(client, notion::NOTION_API, token, database, recipe).await?;
+            Ok(format!("Notion page {}", url))
+        }
+    }
+}
+
+/// Joins an API path onto a base URL given with or without a trailing slash.
+pub fn api_url(base: &str, path: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), path.trim_start_matches('/'))
+}
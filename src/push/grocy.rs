@@ -0,0 +1,102 @@
+//! Creating recipes and filling shopping lists in Grocy through its REST API.
+
+use crate::Recipe;
+use crate::ingredients::ShoppingItem;
+use crate::push::api_url;
+use crate::render::escape_html as escape;
+use serde_json::{Value, json};
+
+/// Creates the recipe, with its ingredients and steps in the description, and returns its Grocy id.
+pub async fn create_recipe(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+    recipe: &Recipe,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let body = json!({
+        "name": recipe.title.as_deref().unwrap_or("Recipe"),
+        "description": description_html(recipe),
+    });
+    create_object(client, base_url, api_key, "recipes", &body).await
+}
+
+/// Adds every item as a free-text entry to the Grocy shopping list and returns how many were added.
+pub async fn add_to_shopping_list(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+    shopping_list_id: i64,
+    items: &[ShoppingItem],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    for item in items {
+        let body = json!({ "shopping_list_id": shopping_list_id, "amount": 1, "note": item.to_string() });
+        create_object(client, base_url, api_key, "shopping_list", &body).await?;
+    }
+    Ok(items.len())
+}
+
+async fn create_object(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+    entity: &str,
+    body: &Value,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let created: Value = client
+        .post(api_url(base_url, &format!("/api/objects/{}", entity)))
+        .header("GROCY-API-KEY", api_key)
+        .json(body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    created
+        .get("created_object_id")
+        .and_then(|id| id.as_i64().or_else(|| id.as_str().and_then(|id| id.parse().ok())))
+        .ok_or_else(|| format!("Grocy did not return an id for the new {} entry.", entity).into())
+}
+
+/// The recipe description as the HTML Grocy shows: the description, ingredient list, steps and source link.
+pub fn description_html(recipe: &Recipe) -> String {
+    let mut html = String::new();
+    if let Some(description) = &recipe.description {
+        html.push_str(&format!("<p>{}</p>", escape(description)));
+    }
+    if let Some(ingredients) = &recipe.ingredients {
+        html.push_str("<ul>");
+        for ingredient in ingredients {
+            html.push_str(&format!("<li>{}</li>", escape(ingredient)));
+        }
+        html.push_str("</ul>");
+    }
+    if let Some(steps) = &recipe.steps {
+        html.push_str("<ol>");
+        for step in steps {
+            html.push_str(&format!("<li>{}</li>", escape(step)));
+        }
+        html.push_str("</ol>");
+    }
+    html.push_str(&format!("<p><a href=\"{0}\">{0}</a></p>", escape(&recipe.source_url)));
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_description_html() {
+        let recipe = Recipe {
+            ingredients: Some(vec!["1 ui".to_string()]),
+            steps: Some(vec!["Bak <goudbruin> & zacht.".to_string()]),
+            source_url: "https://15gram.be/recepten/soep".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            description_html(&recipe),
+            "<ul><li>1 ui</li></ul><ol><li>Bak &lt;goudbruin&gt; &amp; zacht.</li></ol>\
+             <p><a href=\"https://15gram.be/recepten/soep\">https://15gram.be/recepten/soep</a></p>"
+        );
+    }
+}
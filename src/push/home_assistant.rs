@@ -0,0 +1,77 @@
+//! Adding shopping-list items to a Home Assistant to-do list.
+
+use crate::ingredients::ShoppingItem;
+use crate::push::api_url;
+use serde_json::json;
+
+/// Home Assistant connection settings for `plan shopping --send home-assistant`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct HomeAssistantOptions {
+    /// Base URL of Home Assistant, e.g. `http://homeassistant.local:8123`.
+    #[arg(long, env = "FOODSCRAPER_HOME_ASSISTANT_URL")]
+    pub home_assistant_url: Option<String>,
+
+    /// A long-lived access token.
+    #[arg(long, env = "FOODSCRAPER_HOME_ASSISTANT_TOKEN", hide_env_values = true)]
+    pub home_assistant_token: Option<String>,
+
+    /// The to-do list entity the items are added to.
+    #[arg(long, default_value = "todo.shopping_list")]
+    pub home_assistant_entity: String,
+}
+
+/// Adds every item to the to-do list with the `todo.add_item` service and returns how many were added.
+pub async fn add_items(
+    client: &reqwest::Client,
+    options: &HomeAssistantOptions,
+    items: &[ShoppingItem],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let base_url = options.home_assistant_url.as_deref().ok_or("Sending to Home Assistant requires --home-assistant-url.")?;
+    let token = options.home_assistant_token.as_deref().ok_or("Sending to Home Assistant requires --home-assistant-token.")?;
+    for item in items {
+        client
+            .post(api_url(base_url, "/api/services/todo/add_item"))
+            .bearer_auth(token)
+            .json(&json!({ "entity_id": options.home_assistant_entity, "item": item.to_string() }))
+            .send()
+            .await?
+            .error_for_status()?;
+    }
+    Ok(items.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderMap;
+    use axum::{Json, Router, routing::post};
+    use serde_json::Value;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_add_items_calls_todo_service() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let recorded = received.clone();
+        let app = Router::new().route(
+            "/api/services/todo/add_item",
+            post(move |headers: HeaderMap, Json(body): Json<Value>| async move {
+                assert_eq!(headers["authorization"], "Bearer token");
+                recorded.lock().unwrap().push(body);
+                Json(Value::Array(Vec::new()))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let options = HomeAssistantOptions {
+            home_assistant_url: Some(format!("http://{}", listener.local_addr().unwrap())),
+            home_assistant_token: Some("token".to_string()),
+            home_assistant_entity: "todo.boodschappen".to_string(),
+        };
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let items = [ShoppingItem { quantity: Some(500.0), unit: Some("g".to_string()), name: "gehakt".to_string() }];
+        assert_eq!(add_items(&reqwest::Client::new(), &options, &items).await.unwrap(), 1);
+        let received = received.lock().unwrap();
+        assert_eq!(received[0]["entity_id"], "todo.boodschappen");
+        assert_eq!(received[0]["item"], "500 g gehakt");
+    }
+}
@@ -0,0 +1,52 @@
+//! Creating recipes in Mealie through its REST API.
+
+use crate::Recipe;
+use crate::push::api_url;
+use crate::render::schema_org;
+use serde_json::json;
+
+/// Creates the recipe from its schema.org JSON and returns the slug Mealie assigned to it.
+pub async fn create_recipe(
+    client: &reqwest::Client,
+    base_url: &str,
+    token: &str,
+    recipe: &Recipe,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let body = json!({ "data": schema_org(recipe).to_string(), "includeTags": true });
+    let response = client
+        .post(api_url(base_url, "/api/recipes/create/html-or-json"))
+        .bearer_auth(token)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.json::<String>().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::{Json, Router, routing::post};
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_create_recipe_posts_schema_org_json() {
+        let app = Router::new().route(
+            "/api/recipes/create/html-or-json",
+            post(|headers: HeaderMap, Json(body): Json<Value>| async move {
+                assert_eq!(headers["authorization"], "Bearer secret");
+                let data: Value = serde_json::from_str(body["data"].as_str().unwrap()).unwrap();
+                assert_eq!(data["name"], "Soep");
+                (StatusCode::CREATED, Json("soep"))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let recipe = Recipe { title: Some("Soep".to_string()), ..Default::default() };
+        let slug = create_recipe(&reqwest::Client::new(), &base_url, "secret", &recipe).await.unwrap();
+        assert_eq!(slug, "soep");
+    }
+}
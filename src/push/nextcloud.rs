@@ -0,0 +1,134 @@
+//! Uploading recipes into the Nextcloud Cookbook folder over WebDAV.
+//!
+//! Cookbook keeps one folder per recipe with a schema.org `recipe.json` and a `full.jpg` image, so
+//! uploading that folder is enough for the recipe to show up in the app.
+
+use crate::Recipe;
+use crate::render::schema_org;
+use reqwest::{Method, StatusCode};
+use std::path::Path;
+use url::Url;
+
+/// Nextcloud account and the Cookbook folder to upload into.
+#[derive(Debug, Clone)]
+pub struct NextcloudAccount<'a> {
+    pub url: &'a str,
+    pub user: &'a str,
+    pub password: &'a str,
+    /// The Cookbook folder, relative to the user's files.
+    pub folder: &'a str,
+}
+
+/// Uploads the recipe folder and returns its WebDAV URL.
+///
+/// Local images (from `--download-images`) are read from the output folder; remote ones are downloaded first.
+pub async fn upload_recipe(
+    client: &reqwest::Client,
+    account: &NextcloudAccount<'_>,
+    recipe: &Recipe,
+    output_folder: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let name = folder_name(recipe.title.as_deref().unwrap_or("Recipe"));
+    let mut folder_url = Url::parse(account.url)?;
+    folder_url
+        .path_segments_mut()
+        .map_err(|_| "The Nextcloud URL cannot be a base URL.")?
+        .pop_if_empty()
+        .extend(["remote.php", "dav", "files", account.user])
+        .extend(account.folder.split('/').filter(|segment| !segment.is_empty()));
+    make_collection(client, account, &folder_url).await?;
+    folder_url.path_segments_mut().unwrap().push(&name);
+    make_collection(client, account, &folder_url).await?;
+
+    let mut json = schema_org(recipe);
+    if recipe.image_link.is_some() {
+        json["image"] = "full.jpg".into();
+    }
+    put_file(client, account, &folder_url, "recipe.json", serde_json::to_vec_pretty(&json)?).await?;
+
+    if let Some(image_link) = &recipe.image_link {
+        let image = match Url::parse(image_link) {
+            Ok(remote) => client.get(remote).send().await?.error_for_status()?.bytes().await?.to_vec(),
+            Err(_) => std::fs::read(Path::new(output_folder).join(image_link))?,
+        };
+        put_file(client, account, &folder_url, "full.jpg", image).await?;
+    }
+    Ok(folder_url.to_string())
+}
+
+/// Creates a WebDAV collection; an existing one (405 Method Not Allowed) is fine.
+async fn make_collection(client: &reqwest::Client, account: &NextcloudAccount<'_>, url: &Url) -> Result<(), Box<dyn std::error::Error>> {
+    let response = client
+        .request(Method::from_bytes(b"MKCOL")?, url.clone())
+        .basic_auth(account.user, Some(account.password))
+        .send()
+        .await?;
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+        response.error_for_status()?;
+    }
+    Ok(())
+}
+
+async fn put_file(
+    client: &reqwest::Client,
+    account: &NextcloudAccount<'_>,
+    folder_url: &Url,
+    file_name: &str,
+    content: Vec<u8>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut url = folder_url.clone();
+    url.path_segments_mut().unwrap().push(file_name);
+    client.put(url).basic_auth(account.user, Some(account.password)).body(content).send().await?.error_for_status()?;
+    Ok(())
+}
+
+/// A folder name for the recipe without characters Nextcloud does not allow in file names.
+pub fn folder_name(title: &str) -> String {
+    let name: String = title.chars().filter(|c| !matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')).collect();
+    let name = name.trim().trim_matches('.').to_string();
+    if name.is_empty() { "Recipe".to_string() } else { name }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::http::{Method as HttpMethod, StatusCode as HttpStatus, Uri};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_folder_name() {
+        assert_eq!(folder_name("Kip/frieten: met mayo?"), "Kipfrieten met mayo");
+        assert_eq!(folder_name(" .. "), "Recipe");
+    }
+
+    #[tokio::test]
+    async fn test_upload_recipe_creates_folder_and_files() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let app = Router::new().fallback(move |method: HttpMethod, uri: Uri| {
+            let recorded = recorded.clone();
+            async move {
+                let exists = method.as_str() == "MKCOL" && uri.path().ends_with("/Recipes");
+                recorded.lock().unwrap().push(format!("{} {}", method, uri.path()));
+                if exists { HttpStatus::METHOD_NOT_ALLOWED } else { HttpStatus::CREATED }
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let account = NextcloudAccount { url: &base_url, user: "max", password: "app-password", folder: "Recipes" };
+        let recipe = Recipe { title: Some("Soep".to_string()), ..Default::default() };
+        let url = upload_recipe(&reqwest::Client::new(), &account, &recipe, ".").await.unwrap();
+        assert!(url.ends_with("/remote.php/dav/files/max/Recipes/Soep"));
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                "MKCOL /remote.php/dav/files/max/Recipes",
+                "MKCOL /remote.php/dav/files/max/Recipes/Soep",
+                "PUT /remote.php/dav/files/max/Recipes/Soep/recipe.json",
+            ]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Creating recipe pages in a Notion database through the Notion API.
+//!
+//! The database is expected to have a `Name` title property, a `Tags` multi-select, a `Time` number
+//! (minutes) and a `Source` URL property. Ingredients become a bulleted list and steps a numbered list.
+
+use crate::Recipe;
+use crate::push::api_url;
+use serde_json::{Value, json};
+
+/// The public Notion API.
+pub const NOTION_API: &str = "https://api.notion.com";
+const NOTION_VERSION: &str = "2022-06-28";
+/// Notion accepts at most this many child blocks per request.
+const MAX_BLOCKS_PER_REQUEST: usize = 100;
+/// Notion limits a single rich-text content string to this many characters.
+const MAX_TEXT_LENGTH: usize = 2000;
+
+/// Creates a page for the recipe in the database and returns the page URL.
+pub async fn create_page(
+    client: &reqwest::Client,
+    api_base: &str,
+    token: &str,
+    database_id: &str,
+    recipe: &Recipe,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut blocks = content_blocks(recipe);
+    let rest = blocks.split_off(blocks.len().min(MAX_BLOCKS_PER_REQUEST));
+    let body = json!({
+        "parent": {"database_id": database_id},
+        "properties": page_properties(recipe),
+        "children": blocks,
+    });
+    let page: Value = send(client.post(api_url(api_base, "/v1/pages")), token, &body).await?;
+    let page_id = page.get("id").and_then(Value::as_str).ok_or("Notion did not return a page id.")?;
+
+    for chunk in rest.chunks(MAX_BLOCKS_PER_REQUEST) {
+        let url = api_url(api_base, &format!("/v1/blocks/{}/children", page_id));
+        send(client.patch(url), token, &json!({ "children": chunk })).await?;
+    }
+    Ok(page.get("url").and_then(Value::as_str).unwrap_or(page_id).to_string())
+}
+
+async fn send(request: reqwest::RequestBuilder, token: &str, body: &Value) -> Result<Value, Box<dyn std::error::Error>> {
+    let response = request.bearer_auth(token).header("Notion-Version", NOTION_VERSION).json(body).send().await?;
+    let status = response.status();
+    let value: Value = response.json().await?;
+    if !status.is_success() {
+        let message = value.get("message").and_then(Value::as_str).unwrap_or_default();
+        return Err(format!("Notion API error ({}): {}", status, message).into());
+    }
+    Ok(value)
+}
+
+/// The database properties for the recipe page.
+pub fn page_properties(recipe: &Recipe) -> Value {
+    let mut properties = json!({
+        "Name": {"title": rich_text(recipe.title.as_deref().unwrap_or("Recipe"))},
+        "Tags": {"multi_select": recipe.tags.iter().map(|tag| json!({"name": tag.replace(',', " ")})).collect::<Vec<_>>()},
+        "Source": {"url": recipe.source_url},
+    });
+    if let Some(minutes) = crate::plan::recipe_minutes(recipe) {
+        properties["Time"] = json!({ "number": minutes });
+    }
+    properties
+}
+
+/// The page content: description, a bulleted ingredient list and numbered steps.
+pub fn content_blocks(recipe: &Recipe) -> Vec<Value> {
+    let mut blocks = Vec::new();
+    if let Some(description) = &recipe.description {
+        blocks.push(block("paragraph", description));
+    }
+    if let Some(ingredients) = &recipe.ingredients {
+        blocks.push(block("heading_2", "Ingredients"));
+        blocks.extend(ingredients.iter().map(|ingredient| block("bulleted_list_item", ingredient)));
+    }
+    if let Some(steps) = &recipe.steps {
+        blocks.push(block("heading_2", "Steps"));
+        blocks.extend(steps.iter().map(|step| block("numbered_list_item", step)));
+    }
+    blocks
+}
+
+fn block(kind: &str, text: &str) -> Value {
+    json!({ "object": "block", "type": kind, kind: { "rich_text": rich_text(text) } })
+}
+
+fn rich_text(text: &str) -> Value {
+    json!([{ "type": "text", "text": { "content": text.chars().take(MAX_TEXT_LENGTH).collect::<String>() } }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe() -> Recipe {
+        Recipe {
+            title: Some("Soep".to_string()),
+            ingredients: Some(vec!["1 ui".to_string(), "2 wortelen".to_string()]),
+            steps: Some(vec!["Snij.".to_string(), "Kook.".to_string()]),
+            total_time: Some(30),
+            tags: vec!["winter".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_page_properties() {
+        let properties = page_properties(&recipe());
+        assert_eq!(properties["Name"]["title"][0]["text"]["content"], "Soep");
+        assert_eq!(properties["Tags"]["multi_select"][0]["name"], "winter");
+        assert_eq!(properties["Time"]["number"], 30);
+    }
+
+    #[test]
+    fn test_content_blocks() {
+        let blocks = content_blocks(&recipe());
+        let kinds: Vec<&str> = blocks.iter().map(|block| block["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            kinds,
+            vec!["heading_2", "bulleted_list_item", "bulleted_list_item", "heading_2", "numbered_list_item", "numbered_list_item"]
+        );
+        assert_eq!(blocks[5]["numbered_list_item"]["rich_text"][0]["text"]["content"], "Kook.");
+    }
+}
@@ -0,0 +1,83 @@
+//! Creating recipes in Tandoor Recipes through its REST API.
+
+use crate::Recipe;
+use crate::ingredients::parse_ingredient;
+use crate::push::api_url;
+use serde_json::{Value, json};
+
+/// Creates the recipe and returns the id Tandoor assigned to it.
+pub async fn create_recipe(
+    client: &reqwest::Client,
+    base_url: &str,
+    token: &str,
+    recipe: &Recipe,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let created: Value = client
+        .post(api_url(base_url, "/api/recipe/"))
+        .bearer_auth(token)
+        .json(&recipe_body(recipe))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    created.get("id").and_then(Value::as_i64).ok_or_else(|| "Tandoor did not return a recipe id.".into())
+}
+
+/// The Tandoor recipe body: every ingredient is linked to the first step that mentions it, or to the first step.
+pub fn recipe_body(recipe: &Recipe) -> Value {
+    let steps = recipe.steps.clone().unwrap_or_default();
+    let steps = if steps.is_empty() { vec![String::new()] } else { steps };
+    let mut step_ingredients: Vec<Vec<Value>> = vec![Vec::new(); steps.len()];
+    for line in recipe.ingredients.iter().flatten() {
+        let parsed = parse_ingredient(line);
+        let (food, note) = parsed.name.split_once(',').unwrap_or((&parsed.name, ""));
+        let food = food.trim();
+        let step = steps.iter().position(|step| step.to_lowercase().contains(&food.to_lowercase())).unwrap_or(0);
+        step_ingredients[step].push(json!({
+            "amount": parsed.quantity.unwrap_or(0.0),
+            "unit": parsed.unit.map(|unit| json!({"name": unit})),
+            "food": {"name": food},
+            "note": note.trim(),
+            "original_text": line,
+        }));
+    }
+
+    json!({
+        "name": recipe.title.as_deref().unwrap_or("Recipe"),
+        "description": recipe.description.as_deref().unwrap_or_default().chars().take(512).collect::<String>(),
+        "source_url": recipe.source_url,
+        "working_time": recipe.total_time.unwrap_or(0),
+        "waiting_time": 0,
+        "servings": 1,
+        "keywords": recipe.tags.iter().map(|tag| json!({"name": tag})).collect::<Vec<_>>(),
+        "steps": steps
+            .into_iter()
+            .zip(step_ingredients)
+            .map(|(instruction, ingredients)| json!({"instruction": instruction, "ingredients": ingredients}))
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recipe_body_links_ingredients_to_steps() {
+        let recipe = Recipe {
+            title: Some("Stoofvlees".to_string()),
+            ingredients: Some(vec!["1 kg rundvlees".to_string(), "2 uien, gesnipperd".to_string(), "zout".to_string()]),
+            steps: Some(vec!["Snij de uien.".to_string(), "Bak het rundvlees aan.".to_string()]),
+            ..Default::default()
+        };
+        let body = recipe_body(&recipe);
+        let first = &body["steps"][0]["ingredients"];
+        let second = &body["steps"][1]["ingredients"];
+        assert_eq!(first[0]["food"]["name"], "uien");
+        assert_eq!(first[0]["note"], "gesnipperd");
+        assert_eq!(first[1]["food"]["name"], "zout");
+        assert_eq!(second[0]["amount"], 1000.0);
+        assert_eq!(second[0]["unit"]["name"], "g");
+    }
+}
@@ -0,0 +1,67 @@
+//! Notifying a webhook with the scraped recipe after each scrape.
+
+use crate::Recipe;
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use sha2::Sha256;
+
+/// Header carrying the `sha256=<hex>` HMAC of the request body when a secret is configured.
+pub const SIGNATURE_HEADER: &str = "X-FoodScraper-Signature";
+
+/// POSTs the recipe JSON to the webhook, signed with the secret if one is given.
+pub async fn notify(
+    client: &reqwest::Client,
+    url: &str,
+    secret: Option<&str>,
+    recipe: &Recipe,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let body = serde_json::to_vec(recipe)?;
+    let mut request = client.post(url).header(CONTENT_TYPE, "application/json");
+    if let Some(secret) = secret {
+        request = request.header(SIGNATURE_HEADER, signature(secret, &body));
+    }
+    request.body(body).send().await?.error_for_status()?;
+    Ok(())
+}
+
+/// The signature header value: `sha256=` followed by the hex HMAC-SHA256 of the body.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::{Router, routing::post};
+
+    #[test]
+    fn test_signature() {
+        // Test vector 2 from RFC 4231.
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notify_signs_body() {
+        let app = Router::new().route(
+            "/hook",
+            post(|headers: HeaderMap, body: Bytes| async move {
+                assert_eq!(headers[SIGNATURE_HEADER].to_str().unwrap(), signature("secret", &body));
+                assert_eq!(serde_json::from_slice::<Recipe>(&body).unwrap().title.as_deref(), Some("Soep"));
+                StatusCode::NO_CONTENT
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let recipe = Recipe { title: Some("Soep".to_string()), ..Default::default() };
+        notify(&reqwest::Client::new(), &url, Some("secret"), &recipe).await.unwrap();
+    }
+}
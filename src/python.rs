@@ -0,0 +1,49 @@
+//! The `foodscraper` Python module.
+//!
+//! ```python
+//! import foodscraper
+//! recipe = foodscraper.scrape_url("https://15gram.be/recepten/...", user_agent="MyApp (me@example.com)")
+//! recipe = foodscraper.extract_from_html(html, "https://15gram.be/recepten/...")
+//! ```
+//!
+//! Recipes are returned as dicts with the same keys as the saved recipe JSON.
+
+use crate::Recipe;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Fetches the page and extracts the recipe from it, sending `user_agent` as the `User-Agent` header if given.
+#[pyfunction]
+#[pyo3(signature = (url, user_agent = None))]
+fn scrape_url(py: Python<'_>, url: &str, user_agent: Option<&str>) -> PyResult<PyObject> {
+    let recipe = py
+        .allow_threads(|| {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().map_err(|e| e.to_string())?;
+            runtime.block_on(async {
+                crate::scrape_page(&crate::http_client(user_agent), url).await.map(|(recipe, _)| recipe).map_err(|e| e.to_string())
+            })
+        })
+        .map_err(PyValueError::new_err)?;
+    to_dict(py, &recipe)
+}
+
+/// Extracts the recipe from HTML fetched elsewhere; `url` selects the site's selectors.
+#[pyfunction]
+#[pyo3(name = "extract_from_html")]
+fn py_extract_from_html(py: Python<'_>, html: &str, url: &str) -> PyResult<PyObject> {
+    let recipe = crate::extract_from_html(html, url).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    to_dict(py, &recipe)
+}
+
+/// Converts the recipe to a dict through its JSON form, so Python sees exactly the saved fields.
+fn to_dict(py: Python<'_>, recipe: &Recipe) -> PyResult<PyObject> {
+    let json = serde_json::to_string(recipe).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+#[pymodule]
+fn foodscraper(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(scrape_url, module)?)?;
+    module.add_function(wrap_pyfunction!(py_extract_from_html, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Re-scraping of saved recipes.
+
+use crate::fetch::{self, FetchOutcome};
+use crate::{Recipe, extract_recipe, selectors_for_url};
+use reqwest::StatusCode;
+use scraper::Html;
+use std::fmt;
+
+/// What happened when a saved recipe was re-scraped.
+#[derive(Debug)]
+pub enum RefreshStatus {
+    /// The page changed; holds the updated recipe to store.
+    Changed(Box<Recipe>),
+    /// The page was downloaded again but the extracted recipe is identical.
+    Unchanged,
+    /// The server answered `304 Not Modified` to the conditional request.
+    NotModified,
+    /// The page is gone (`404`/`410`).
+    Missing(StatusCode),
+    /// The page could not be fetched or extracted.
+    Failed(String),
+}
+
+impl RefreshStatus {
+    /// Short label used in the per-recipe report.
+    pub fn label(&self) -> String {
+        match self {
+            RefreshStatus::Changed(_) => "changed".to_string(),
+            RefreshStatus::Unchanged => "unchanged".to_string(),
+            RefreshStatus::NotModified => "not-modified".to_string(),
+            RefreshStatus::Missing(status) => format!("gone ({})", status.as_u16()),
+            RefreshStatus::Failed(error) => format!("failed: {}", error),
+        }
+    }
+}
+
+/// Counts of refresh outcomes, printed at the end of a run.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct RefreshSummary {
+    pub changed: usize,
+    pub unchanged: usize,
+    pub missing: usize,
+    pub failed: usize,
+}
+
+impl RefreshSummary {
+    /// Counts one outcome.
+    pub fn record(&mut self, status: &RefreshStatus) {
+        match status {
+            RefreshStatus::Changed(_) => self.changed += 1,
+            RefreshStatus::Unchanged | RefreshStatus::NotModified => self.unchanged += 1,
+            RefreshStatus::Missing(_) => self.missing += 1,
+            RefreshStatus::Failed(_) => self.failed += 1,
+        }
+    }
+}
+
+impl fmt::Display for RefreshSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Refresh finished: {} changed, {} unchanged, {} gone, {} failed.",
+            self.changed, self.unchanged, self.missing, self.failed
+        )
+    }
+}
+
+/// Re-fetches a saved recipe with a conditional request and extracts it again.
+///
+/// User metadata such as tags, and locally downloaded images, are carried over to the updated recipe.
+pub async fn refresh_recipe(client: &reqwest::Client, saved: &Recipe) -> Result<RefreshStatus, Box<dyn std::error::Error>> {
+    let (body, cache) = match fetch::fetch_page(client, &saved.source_url, saved.http_cache.as_ref()).await? {
+        FetchOutcome::Fetched(page) => (page.body, page.cache),
+        FetchOutcome::NotModified => return Ok(RefreshStatus::NotModified),
+        FetchOutcome::Missing(status) => return Ok(RefreshStatus::Missing(status)),
+    };
+
+    let selectors = selectors_for_url(&saved.source_url)?;
+    let mut updated = extract_recipe(&Html::parse_document(&body), &selectors, &saved.source_url);
+    updated.tags = saved.tags.clone();
+    updated.favorite = saved.favorite;
+    if saved.image_link.as_deref().is_some_and(|link| url::Url::parse(link).is_err()) {
+        updated.image_link = saved.image_link.clone();
+        updated.thumbnail = saved.thumbnail.clone();
+    }
+    updated.scraped_at = saved.scraped_at.clone();
+    updated.http_cache = saved.http_cache.clone();
+    let matched_selectors = std::mem::replace(&mut updated.matched_selectors, saved.matched_selectors.clone());
+    let confidence = std::mem::replace(&mut updated.confidence, saved.confidence.clone());
+    if updated == *saved {
+        return Ok(RefreshStatus::Unchanged);
+    }
+    updated.matched_selectors = matched_selectors;
+    updated.confidence = confidence;
+
+    updated.scraped_at = Some(chrono::Utc::now().to_rfc3339());
+    updated.http_cache = cache;
+    Ok(RefreshStatus::Changed(Box::new(updated)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_summary_counts() {
+        let mut summary = RefreshSummary::default();
+        summary.record(&RefreshStatus::Unchanged);
+        summary.record(&RefreshStatus::NotModified);
+        summary.record(&RefreshStatus::Missing(StatusCode::NOT_FOUND));
+        assert_eq!(summary.to_string(), "Refresh finished: 0 changed, 2 unchanged, 1 gone, 0 failed.");
+    }
+}
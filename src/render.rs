@@ -0,0 +1,319 @@
+//! Rendering recipes into the supported output formats.
+
+use crate::Recipe;
+use crate::config::CardConfig;
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+/// Formats a recipe can be written in.
+#[derive(ValueEnum, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecipeFormat {
+    /// The recipe JSON produced by the scraper.
+    Json,
+    /// A Markdown document with ingredient and step lists.
+    Markdown,
+    /// A standalone HTML page.
+    Html,
+    /// A PNG recipe card with the title, photo, ingredients and a QR code to the source, for sharing.
+    #[cfg(feature = "cards")]
+    Card,
+}
+
+impl RecipeFormat {
+    /// File extension used for this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            RecipeFormat::Json => "json",
+            RecipeFormat::Markdown => "md",
+            RecipeFormat::Html => "html",
+            #[cfg(feature = "cards")]
+            RecipeFormat::Card => "png",
+        }
+    }
+}
+
+/// Renders the recipe in the given format, with a QR code of the source URL in HTML pages and cards if `qr_code` is
+/// set (cards follow the `card` template otherwise). Cards rendered this way leave out the photo; see
+/// [`crate::card::render_card`].
+#[cfg_attr(not(feature = "cards"), allow(unused_variables))]
+pub fn render(recipe: &Recipe, format: RecipeFormat, qr_code: bool, card: &CardConfig) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    match format {
+        RecipeFormat::Json => Ok(serde_json::to_string_pretty(recipe)?.into_bytes()),
+        RecipeFormat::Markdown => Ok(render_markdown(recipe).into_bytes()),
+        RecipeFormat::Html => Ok(render_html(recipe, recipe.image_link.as_deref(), qr_code).into_bytes()),
+        #[cfg(feature = "cards")]
+        RecipeFormat::Card => {
+            let template = CardConfig { qr_code: card.qr_code || qr_code, ..card.clone() };
+            crate::card::render_card(recipe, None, &template)
+        }
+    }
+}
+
+/// Renders the recipe as Markdown.
+pub fn render_markdown(recipe: &Recipe) -> String {
+    let mut markdown = format!("# {}\n\n", recipe.title.as_deref().unwrap_or("Recipe"));
+    if let Some(image_link) = &recipe.image_link {
+        markdown.push_str(&format!("![{}]({})\n\n", recipe.title.as_deref().unwrap_or_default(), image_link));
+    }
+    if let Some(description) = &recipe.description {
+        markdown.push_str(&format!("{}\n\n", description));
+    }
+    if let Some(ingredients) = &recipe.ingredients {
+        markdown.push_str("## Ingredients\n\n");
+        for ingredient in ingredients {
+            markdown.push_str(&format!("- {}\n", ingredient));
+        }
+        markdown.push('\n');
+    }
+    if let Some(steps) = &recipe.steps {
+        markdown.push_str("## Steps\n\n");
+        for (number, step) in steps.iter().enumerate() {
+            markdown.push_str(&format!("{}. {}\n", number + 1, step));
+        }
+        markdown.push('\n');
+    }
+    if !recipe.tags.is_empty() {
+        markdown.push_str(&format!("Tags: {}\n\n", recipe.tags.join(", ")));
+    }
+    markdown.push_str(&format!("Source: <{}>\n", recipe.source_url));
+    markdown
+}
+
+/// Renders the recipe as a standalone HTML page, showing the image from `image_src` if given, and with `qr_code` a QR
+/// code linking to the source page so printouts lead back to it.
+pub fn render_html(recipe: &Recipe, image_src: Option<&str>, qr_code: bool) -> String {
+    let title = escape_html(recipe.title.as_deref().unwrap_or("Recipe"));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head><meta charset=\"utf-8\"><title>{}</title></head>\n<body>\n<h1>{}</h1>\n",
+        recipe.language.as_deref().unwrap_or("nl"),
+        title,
+        title
+    );
+    if let Some(image_src) = image_src {
+        html.push_str(&format!("<img src=\"{}\" alt=\"{}\" style=\"max-width: 100%\">\n", escape_html(image_src), title));
+    }
+    if let Some(description) = &recipe.description {
+        html.push_str(&format!("<p>{}</p>\n", escape_html(description)));
+    }
+    if let Some(ingredients) = &recipe.ingredients {
+        html.push_str("<h2>Ingredients</h2>\n<ul>\n");
+        for ingredient in ingredients {
+            html.push_str(&format!("<li>{}</li>\n", escape_html(ingredient)));
+        }
+        html.push_str("</ul>\n");
+    }
+    if let Some(steps) = &recipe.steps {
+        html.push_str("<h2>Steps</h2>\n<ol>\n");
+        for step in steps {
+            html.push_str(&format!("<li>{}</li>\n", escape_html(step)));
+        }
+        html.push_str("</ol>\n");
+    }
+    if !recipe.tags.is_empty() {
+        html.push_str(&format!("<p>Tags: {}</p>\n", escape_html(&recipe.tags.join(", "))));
+    }
+    if let Some(svg) = qr_code.then(|| qr_code_svg(&recipe.source_url)).flatten() {
+        html.push_str(&format!("<figure style=\"width: 160px\">{}<figcaption>Scan for the original recipe</figcaption></figure>\n", svg));
+    }
+    html.push_str(&format!("<p>Source: <a href=\"{0}\">{0}</a></p>\n</body>\n</html>\n", escape_html(&recipe.source_url)));
+    html
+}
+
+/// A QR code of the URL as an inline SVG element, or `None` for an empty or overlong URL.
+pub fn qr_code_svg(url: &str) -> Option<String> {
+    if url.is_empty() {
+        return None;
+    }
+    let code = qrcode::QrCode::new(url.as_bytes()).ok()?;
+    let svg = code.render::<qrcode::render::svg::Color>().min_dimensions(160, 160).build();
+    // Drop the XML declaration, which is not allowed inside HTML.
+    Some(svg.split_once("?>").map_or(svg.as_str(), |(_, element)| element).to_string())
+}
+
+/// Renders the recipe for reading in a terminal `width` columns wide: the title, the ingredients in a box and the
+/// steps numbered and wrapped. With `color`, headings and step numbers are styled with ANSI escapes.
+pub fn render_terminal(recipe: &Recipe, width: usize, color: bool) -> String {
+    let width = width.max(20);
+    let paint = |code: &str, text: &str| if color { format!("\x1b[{}m{}\x1b[0m", code, text) } else { text.to_string() };
+    let mut output = format!("{}\n", paint("1", recipe.title.as_deref().unwrap_or("(untitled)")));
+    let mut about: Vec<String> = crate::library::source_domain(&recipe.source_url).into_iter().collect();
+    about.extend(recipe.total_time.map(|minutes| format!("{} min", minutes)));
+    about.extend((!recipe.tags.is_empty()).then(|| recipe.tags.join(", ")));
+    if !about.is_empty() {
+        output.push_str(&format!("{}\n", paint("2", &about.join(" · "))));
+    }
+    if let Some(description) = &recipe.description {
+        output.push('\n');
+        for line in wrap(description, width) {
+            output.push_str(&format!("{}\n", line));
+        }
+    }
+
+    output.push('\n');
+    let ingredients = recipe.ingredients.as_deref().unwrap_or_default();
+    let lines: Vec<String> = ingredients
+        .iter()
+        .flat_map(|ingredient| {
+            let wrapped = wrap(ingredient, width - 6).into_iter().enumerate();
+            wrapped.map(|(index, line)| format!("{} {}", if index == 0 { "•" } else { " " }, line)).collect::<Vec<_>>()
+        })
+        .collect();
+    let heading = "─ Ingredients ";
+    let inner = lines.iter().map(|line| line.chars().count() + 2).max().unwrap_or(0).max(heading.chars().count() + 1).min(width - 2);
+    output.push_str(&format!("┌{}{}┐\n", paint("36", heading), "─".repeat(inner - heading.chars().count())));
+    if lines.is_empty() {
+        output.push_str(&format!("│ {:<pad$} │\n", "(none found)", pad = inner - 2));
+    }
+    for line in &lines {
+        output.push_str(&format!("│ {:<pad$} │\n", line, pad = inner - 2));
+    }
+    output.push_str(&format!("└{}┘\n", "─".repeat(inner)));
+
+    output.push_str(&format!("\n{}\n", paint("1", "Steps")));
+    let steps = recipe.steps.as_deref().unwrap_or_default();
+    let number_width = steps.len().to_string().len();
+    for (number, step) in steps.iter().enumerate() {
+        for (index, line) in wrap(step, width - number_width - 3).into_iter().enumerate() {
+            let prefix = if index == 0 { format!("{:>w$}.", number + 1, w = number_width) } else { " ".repeat(number_width + 1) };
+            output.push_str(&format!(" {} {}\n", paint("36", &prefix), line));
+        }
+    }
+    if steps.is_empty() {
+        output.push_str(" (none found)\n");
+    }
+    output
+}
+
+/// Breaks text into lines of at most `width` characters at spaces; longer words get a line of their own.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Escapes text for use in HTML content and attribute values.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Converts the recipe to a schema.org `Recipe` object, the format other recipe managers import.
+pub fn schema_org(recipe: &Recipe) -> Value {
+    let mut value = json!({
+        "@context": "https://schema.org",
+        "@type": "Recipe",
+        "name": recipe.title.as_deref().unwrap_or("Recipe"),
+        "url": recipe.source_url,
+        "recipeIngredient": recipe.ingredients.clone().unwrap_or_default(),
+        "recipeInstructions": recipe
+            .steps
+            .iter()
+            .flatten()
+            .map(|step| json!({"@type": "HowToStep", "text": step}))
+            .collect::<Vec<_>>(),
+        "keywords": recipe.tags.join(", "),
+    });
+    let optional = [
+        ("description", recipe.description.clone().map(Value::String)),
+        ("image", recipe.image_link.clone().map(Value::String)),
+        ("inLanguage", recipe.language.clone().map(Value::String)),
+        ("totalTime", recipe.total_time.map(|minutes| Value::String(format!("PT{}M", minutes)))),
+    ];
+    for (key, field) in optional {
+        if let Some(field) = field {
+            value[key] = field;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        let recipe = Recipe {
+            title: Some("Soep".to_string()),
+            ingredients: Some(vec!["1 ui".to_string()]),
+            steps: Some(vec!["Snij de ui.".to_string(), "Kook.".to_string()]),
+            source_url: "https://15gram.be/recepten/soep".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_markdown(&recipe),
+            "# Soep\n\n## Ingredients\n\n- 1 ui\n\n## Steps\n\n1. Snij de ui.\n2. Kook.\n\nSource: <https://15gram.be/recepten/soep>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_terminal() {
+        let recipe = Recipe {
+            title: Some("Soep".to_string()),
+            ingredients: Some(vec!["1 ui".to_string(), "1 l groentebouillon".to_string()]),
+            steps: Some(vec!["Snij de ui en stoof ze glazig in wat boter.".to_string(), "Kook.".to_string()]),
+            source_url: "https://15gram.be/recepten/soep".to_string(),
+            total_time: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_terminal(&recipe, 30, false),
+            "Soep\n15gram.be · 30 min\n\n\
+             ┌─ Ingredients ─────────┐\n│ • 1 ui                │\n│ • 1 l groentebouillon │\n└───────────────────────┘\n\n\
+             Steps\n 1. Snij de ui en stoof ze\n    glazig in wat boter.\n 2. Kook.\n"
+        );
+        assert!(render_terminal(&recipe, 30, true).starts_with("\x1b[1mSoep\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_schema_org() {
+        let recipe = Recipe {
+            title: Some("Soep".to_string()),
+            steps: Some(vec!["Kook.".to_string()]),
+            total_time: Some(30),
+            source_url: "https://15gram.be/recepten/soep".to_string(),
+            ..Default::default()
+        };
+        let value = schema_org(&recipe);
+        assert_eq!(value["@type"], "Recipe");
+        assert_eq!(value["recipeInstructions"][0]["text"], "Kook.");
+        assert_eq!(value["totalTime"], "PT30M");
+        assert!(value.get("description").is_none());
+        assert_eq!(crate::import::parse_recipe_value(&value).unwrap().title.as_deref(), Some("Soep"));
+    }
+
+    #[test]
+    fn test_render_html_escapes_text() {
+        let recipe = Recipe {
+            title: Some("Fish & chips".to_string()),
+            steps: Some(vec!["Bak <goudbruin>.".to_string()]),
+            ..Default::default()
+        };
+        let html = render_html(&recipe, Some("cid:recipe-image"), false);
+        assert!(html.contains("<h1>Fish &amp; chips</h1>"));
+        assert!(html.contains("<img src=\"cid:recipe-image\""));
+        assert!(html.contains("<li>Bak &lt;goudbruin&gt;.</li>"));
+        assert!(!html.contains("<svg"));
+    }
+
+    #[test]
+    fn test_render_html_qr_code() {
+        let recipe = Recipe { source_url: "https://15gram.be/recepten/soep".to_string(), ..Default::default() };
+        let html = render_html(&recipe, None, true);
+        assert!(html.contains("<figure style=\"width: 160px\"><svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(!html.contains("<?xml"));
+        assert_eq!(qr_code_svg(""), None);
+    }
+}
@@ -0,0 +1,20 @@
+//! `selectors update`: installing the latest selector definitions without a new release.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// The selectors file on the main branch of the repository.
+pub const DEFAULT_UPDATE_URL: &str = "https://raw.githubusercontent.com/mberetvas/FoodScraper/main/src/selectors.toml";
+
+/// Downloads the selectors file, checks that it loads, and installs it as the user's selectors file. Returns where
+/// it was installed and the number of sites it configures.
+pub async fn update_selectors(client: &reqwest::Client, url: &str) -> Result<(PathBuf, usize), Box<dyn std::error::Error>> {
+    let content = client.get(url).send().await?.error_for_status()?.text().await?;
+    let sites = foodscraper::check_selectors(&content).map_err(|e| format!("The selectors at {} are invalid: {}", url, e))?;
+    let path = foodscraper::user_selectors_path().ok_or("Cannot find the config directory: set XDG_CONFIG_HOME or HOME.")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, content)?;
+    Ok((path, sites))
+}
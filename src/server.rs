@@ -0,0 +1,190 @@
+//! `serve`: a small REST API over the scraper and the local library.
+//!
+//! - `POST /scrape` with `{"url": "...", "save": false}` scrapes a page and returns the recipe JSON,
+//!   storing it with the configured `--store` backend when `save` is true.
+//! - `GET /recipes` lists the saved recipes, optionally filtered with `site`, `tag`, `favorites` and
+//!   a full-text `q` query.
+//! - With the `graphql` feature, `/graphql` answers GraphQL queries over the library and `/graphiql`
+//!   serves an in-browser query editor.
+
+use crate::{OnConflict, Recipe, ScrapeError, StoreMode, library, storage};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Where the server reads and writes the library, and the client it scrapes with.
+#[derive(Debug, Clone)]
+pub struct ServerState {
+    pub output_folder: String,
+    pub db_path: String,
+    pub store: StoreMode,
+    pub on_conflict: OnConflict,
+    pub client: reqwest::Client,
+}
+
+/// Binds to `address` and serves the API until the process is stopped.
+pub async fn serve(address: &str, state: ServerState) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    status!("Serving the recipe API on http://{}", listener.local_addr()?);
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+/// The API routes.
+pub fn router(state: ServerState) -> Router {
+    let state = Arc::new(state);
+    let router = Router::new().route("/scrape", post(scrape)).route("/recipes", get(recipes));
+    #[cfg(feature = "graphql")]
+    let router = router
+        .route_service("/graphql", async_graphql_axum::GraphQL::new(crate::graphql::schema(state.clone())))
+        .route("/graphiql", get(graphiql));
+    router.with_state(state)
+}
+
+#[cfg(feature = "graphql")]
+async fn graphiql() -> axum::response::Html<String> {
+    axum::response::Html(async_graphql::http::GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+/// An error turned into a JSON `{"error": ...}` response.
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn internal_error(error: Box<dyn std::error::Error>) -> ApiError {
+    ApiError(StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+}
+
+/// The status to answer a failed scrape with: the client's fault for URLs we can't scrape or pages
+/// without a recipe, the site's when it can't be fetched.
+fn scrape_error_status(error: &ScrapeError) -> StatusCode {
+    match error {
+        ScrapeError::UnsupportedSite(_) => StatusCode::BAD_REQUEST,
+        ScrapeError::EmptyExtraction(_) | ScrapeError::LowConfidence { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        ScrapeError::Network(_) | ScrapeError::PageGone(_) => StatusCode::BAD_GATEWAY,
+        ScrapeError::Selectors(_) | ScrapeError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Runs blocking SQLite and file work on tokio's blocking pool, off the async workers.
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> Result<T, Box<dyn std::error::Error>> + Send + 'static) -> Result<T, ApiError> {
+    tokio::task::spawn_blocking(move || work().map_err(internal_error)).await.map_err(|e| internal_error(e.into()))?
+}
+
+#[derive(Debug, Deserialize)]
+struct ScrapeRequest {
+    url: String,
+    #[serde(default)]
+    save: bool,
+}
+
+async fn scrape(State(state): State<Arc<ServerState>>, Json(request): Json<ScrapeRequest>) -> Result<Json<Recipe>, ApiError> {
+    if crate::validate_url(&request.url).is_err() || !crate::validate_supported_url(&request.url) {
+        return Err(ApiError(StatusCode::BAD_REQUEST, "Invalid URL or unsupported domain.".to_string()));
+    }
+    let recipe = match crate::scrape_page(&state.client, &request.url).await {
+        Ok((recipe, _)) => recipe,
+        Err(e) => return Err(ApiError(scrape_error_status(&e), e.to_string())),
+    };
+    if !request.save {
+        return Ok(Json(recipe));
+    }
+    blocking(move || {
+        crate::save_recipe(&recipe, state.store, state.on_conflict, &state.output_folder, &state.db_path)?;
+        Ok(Json(recipe))
+    })
+    .await
+}
+
+/// Filters for listing the library.
+#[derive(Debug, Default, Deserialize)]
+pub struct RecipesQuery {
+    pub site: Option<String>,
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub favorites: bool,
+    /// Full-text search over titles, ingredients and steps.
+    pub q: Option<String>,
+}
+
+/// A saved recipe together with where it is stored (`#id` or a file path).
+#[derive(Debug, Serialize)]
+pub struct SavedRecipe {
+    pub location: String,
+    #[serde(flatten)]
+    pub recipe: Recipe,
+}
+
+async fn recipes(State(state): State<Arc<ServerState>>, Query(query): Query<RecipesQuery>) -> Result<Json<Vec<SavedRecipe>>, ApiError> {
+    blocking(move || list_recipes(&state, &query).map(Json)).await
+}
+
+/// The saved recipes matching the query.
+pub fn list_recipes(state: &ServerState, query: &RecipesQuery) -> Result<Vec<SavedRecipe>, Box<dyn std::error::Error>> {
+    let filter = library::LibraryFilter {
+        site: query.site.clone(),
+        tag: query.tag.clone(),
+        since: None,
+        favorites_only: query.favorites,
+    };
+    let matching: Option<Vec<String>> = match query.q.as_deref().filter(|q| !q.trim().is_empty()) {
+        Some(q) if std::path::Path::new(&state.db_path).exists() => {
+            let connection = storage::open_database(&state.db_path)?;
+            Some(storage::search_recipes(&connection, q)?.into_iter().map(|hit| format!("#{}", hit.id)).collect())
+        }
+        Some(q) => Some(library::search_folder(&state.output_folder, q)?.into_iter().map(|(path, _)| path.display().to_string()).collect()),
+        None => None,
+    };
+
+    Ok(crate::load_saved_recipes(&state.output_folder, &state.db_path)?
+        .into_iter()
+        .filter(|(location, recipe)| filter.matches(recipe) && matching.as_ref().is_none_or(|hits| hits.contains(location)))
+        .map(|(location, recipe)| SavedRecipe { location, recipe })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_recipes_filters_folder() {
+        let folder = std::env::temp_dir().join(format!("foodscraper-server-{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        for (name, tags) in [("soep", vec!["winter".to_string()]), ("pasta", Vec::new())] {
+            let recipe = Recipe {
+                title: Some(name.to_string()),
+                source_url: format!("https://15gram.be/recepten/{}", name),
+                tags,
+                ..Default::default()
+            };
+            std::fs::write(folder.join(format!("recipe_{}.json", name)), serde_json::to_string(&recipe).unwrap()).unwrap();
+        }
+
+        let output_folder = folder.to_str().unwrap().to_string();
+        let state = ServerState { db_path: format!("{}/recipes.db", output_folder), output_folder, store: StoreMode::Json, on_conflict: OnConflict::Overwrite, client: reqwest::Client::new() };
+        let query = RecipesQuery { tag: Some("winter".to_string()), ..Default::default() };
+        let recipes = list_recipes(&state, &query).unwrap();
+        assert_eq!(recipes.len(), 1);
+        assert_eq!(recipes[0].recipe.title.as_deref(), Some("soep"));
+        assert!(recipes[0].location.ends_with("recipe_soep.json"));
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_scrape_error_status() {
+        assert_eq!(scrape_error_status(&ScrapeError::UnsupportedSite("https://example.com".to_string())), StatusCode::BAD_REQUEST);
+        assert_eq!(scrape_error_status(&ScrapeError::EmptyExtraction("no recipe".to_string())), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(scrape_error_status(&ScrapeError::Network("timed out".to_string())), StatusCode::BAD_GATEWAY);
+        assert_eq!(scrape_error_status(&ScrapeError::Selectors("bad selector".to_string())), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
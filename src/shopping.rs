@@ -0,0 +1,153 @@
+//! Shopping lists grouped by store section.
+//!
+//! The layout maps ingredients to the sections of a supermarket, in walking order, and merges
+//! ingredient variants (`uien`, `gele ui`) into one canonical name before quantities are summed.
+
+use crate::ingredients::{self, ShoppingItem};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// The built-in layout, used when there is no `store_sections.toml` in the working directory.
+const DEFAULT_LAYOUT: &str = include_str!("store_sections.toml");
+
+/// File name of a user-provided layout in the working directory.
+pub const LAYOUT_FILE: &str = "store_sections.toml";
+
+/// Name of the section for ingredients no section claims.
+const OTHER_SECTION: &str = "Overig";
+
+/// One aisle or department of the store.
+#[derive(Debug, Deserialize)]
+pub struct StoreSection {
+    pub name: String,
+    /// Canonical ingredient names sold in this section.
+    #[serde(default)]
+    pub ingredients: Vec<String>,
+}
+
+/// The sections of a store in walking order, plus the canonical ingredient mapping.
+#[derive(Debug, Deserialize)]
+pub struct StoreLayout {
+    #[serde(rename = "section", default)]
+    pub sections: Vec<StoreSection>,
+    /// Canonical name to the variants that should be merged into it.
+    #[serde(default)]
+    pub canonical: HashMap<String, Vec<String>>,
+}
+
+impl StoreLayout {
+    /// Loads `store_sections.toml` from the working directory, or the built-in layout if there is none.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let content =
+            if Path::new(LAYOUT_FILE).exists() { fs::read_to_string(LAYOUT_FILE)? } else { DEFAULT_LAYOUT.to_string() };
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// Maps an ingredient name to its canonical form, or returns it lowercased if it has none.
+    pub fn canonical_name(&self, name: &str) -> String {
+        let name = name.trim().to_lowercase();
+        self.canonical
+            .iter()
+            .find(|(canonical, variants)| **canonical == name || variants.iter().any(|variant| variant.to_lowercase() == name))
+            .map(|(canonical, _)| canonical.clone())
+            .unwrap_or(name)
+    }
+
+    /// The index of the section selling the ingredient: an exact match first, otherwise the section
+    /// whose longest ingredient appears as whole words in the name (`gehakt van rund` finds `gehakt`).
+    pub fn section_of(&self, name: &str) -> Option<usize> {
+        let name = name.to_lowercase();
+        let words: Vec<&str> = name.split(|c: char| !c.is_alphanumeric() && c != '-').collect();
+        let mut best: Option<(usize, usize)> = None;
+        for (index, section) in self.sections.iter().enumerate() {
+            for ingredient in &section.ingredients {
+                let ingredient = ingredient.to_lowercase();
+                if ingredient == name {
+                    return Some(index);
+                }
+                let ingredient_words: Vec<&str> = ingredient.split(' ').collect();
+                let contained = words.windows(ingredient_words.len()).any(|window| window == ingredient_words.as_slice());
+                if contained && best.is_none_or(|(_, length)| ingredient.len() > length) {
+                    best = Some((index, ingredient.len()));
+                }
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+
+    /// Merges the ingredient lines using the canonical mapping and groups them by section, in walking order.
+    pub fn shopping_list<'a>(&self, lines: impl IntoIterator<Item = &'a str>) -> Vec<(String, Vec<ShoppingItem>)> {
+        let parsed = lines.into_iter().map(|line| {
+            let mut parsed = ingredients::parse_ingredient(line);
+            let canonical = self.canonical_name(&parsed.key());
+            if canonical != parsed.key() {
+                parsed.name = canonical;
+            }
+            parsed
+        });
+
+        let mut groups: Vec<Vec<ShoppingItem>> = (0..=self.sections.len()).map(|_| Vec::new()).collect();
+        for item in ingredients::merge_parsed(parsed) {
+            let index = self.section_of(&item.name).unwrap_or(self.sections.len());
+            groups[index].push(item);
+        }
+
+        let names = self.sections.iter().map(|section| section.name.clone()).chain([OTHER_SECTION.to_string()]);
+        names.zip(groups).filter(|(_, items)| !items.is_empty()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout() -> StoreLayout {
+        toml::from_str(
+            r#"
+            [[section]]
+            name = "Groenten"
+            ingredients = ["ui", "wortel"]
+
+            [[section]]
+            name = "Vlees"
+            ingredients = ["gehakt"]
+
+            [canonical]
+            ui = ["uien", "gele ui"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_default_layout_parses() {
+        let layout: StoreLayout = toml::from_str(DEFAULT_LAYOUT).unwrap();
+        assert!(!layout.sections.is_empty());
+        assert_eq!(layout.canonical_name("Gele uien"), "ui");
+    }
+
+    #[test]
+    fn test_section_of_matches_words() {
+        let layout = layout();
+        assert_eq!(layout.section_of("wortel"), Some(0));
+        assert_eq!(layout.section_of("gehakt van rund"), Some(1));
+        assert_eq!(layout.section_of("uitjes"), None);
+    }
+
+    #[test]
+    fn test_shopping_list_groups_in_walking_order() {
+        let list = layout().shopping_list(["500 g gehakt", "1 ui", "2 uien", "1 gele ui", "olijfolie", "1 wortel"]);
+        let rendered: Vec<(String, Vec<String>)> =
+            list.into_iter().map(|(section, items)| (section, items.iter().map(ToString::to_string).collect())).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                ("Groenten".to_string(), vec!["4 ui".to_string(), "1 wortel".to_string()]),
+                ("Vlees".to_string(), vec!["500 g gehakt".to_string()]),
+                ("Overig".to_string(), vec!["olijfolie".to_string()]),
+            ]
+        );
+    }
+}
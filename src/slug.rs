@@ -0,0 +1,67 @@
+//! Filesystem-safe file names from recipe titles: `Kip à l'estragon / snelle versie` is saved as
+//! `recipe_kip-a-l-estragon-snelle-versie.json`, on every platform.
+
+use deunicode::deunicode;
+
+/// Slugs are cut to at most this many characters, at a word boundary when there is one.
+pub const MAX_SLUG_LEN: usize = 60;
+
+/// The title in lowercase ASCII, accents transliterated (`à` → `a`, `ß` → `ss`), with every run of other characters
+/// (spaces, slashes, quotes, …) replaced by a single `-`.
+pub fn slugify(title: &str) -> String {
+    let ascii = deunicode(title).to_lowercase();
+    let words: Vec<&str> = ascii.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()).collect();
+    let slug = words.join("-");
+    if slug.len() <= MAX_SLUG_LEN {
+        return slug;
+    }
+    let cut = &slug[..MAX_SLUG_LEN];
+    match cut.rfind('-') {
+        Some(end) if end > MAX_SLUG_LEN / 2 => cut[..end].to_string(),
+        _ => cut.to_string(),
+    }
+}
+
+/// The file name, without extension, of a recipe with this title: `recipe_<slug>`, or `recipe` without a title.
+pub fn recipe_stem(title: Option<&str>) -> String {
+    match title.map(slugify).filter(|slug| !slug.is_empty()) {
+        Some(slug) => format!("recipe_{}", slug),
+        None => "recipe".to_string(),
+    }
+}
+
+/// The first of `stem`, `stem-2`, `stem-3`, … that is not `taken`.
+pub fn unique_stem(stem: &str, mut taken: impl FnMut(&str) -> bool) -> String {
+    let mut candidate = stem.to_string();
+    let mut counter = 2;
+    while taken(&candidate) {
+        candidate = format!("{}-{}", stem, counter);
+        counter += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Kip à l'estragon / snelle versie"), "kip-a-l-estragon-snelle-versie");
+        assert_eq!(slugify("  Crème brûlée: 4×  "), "creme-brulee-4x");
+        assert_eq!(slugify("Gebäck mit Weißmehl"), "geback-mit-weissmehl");
+        assert_eq!(recipe_stem(Some("***")), "recipe");
+        assert_eq!(recipe_stem(None), "recipe");
+
+        let long = slugify(&"ovenschotel met ".repeat(10));
+        assert!(long.len() <= MAX_SLUG_LEN && !long.ends_with('-'));
+        assert!(long.ends_with("met") || long.ends_with("ovenschotel"));
+    }
+
+    #[test]
+    fn test_unique_stem() {
+        let taken = ["recipe_soep", "recipe_soep-2"];
+        assert_eq!(unique_stem("recipe_soep", |stem| taken.contains(&stem)), "recipe_soep-3");
+        assert_eq!(unique_stem("recipe_taart", |stem| taken.contains(&stem)), "recipe_taart");
+    }
+}
@@ -0,0 +1,77 @@
+//! Snapshot tests of the extraction: saved recipe pages, each with the recipe expected from it.
+//!
+//! A fixture is a directory `tests/fixtures/<site>/` holding the page as `page.html` and the recipe extracted from it
+//! as `expected.json`, whose `source_url` is where the page came from. `cargo test` checks every fixture against the
+//! built-in selectors; `--record-fixture` saves the page being scraped as a new one.
+
+use crate::{DEFAULT_SELECTORS, Recipe, RecipeCssSelectors, extract_recipe, site_for_url};
+use scraper::Html;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where the fixtures live, relative to the repository root.
+pub const FIXTURES_DIR: &str = "tests/fixtures";
+
+/// Extracts the recipe from a saved page with the built-in selectors, so that the result depends on the tree only
+/// and not on any selectors file.
+pub fn extract_snapshot(html: &str, url: &str) -> Result<Recipe, Box<dyn std::error::Error>> {
+    let selectors = RecipeCssSelectors::for_url(DEFAULT_SELECTORS, url)?;
+    Ok(extract_recipe(&Html::parse_document(html), &selectors, url))
+}
+
+/// Checks the fixture in `dir` against its snapshot, listing every field that differs.
+pub fn check_fixture(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let html = fs::read_to_string(dir.join("page.html"))?;
+    let expected: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join("expected.json"))?)?;
+    let url = expected["source_url"].as_str().ok_or("expected.json has no source_url")?;
+    let actual = serde_json::to_value(extract_snapshot(&html, url)?)?;
+    let (Some(expected), Some(actual)) = (expected.as_object(), actual.as_object()) else {
+        return Err("expected.json is not a recipe".into());
+    };
+    let fields: std::collections::BTreeSet<_> = expected.keys().chain(actual.keys()).collect();
+    let null = serde_json::Value::Null;
+    let differences: Vec<String> = fields
+        .into_iter()
+        .filter_map(|field| {
+            let (expected, actual) = (expected.get(field).unwrap_or(&null), actual.get(field).unwrap_or(&null));
+            (expected != actual).then(|| format!("  {}: expected {}, got {}", field, expected, actual))
+        })
+        .collect();
+    match differences.is_empty() {
+        true => Ok(()),
+        false => Err(format!("{}:\n{}", dir.display(), differences.join("\n")).into()),
+    }
+}
+
+/// Saves a page of a built-in site and the recipe extracted from it as the fixture `<root>/<site>/`, replacing the
+/// site's previous fixture.
+pub fn record_fixture(root: &Path, url: &str, html: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let site = site_for_url(DEFAULT_SELECTORS, url).ok_or("Only pages of the built-in sites can be recorded as fixtures.")?;
+    let recipe = extract_snapshot(html, url)?;
+    let dir = root.join(site);
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("page.html"), html)?;
+    fs::write(dir.join("expected.json"), serde_json::to_string_pretty(&recipe)? + "\n")?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_check_fixture() {
+        let root = std::env::temp_dir().join(format!("foodscraper-snapshot-{}", std::process::id()));
+        let html = include_str!("../tests/fixtures/njam/page.html");
+        let dir = record_fixture(&root, "https://www.njam.be/recepten/spaghetti-met-gehaktballetjes", html).unwrap();
+        assert_eq!(dir, root.join("njam"));
+        assert!(check_fixture(&dir).is_ok());
+
+        let expected = fs::read_to_string(dir.join("expected.json")).unwrap();
+        fs::write(dir.join("expected.json"), expected.replace("\"language\": \"nl\"", "\"language\": \"fr\"")).unwrap();
+        let error = check_fixture(&dir).unwrap_err().to_string();
+        assert!(error.contains("language: expected \"fr\", got \"nl\""), "{}", error);
+        assert!(record_fixture(&root, "https://example.com/recipe", html).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,493 @@
+//! SQLite storage backend for scraped recipes.
+//!
+//! Recipes are split over proper tables (recipes, ingredients, steps, timers and tags) so the
+//! library can be queried without loading every JSON file. Recipes are keyed by their source URL:
+//! storing the same recipe again replaces the previous row and its children.
+
+use crate::Recipe;
+use crate::fetch::HttpCache;
+use crate::steps::StepTimer;
+use rusqlite::{Connection, OptionalExtension, params};
+use sha2::{Digest, Sha256};
+
+/// Schema of the recipe database; every statement is idempotent.
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+
+    CREATE TABLE IF NOT EXISTS recipes (
+        id          INTEGER PRIMARY KEY,
+        source_url  TEXT NOT NULL UNIQUE,
+        title       TEXT,
+        description TEXT,
+        image_link  TEXT,
+        language    TEXT,
+        scraped_at  TEXT NOT NULL,
+        etag          TEXT,
+        last_modified TEXT,
+        content_hash  TEXT,
+        total_time    INTEGER,
+        favorite      INTEGER NOT NULL DEFAULT 0,
+        thumbnail     TEXT,
+        archived_url  TEXT,
+        archived_at   TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS ingredients (
+        recipe_id INTEGER NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+        position  INTEGER NOT NULL,
+        text      TEXT NOT NULL,
+        PRIMARY KEY (recipe_id, position)
+    );
+
+    CREATE TABLE IF NOT EXISTS steps (
+        recipe_id INTEGER NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+        position  INTEGER NOT NULL,
+        text      TEXT NOT NULL,
+        PRIMARY KEY (recipe_id, position)
+    );
+
+    CREATE TABLE IF NOT EXISTS timers (
+        recipe_id   INTEGER NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+        step        INTEGER NOT NULL,
+        text        TEXT NOT NULL,
+        seconds     INTEGER NOT NULL,
+        max_seconds INTEGER
+    );
+
+    CREATE TABLE IF NOT EXISTS tags (
+        recipe_id INTEGER NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+        tag       TEXT NOT NULL,
+        PRIMARY KEY (recipe_id, tag)
+    );
+
+    CREATE VIRTUAL TABLE IF NOT EXISTS recipes_fts USING fts5(
+        title, ingredients, steps,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+";
+
+/// A recipe matching a full-text search.
+#[derive(Debug)]
+pub struct SearchHit {
+    /// Row id of the recipe.
+    pub id: i64,
+    /// Title of the recipe.
+    pub title: Option<String>,
+    /// URL the recipe was scraped from.
+    pub source_url: String,
+}
+
+/// Columns added to `recipes` after its first release, with their definitions.
+const ADDED_RECIPE_COLUMNS: &[(&str, &str)] = &[
+    ("etag", "TEXT"),
+    ("last_modified", "TEXT"),
+    ("content_hash", "TEXT"),
+    ("total_time", "INTEGER"),
+    ("favorite", "INTEGER NOT NULL DEFAULT 0"),
+    ("thumbnail", "TEXT"),
+    ("archived_url", "TEXT"),
+    ("archived_at", "TEXT"),
+];
+
+/// Opens (and creates if needed) the recipe database at the given path.
+pub fn open_database(path: &str) -> rusqlite::Result<Connection> {
+    let connection = Connection::open(path)?;
+    connection.execute_batch(SCHEMA)?;
+    migrate(&connection)?;
+    Ok(connection)
+}
+
+/// Adds columns missing from databases created by older versions, and fills the search index of databases created
+/// before there was one.
+fn migrate(connection: &Connection) -> rusqlite::Result<()> {
+    let mut statement = connection.prepare("SELECT name FROM pragma_table_info('recipes')")?;
+    let existing: Vec<String> = statement.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+    for (column, definition) in ADDED_RECIPE_COLUMNS {
+        if !existing.iter().any(|name| name == column) {
+            connection.execute(&format!("ALTER TABLE recipes ADD COLUMN {column} {definition}"), [])?;
+        }
+    }
+    let unindexed: bool = connection.query_row(
+        "SELECT NOT EXISTS (SELECT 1 FROM recipes_fts) AND EXISTS (SELECT 1 FROM recipes)",
+        [],
+        |row| row.get(0),
+    )?;
+    if unindexed {
+        // The same text `store_recipe` indexes: the title, and the ingredients and steps one per line.
+        connection.execute(
+            "INSERT INTO recipes_fts (rowid, title, ingredients, steps)
+             SELECT id, title,
+                 COALESCE((SELECT group_concat(text, char(10)) FROM (SELECT text FROM ingredients WHERE recipe_id = recipes.id ORDER BY position)), ''),
+                 COALESCE((SELECT group_concat(text, char(10)) FROM (SELECT text FROM steps WHERE recipe_id = recipes.id ORDER BY position)), '')
+             FROM recipes",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// Inserts or replaces a recipe and returns its row id.
+pub fn store_recipe(connection: &mut Connection, recipe: &Recipe) -> rusqlite::Result<i64> {
+    let transaction = connection.transaction()?;
+    let scraped_at = recipe.scraped_at.clone().unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let id: i64 = transaction.query_row(
+        "INSERT INTO recipes (source_url, title, description, image_link, language, scraped_at, etag, last_modified, content_hash, total_time, favorite, thumbnail, archived_url, archived_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+         ON CONFLICT (source_url) DO UPDATE SET
+             title = excluded.title,
+             description = excluded.description,
+             image_link = excluded.image_link,
+             language = excluded.language,
+             scraped_at = excluded.scraped_at,
+             etag = excluded.etag,
+             last_modified = excluded.last_modified,
+             content_hash = excluded.content_hash,
+             total_time = excluded.total_time,
+             favorite = excluded.favorite,
+             thumbnail = excluded.thumbnail,
+             archived_url = excluded.archived_url,
+             archived_at = excluded.archived_at
+         RETURNING id",
+        params![
+            recipe.source_url,
+            recipe.title,
+            recipe.description,
+            recipe.image_link,
+            recipe.language,
+            scraped_at,
+            recipe.http_cache.as_ref().and_then(|cache| cache.etag.as_deref()),
+            recipe.http_cache.as_ref().and_then(|cache| cache.last_modified.as_deref()),
+            content_hash(recipe),
+            recipe.total_time,
+            recipe.favorite,
+            recipe.thumbnail,
+            recipe.archived_copy.as_ref().map(|copy| copy.url.as_str()),
+            recipe.archived_copy.as_ref().map(|copy| copy.archived_at.as_str())
+        ],
+        |row| row.get(0),
+    )?;
+
+    for table in ["ingredients", "steps", "timers", "tags"] {
+        transaction.execute(&format!("DELETE FROM {table} WHERE recipe_id = ?1"), [id])?;
+    }
+    transaction.execute("DELETE FROM recipes_fts WHERE rowid = ?1", [id])?;
+    transaction.execute(
+        "INSERT INTO recipes_fts (rowid, title, ingredients, steps) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            id,
+            recipe.title,
+            recipe.ingredients.as_deref().unwrap_or_default().join("\n"),
+            recipe.steps.as_deref().unwrap_or_default().join("\n")
+        ],
+    )?;
+    for (table, items) in [("ingredients", &recipe.ingredients), ("steps", &recipe.steps)] {
+        let insert = format!("INSERT INTO {table} (recipe_id, position, text) VALUES (?1, ?2, ?3)");
+        for (position, text) in items.iter().flatten().enumerate() {
+            transaction.execute(&insert, params![id, position, text])?;
+        }
+    }
+    for timer in recipe.timers.iter().flatten() {
+        transaction.execute(
+            "INSERT INTO timers (recipe_id, step, text, seconds, max_seconds) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, timer.step, timer.text, timer.seconds, timer.max_seconds],
+        )?;
+    }
+
+    for tag in &recipe.tags {
+        transaction.execute("INSERT OR IGNORE INTO tags (recipe_id, tag) VALUES (?1, ?2)", params![id, tag])?;
+    }
+
+    transaction.commit()?;
+    Ok(id)
+}
+
+/// Deletes a recipe with its ingredients, steps, timers and tags, and returns whether it existed.
+pub fn delete_recipe(connection: &mut Connection, id: i64) -> rusqlite::Result<bool> {
+    let transaction = connection.transaction()?;
+    transaction.execute("DELETE FROM recipes_fts WHERE rowid = ?1", [id])?;
+    let deleted = transaction.execute("DELETE FROM recipes WHERE id = ?1", [id])?;
+    transaction.commit()?;
+    Ok(deleted > 0)
+}
+
+/// Why a recipe is considered already present in the database.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Duplicate {
+    /// A recipe with the same source URL exists.
+    SourceUrl(i64),
+    /// A recipe with identical title, ingredients and steps exists.
+    Content(i64),
+}
+
+/// Finds an existing recipe with the same source URL or the same content.
+pub fn find_duplicate(connection: &Connection, recipe: &Recipe) -> rusqlite::Result<Option<Duplicate>> {
+    let by_url = connection
+        .query_row("SELECT id FROM recipes WHERE source_url = ?1", [&recipe.source_url], |row| row.get(0))
+        .optional()?;
+    if let Some(id) = by_url {
+        return Ok(Some(Duplicate::SourceUrl(id)));
+    }
+    let by_content = connection
+        .query_row("SELECT id FROM recipes WHERE content_hash = ?1", [content_hash(recipe)], |row| row.get(0))
+        .optional()?;
+    Ok(by_content.map(Duplicate::Content))
+}
+
+/// SHA-256 over the normalized title, ingredients and steps, used to spot the same recipe under different URLs.
+pub fn content_hash(recipe: &Recipe) -> String {
+    let mut hasher = Sha256::new();
+    let title = recipe.title.as_deref().unwrap_or_default();
+    for part in std::iter::once(title)
+        .chain(recipe.ingredients.iter().flatten().map(String::as_str))
+        .chain(recipe.steps.iter().flatten().map(String::as_str))
+    {
+        hasher.update(crate::text::collapse_whitespace(part).to_lowercase().as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Loads every recipe in the database, with its children, ordered by id.
+pub fn load_recipes(connection: &Connection) -> rusqlite::Result<Vec<(i64, Recipe)>> {
+    let mut statement = connection
+        .prepare("SELECT id, source_url, title, description, image_link, language, scraped_at, etag, last_modified, total_time, favorite, thumbnail, archived_url, archived_at FROM recipes ORDER BY id")?;
+    let rows = statement.query_map([], |row| {
+        let cache = HttpCache { etag: row.get(7)?, last_modified: row.get(8)? };
+        let recipe = Recipe {
+            source_url: row.get(1)?,
+            title: row.get(2)?,
+            description: row.get(3)?,
+            image_link: row.get(4)?,
+            language: row.get(5)?,
+            scraped_at: row.get(6)?,
+            http_cache: (cache != HttpCache::default()).then_some(cache),
+            total_time: row.get(9)?,
+            favorite: row.get(10)?,
+            thumbnail: row.get(11)?,
+            archived_copy: match (row.get(12)?, row.get(13)?) {
+                (Some(url), Some(archived_at)) => Some(foodscraper::ArchivedCopy { url, archived_at }),
+                _ => None,
+            },
+            ..Default::default()
+        };
+        Ok((row.get::<_, i64>(0)?, recipe))
+    })?;
+
+    let mut recipes = Vec::new();
+    for row in rows {
+        let (id, mut recipe) = row?;
+        let ingredients = load_texts(connection, "ingredients", id)?;
+        let steps = load_texts(connection, "steps", id)?;
+        recipe.ingredients = (!ingredients.is_empty()).then_some(ingredients);
+        recipe.steps = (!steps.is_empty()).then_some(steps);
+
+        let mut timers = connection
+            .prepare("SELECT step, text, seconds, max_seconds FROM timers WHERE recipe_id = ?1 ORDER BY rowid")?;
+        let timers: Vec<StepTimer> = timers
+            .query_map([id], |row| {
+                Ok(StepTimer { step: row.get(0)?, text: row.get(1)?, seconds: row.get(2)?, max_seconds: row.get(3)? })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        recipe.timers = (!timers.is_empty()).then_some(timers);
+
+        let mut tags = connection.prepare("SELECT tag FROM tags WHERE recipe_id = ?1 ORDER BY tag")?;
+        recipe.tags = tags.query_map([id], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+        recipes.push((id, recipe));
+    }
+    Ok(recipes)
+}
+
+/// Loads the ordered text rows of a recipe's ingredients or steps table.
+fn load_texts(connection: &Connection, table: &str, recipe_id: i64) -> rusqlite::Result<Vec<String>> {
+    let mut statement = connection.prepare(&format!("SELECT text FROM {table} WHERE recipe_id = ?1 ORDER BY position"))?;
+    statement.query_map([recipe_id], |row| row.get(0))?.collect()
+}
+
+/// Searches titles, ingredients and steps for recipes containing every word of the query, best matches first.
+///
+/// Words are matched as prefixes, so `pompoen` also finds `pompoensoep`.
+pub fn search_recipes(connection: &Connection, query: &str) -> rusqlite::Result<Vec<SearchHit>> {
+    let Some(fts_query) = fts_query(query) else {
+        return Ok(Vec::new());
+    };
+    let mut statement = connection.prepare(
+        "SELECT recipes.id, recipes.title, recipes.source_url
+         FROM recipes_fts JOIN recipes ON recipes.id = recipes_fts.rowid
+         WHERE recipes_fts MATCH ?1
+         ORDER BY bm25(recipes_fts)",
+    )?;
+    let hits = statement.query_map([fts_query], |row| {
+        Ok(SearchHit { id: row.get(0)?, title: row.get(1)?, source_url: row.get(2)? })
+    })?;
+    hits.collect()
+}
+
+/// Turns free text into an FTS5 query of quoted prefix terms, so user input can never be a syntax error.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_recipe() -> Recipe {
+        Recipe {
+            title: Some("Pompoensoep".to_string()),
+            ingredients: Some(vec!["1 pompoen".to_string(), "1 ui".to_string()]),
+            steps: Some(vec!["Snij de pompoen.".to_string(), "Kook 20 minuten.".to_string()]),
+            timers: Some(crate::steps::extract_timers(&["Snij de pompoen.".to_string(), "Kook 20 minuten.".to_string()])),
+            language: Some("nl".to_string()),
+            source_url: "https://15gram.be/recepten/pompoensoep".to_string(),
+            scraped_at: Some("2024-06-01T12:00:00+00:00".to_string()),
+            tags: vec!["soep".to_string()],
+            favorite: true,
+            http_cache: Some(HttpCache { etag: Some("\"v1\"".to_string()), last_modified: None }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_store_recipe_inserts_children() {
+        let mut connection = open_database(":memory:").unwrap();
+        let id = store_recipe(&mut connection, &sample_recipe()).unwrap();
+
+        let ingredients: i64 =
+            connection.query_row("SELECT COUNT(*) FROM ingredients WHERE recipe_id = ?1", [id], |row| row.get(0)).unwrap();
+        let timer_seconds: i64 =
+            connection.query_row("SELECT seconds FROM timers WHERE recipe_id = ?1", [id], |row| row.get(0)).unwrap();
+        assert_eq!(ingredients, 2);
+        assert_eq!(timer_seconds, 1200);
+    }
+
+    #[test]
+    fn test_store_recipe_replaces_existing_source_url() {
+        let mut connection = open_database(":memory:").unwrap();
+        let first = store_recipe(&mut connection, &sample_recipe()).unwrap();
+
+        let mut updated = sample_recipe();
+        updated.ingredients = Some(vec!["1 butternut".to_string()]);
+        let second = store_recipe(&mut connection, &updated).unwrap();
+
+        let ingredients: i64 = connection.query_row("SELECT COUNT(*) FROM ingredients", [], |row| row.get(0)).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(ingredients, 1);
+    }
+
+    #[test]
+    fn test_search_recipes_matches_all_terms() {
+        let mut connection = open_database(":memory:").unwrap();
+        let id = store_recipe(&mut connection, &sample_recipe()).unwrap();
+
+        let hits = search_recipes(&connection, "pompoen kook").unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, id);
+        assert!(search_recipes(&connection, "pompoen lasagne").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_recipes_ignores_fts_syntax() {
+        let mut connection = open_database(":memory:").unwrap();
+        store_recipe(&mut connection, &sample_recipe()).unwrap();
+        assert_eq!(search_recipes(&connection, "\"pompoen) (*").unwrap().len(), 1);
+        assert!(search_recipes(&connection, "  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_recipes_round_trips() {
+        let mut connection = open_database(":memory:").unwrap();
+        let id = store_recipe(&mut connection, &sample_recipe()).unwrap();
+
+        let recipes = load_recipes(&connection).unwrap();
+        assert_eq!(recipes.len(), 1);
+        let (loaded_id, recipe) = &recipes[0];
+        assert_eq!(*loaded_id, id);
+        assert_eq!(recipe.ingredients, sample_recipe().ingredients);
+        assert_eq!(recipe.timers, sample_recipe().timers);
+        assert_eq!(recipe.tags, vec!["soep".to_string()]);
+        assert!(recipe.favorite);
+        assert_eq!(recipe.scraped_at.as_deref(), Some("2024-06-01T12:00:00+00:00"));
+        assert_eq!(recipe.http_cache, sample_recipe().http_cache);
+    }
+
+    #[test]
+    fn test_load_recipes_keeps_archived_copy() {
+        let mut connection = open_database(":memory:").unwrap();
+        let mut recipe = sample_recipe();
+        recipe.archived_copy = Some(foodscraper::ArchivedCopy {
+            url: "https://web.archive.org/web/20240101120000/https://15gram.be/recepten/pompoensoep".to_string(),
+            archived_at: "2024-01-01T12:00:00+00:00".to_string(),
+        });
+        store_recipe(&mut connection, &recipe).unwrap();
+        assert_eq!(load_recipes(&connection).unwrap()[0].1.archived_copy, recipe.archived_copy);
+    }
+
+    #[test]
+    fn test_open_database_migrates_old_schema() {
+        let path = std::env::temp_dir().join(format!("foodscraper-migrate-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("CREATE TABLE recipes (id INTEGER PRIMARY KEY, source_url TEXT NOT NULL UNIQUE, title TEXT, description TEXT, image_link TEXT, language TEXT, scraped_at TEXT NOT NULL);")
+            .unwrap();
+
+        let mut connection = open_database(path.to_str().unwrap()).unwrap();
+        store_recipe(&mut connection, &sample_recipe()).unwrap();
+        assert_eq!(load_recipes(&connection).unwrap()[0].1.http_cache, sample_recipe().http_cache);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_open_database_indexes_recipes_stored_before_search() {
+        let path = std::env::temp_dir().join(format!("foodscraper-migrate-fts-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let old = Connection::open(&path).unwrap();
+        // The schema from before the search index.
+        old.execute_batch(SCHEMA.split("CREATE VIRTUAL TABLE").next().unwrap()).unwrap();
+        old.execute("INSERT INTO recipes (id, source_url, title, scraped_at) VALUES (1, 'https://15gram.be/recepten/soep', 'Soep', '2024-06-01T10:00:00+00:00')", []).unwrap();
+        old.execute("INSERT INTO ingredients (recipe_id, position, text) VALUES (1, 0, '1 pompoen'), (1, 1, '2 uien')", []).unwrap();
+        old.execute("INSERT INTO steps (recipe_id, position, text) VALUES (1, 0, 'Kook de pompoen gaar.')", []).unwrap();
+        drop(old);
+
+        let connection = open_database(path.to_str().unwrap()).unwrap();
+        let hits = search_recipes(&connection, "uien").unwrap();
+        assert_eq!(hits.iter().map(|hit| hit.id).collect::<Vec<_>>(), [1]);
+        assert_eq!(search_recipes(&connection, "gaar").unwrap().len(), 1);
+        drop(connection);
+        // Opening it again does not index the recipe twice.
+        assert_eq!(search_recipes(&open_database(path.to_str().unwrap()).unwrap(), "soep").unwrap().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_delete_recipe() {
+        let mut connection = open_database(":memory:").unwrap();
+        let id = store_recipe(&mut connection, &sample_recipe()).unwrap();
+        assert!(delete_recipe(&mut connection, id).unwrap());
+        assert!(load_recipes(&connection).unwrap().is_empty());
+        assert!(search_recipes(&connection, "pompoen").unwrap().is_empty());
+        let tags: i64 = connection.query_row("SELECT COUNT(*) FROM tags", [], |row| row.get(0)).unwrap();
+        assert_eq!(tags, 0);
+        assert!(!delete_recipe(&mut connection, id).unwrap());
+    }
+
+    #[test]
+    fn test_find_duplicate_by_url_and_content() {
+        let mut connection = open_database(":memory:").unwrap();
+        let id = store_recipe(&mut connection, &sample_recipe()).unwrap();
+        assert_eq!(find_duplicate(&connection, &sample_recipe()).unwrap(), Some(Duplicate::SourceUrl(id)));
+
+        let mut mirrored = sample_recipe();
+        mirrored.source_url = "https://example.com/pompoensoep".to_string();
+        mirrored.title = Some("  POMPOENSOEP ".to_string());
+        assert_eq!(find_duplicate(&connection, &mirrored).unwrap(), Some(Duplicate::Content(id)));
+
+        mirrored.steps = None;
+        assert_eq!(find_duplicate(&connection, &mirrored).unwrap(), None);
+    }
+}
@@ -0,0 +1,84 @@
+//! `--strict`: checking a scraped recipe against the bundled JSON Schema of a schema.org `Recipe`, for output that
+//! other systems consume.
+
+use crate::Recipe;
+use crate::render::schema_org;
+use std::sync::LazyLock;
+
+/// The JSON Schema the schema.org form of every recipe must satisfy.
+pub const RECIPE_SCHEMA: &str = include_str!("recipe.schema.json");
+
+static VALIDATOR: LazyLock<jsonschema::Validator> = LazyLock::new(|| {
+    let schema = serde_json::from_str(RECIPE_SCHEMA).expect("the bundled recipe schema is JSON");
+    jsonschema::draft202012::options().should_validate_formats(true).build(&schema).expect("the bundled recipe schema is valid")
+});
+
+/// The recipe field each schema.org property comes from, to point the errors at what to fix.
+const FIELDS: &[(&str, &str)] = &[
+    ("name", "title"),
+    ("description", "description"),
+    ("url", "source_url"),
+    ("image", "image_link"),
+    ("inLanguage", "language"),
+    ("totalTime", "total_time"),
+    ("recipeIngredient", "ingredients"),
+    ("recipeInstructions", "steps"),
+];
+
+/// Checks the recipe, returning one message per violation of the schema, naming the recipe field it is about.
+pub fn schema_errors(recipe: &Recipe) -> Vec<String> {
+    let mut value = schema_org(recipe);
+    // `schema_org` names untitled recipes "Recipe" for importers; a missing title is exactly what --strict reports.
+    if recipe.title.is_none()
+        && let Some(object) = value.as_object_mut()
+    {
+        object.remove("name");
+    }
+    VALIDATOR
+        .iter_errors(&value)
+        .map(|error| {
+            let (path, message) = (error.instance_path().to_string(), error.to_string());
+            // A missing property is reported on the object itself, so its name is taken from the message.
+            let property = match path.trim_start_matches('/').split('/').next() {
+                Some("") | None => FIELDS.iter().map(|(property, _)| *property).find(|property| message.contains(&format!("\"{}\"", property))),
+                property => property,
+            };
+            match FIELDS.iter().find(|(p, _)| Some(*p) == property) {
+                Some((property, field)) => {
+                    let rest = path.split_once(property).map_or("", |(_, rest)| rest);
+                    format!("{} ({}{}): {}", field, property, rest, message)
+                }
+                None => format!("{}: {}", if path.is_empty() { "recipe" } else { &path }, message),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_errors() {
+        let mut recipe = Recipe {
+            title: Some("Soep".to_string()),
+            ingredients: Some(vec!["1 ui".to_string()]),
+            steps: Some(vec!["Kook.".to_string()]),
+            image_link: Some("https://15gram.be/media/soep.jpg".to_string()),
+            total_time: Some(30),
+            language: Some("nl".to_string()),
+            source_url: "https://15gram.be/recepten/soep".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(schema_errors(&recipe), Vec::<String>::new());
+
+        recipe.title = None;
+        recipe.steps = Some(vec![]);
+        recipe.image_link = Some("soep.jpg".to_string());
+        let errors = schema_errors(&recipe);
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("title (name): ")), "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("steps (recipeInstructions): ")), "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("image_link (image): ")), "{:?}", errors);
+    }
+}
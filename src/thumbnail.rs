@@ -0,0 +1,96 @@
+//! Thumbnails of downloaded recipe images, for library listings and exports.
+
+use clap::ValueEnum;
+use image::ImageFormat;
+use std::path::Path;
+
+/// The encoding used for thumbnails.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThumbnailFormat {
+    Jpeg,
+    Webp,
+}
+
+impl ThumbnailFormat {
+    /// The extension of thumbnail files in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "jpg",
+            ThumbnailFormat::Webp => "webp",
+        }
+    }
+}
+
+/// Maximum thumbnail dimensions, parsed from `WIDTHxHEIGHT` (or a single number for a square).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThumbnailSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl std::str::FromStr for ThumbnailSize {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Invalid thumbnail size '{}': use WIDTHxHEIGHT like 320x240.", text);
+        let (width, height) = text.split_once(['x', 'X']).unwrap_or((text, text));
+        let width: u32 = width.trim().parse().map_err(|_| error())?;
+        let height: u32 = height.trim().parse().map_err(|_| error())?;
+        if width == 0 || height == 0 {
+            return Err(error());
+        }
+        Ok(ThumbnailSize { width, height })
+    }
+}
+
+/// Writes a thumbnail of the image in `folder` as `<stem>.thumb.<ext>` and returns its file name.
+///
+/// The image is scaled down to fit within `size`, keeping its aspect ratio; smaller images are not enlarged.
+pub fn create_thumbnail(
+    folder: &str,
+    image_file: &str,
+    stem: &str,
+    size: ThumbnailSize,
+    format: ThumbnailFormat,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let folder = Path::new(folder);
+    let mut image = image::open(folder.join(image_file))?;
+    if image.width() > size.width || image.height() > size.height {
+        image = image.thumbnail(size.width, size.height);
+    }
+
+    let file_name = format!("{}.thumb.{}", stem, format.extension());
+    match format {
+        ThumbnailFormat::Jpeg => image.to_rgb8().save_with_format(folder.join(&file_name), ImageFormat::Jpeg)?,
+        ThumbnailFormat::Webp => image.to_rgba8().save_with_format(folder.join(&file_name), ImageFormat::WebP)?,
+    }
+    Ok(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_size_from_str() {
+        assert_eq!("320x240".parse(), Ok(ThumbnailSize { width: 320, height: 240 }));
+        assert_eq!("200".parse(), Ok(ThumbnailSize { width: 200, height: 200 }));
+        assert!("0x10".parse::<ThumbnailSize>().is_err());
+        assert!("big".parse::<ThumbnailSize>().is_err());
+    }
+
+    #[test]
+    fn test_create_thumbnail_fits_within_size() {
+        let folder = std::env::temp_dir().join(format!("foodscraper-thumb-{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        image::RgbImage::new(400, 200).save(folder.join("recipe_soep.png")).unwrap();
+
+        let folder_name = folder.to_str().unwrap();
+        let size = ThumbnailSize { width: 100, height: 100 };
+        let file_name = create_thumbnail(folder_name, "recipe_soep.png", "recipe_soep", size, ThumbnailFormat::Jpeg).unwrap();
+        assert_eq!(file_name, "recipe_soep.thumb.jpg");
+        let thumbnail = image::open(folder.join(&file_name)).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (100, 50));
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,357 @@
+//! The `tui` subcommand: a keyboard-driven browser over the saved recipes, with fuzzy search on the title, tags and
+//! site, a detail pane with the ingredients and steps, and tagging and deleting written back to the library.
+
+use crate::{Recipe, library};
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+/// What the keyboard currently drives.
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Browse,
+    Search,
+    /// Editing the tags of the selected recipe, as comma-separated text.
+    Tags(String),
+    ConfirmDelete,
+}
+
+/// What the event loop has to do after a key press.
+#[derive(Debug, PartialEq)]
+enum Effect {
+    None,
+    Quit,
+    /// Write back the recipe at this index of [`App::recipes`].
+    Save(usize),
+    /// Delete the recipe at this index of [`App::recipes`].
+    Delete(usize),
+}
+
+/// The state of the browser.
+struct App {
+    /// The saved recipes with their location, sorted by title.
+    recipes: Vec<(String, Recipe)>,
+    query: String,
+    /// The indices of the recipes matching the query, best match first.
+    visible: Vec<usize>,
+    list: ListState,
+    mode: Mode,
+    detail_scroll: u16,
+    /// The outcome of the last action, shown in the help line.
+    status: String,
+}
+
+/// Runs the browser over the recipes until the user quits.
+pub fn run(recipes: Vec<(String, Recipe)>, db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if recipes.is_empty() {
+        println!("No saved recipes found.");
+        return Ok(());
+    }
+    let mut app = App::new(recipes);
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, db_path);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App, db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match app.handle_key(key) {
+            Effect::None => {}
+            Effect::Quit => return Ok(()),
+            Effect::Save(index) => {
+                let (location, recipe) = &app.recipes[index];
+                app.status = match crate::update_saved_recipe(location, recipe, db_path) {
+                    Ok(()) => format!("Saved the tags of '{}'.", title(recipe)),
+                    Err(error) => format!("Saving failed: {}", error),
+                };
+            }
+            Effect::Delete(index) => {
+                let (location, recipe) = &app.recipes[index];
+                app.status = match crate::delete_saved_recipe(location, db_path) {
+                    Ok(()) => {
+                        let status = format!("Deleted '{}'.", title(recipe));
+                        app.remove(index);
+                        status
+                    }
+                    Err(error) => format!("Deleting failed: {}", error),
+                };
+            }
+        }
+    }
+}
+
+fn title(recipe: &Recipe) -> &str {
+    recipe.title.as_deref().unwrap_or("(untitled)")
+}
+
+impl App {
+    fn new(mut recipes: Vec<(String, Recipe)>) -> Self {
+        recipes.sort_by_cached_key(|(_, recipe)| title(recipe).to_lowercase());
+        let mut app = App {
+            recipes,
+            query: String::new(),
+            visible: Vec::new(),
+            list: ListState::default(),
+            mode: Mode::Browse,
+            detail_scroll: 0,
+            status: String::new(),
+        };
+        app.apply_query();
+        app
+    }
+
+    /// Recomputes the visible recipes after the query or the library changed, keeping a selection when any match.
+    fn apply_query(&mut self) {
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let mut scored: Vec<(i64, usize)> = self
+            .recipes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (_, recipe))| {
+                if self.query.is_empty() {
+                    return Some((0, index));
+                }
+                let domain = library::source_domain(&recipe.source_url).unwrap_or_default();
+                let haystack = format!("{} {} {}", title(recipe), recipe.tags.join(" "), domain);
+                matcher.fuzzy_match(&haystack, &self.query).map(|score| (score, index))
+            })
+            .collect();
+        scored.sort_by_key(|&(score, index)| (std::cmp::Reverse(score), index));
+        self.visible = scored.into_iter().map(|(_, index)| index).collect();
+        let selected = self.list.selected().unwrap_or(0).min(self.visible.len().saturating_sub(1));
+        self.list.select(if self.visible.is_empty() { None } else { Some(selected) });
+        self.detail_scroll = 0;
+    }
+
+    /// The index in [`App::recipes`] of the selected recipe.
+    fn selected(&self) -> Option<usize> {
+        self.list.selected().and_then(|position| self.visible.get(position).copied())
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.recipes.remove(index);
+        self.apply_query();
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Effect {
+        self.status.clear();
+        match &mut self.mode {
+            Mode::Search => match key.code {
+                KeyCode::Enter | KeyCode::Down => self.mode = Mode::Browse,
+                KeyCode::Esc => {
+                    self.query.clear();
+                    self.mode = Mode::Browse;
+                    self.apply_query();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.apply_query();
+                }
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.apply_query();
+                }
+                _ => {}
+            },
+            Mode::Tags(input) => match key.code {
+                KeyCode::Enter => {
+                    let mut tags: Vec<String> = Vec::new();
+                    for tag in input.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+                        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                            tags.push(tag.to_string());
+                        }
+                    }
+                    tags.sort();
+                    self.mode = Mode::Browse;
+                    if let Some(index) = self.selected() {
+                        self.recipes[index].1.tags = tags;
+                        return Effect::Save(index);
+                    }
+                }
+                KeyCode::Esc => self.mode = Mode::Browse,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            },
+            Mode::ConfirmDelete => {
+                self.mode = Mode::Browse;
+                if key.code == KeyCode::Char('y')
+                    && let Some(index) = self.selected()
+                {
+                    return Effect::Delete(index);
+                }
+                self.status = "Not deleted.".to_string();
+            }
+            Mode::Browse => match key.code {
+                KeyCode::Char('q') => return Effect::Quit,
+                KeyCode::Esc if self.query.is_empty() => return Effect::Quit,
+                KeyCode::Esc => {
+                    self.query.clear();
+                    self.apply_query();
+                }
+                KeyCode::Char('/') => self.mode = Mode::Search,
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+                KeyCode::PageDown => self.detail_scroll = self.detail_scroll.saturating_add(10),
+                KeyCode::PageUp => self.detail_scroll = self.detail_scroll.saturating_sub(10),
+                KeyCode::Char('t') => {
+                    if let Some(index) = self.selected() {
+                        self.mode = Mode::Tags(self.recipes[index].1.tags.join(", "));
+                    }
+                }
+                KeyCode::Char('d') if self.selected().is_some() => self.mode = Mode::ConfirmDelete,
+                _ => {}
+            },
+        }
+        Effect::None
+    }
+
+    fn move_selection(&mut self, by: isize) {
+        if let Some(selected) = self.list.selected() {
+            let selected = selected.saturating_add_signed(by).min(self.visible.len().saturating_sub(1));
+            self.list.select(Some(selected));
+            self.detail_scroll = 0;
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search, main, help] = Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list, detail] = Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(main);
+
+        let cursor = if self.mode == Mode::Search { "█" } else { "" };
+        let count = format!("  {}/{}", self.visible.len(), self.recipes.len());
+        frame.render_widget(Line::from(vec!["Search: ".bold(), Span::raw(format!("{}{}", self.query, cursor)), count.dim()]), search);
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&index| {
+                let recipe = &self.recipes[index].1;
+                ListItem::new(format!("{}{}", if recipe.favorite { "★ " } else { "" }, title(recipe)))
+            })
+            .collect();
+        let recipes = List::new(items).block(Block::bordered().title(" Recipes ")).highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(recipes, list, &mut self.list);
+
+        let lines = self.selected().map(|index| detail_lines(&self.recipes[index].1)).unwrap_or_default();
+        let details = Paragraph::new(lines).block(Block::bordered()).wrap(Wrap { trim: false }).scroll((self.detail_scroll, 0));
+        frame.render_widget(details, detail);
+
+        let help_line = match &self.mode {
+            Mode::Browse if !self.status.is_empty() => Line::from(self.status.as_str()),
+            Mode::Browse => "↑↓ move  / search  t tags  d delete  PgUp/PgDn scroll  q quit".dim().into(),
+            Mode::Search => "Type to filter, Enter to browse the matches, Esc to clear".dim().into(),
+            Mode::Tags(input) => Line::from(vec!["Tags (comma-separated): ".bold(), Span::raw(format!("{}█", input)), "  Enter save, Esc cancel".dim()]),
+            Mode::ConfirmDelete => {
+                let name = self.selected().map(|index| title(&self.recipes[index].1)).unwrap_or_default();
+                Line::from(format!("Delete '{}'? y/n", name)).bold()
+            }
+        };
+        frame.render_widget(help_line, help);
+    }
+}
+
+/// The contents of the detail pane for a recipe.
+fn detail_lines(recipe: &Recipe) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(title(recipe).to_string()).bold()];
+    let mut about = vec![library::source_domain(&recipe.source_url).unwrap_or_default()];
+    about.extend(recipe.scraped_at.as_deref().map(|date| date.chars().take(10).collect()));
+    about.extend(recipe.total_time.map(|minutes| format!("{} min", minutes)));
+    lines.push(Line::from(about.join(" · ")).dim());
+    if !recipe.tags.is_empty() {
+        lines.push(Line::from(format!("Tags: {}", recipe.tags.join(", "))));
+    }
+    if let Some(description) = &recipe.description {
+        lines.extend([Line::default(), Line::from(description.clone())]);
+    }
+    lines.extend([Line::default(), Line::from("Ingredients").bold()]);
+    lines.extend(recipe.ingredients.iter().flatten().map(|ingredient| Line::from(format!("• {}", ingredient))));
+    lines.extend([Line::default(), Line::from("Steps").bold()]);
+    lines.extend(recipe.steps.iter().flatten().enumerate().map(|(number, step)| Line::from(format!("{}. {}", number + 1, step))));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let recipe = |title: &str, tags: &[&str]| Recipe {
+            title: Some(title.to_string()),
+            source_url: "https://15gram.be/recepten/x".to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        };
+        App::new(vec![
+            ("#1".to_string(), recipe("Pompoensoep", &["soep"])),
+            ("#2".to_string(), recipe("Lasagne met spinazie", &["pasta"])),
+            ("#3".to_string(), recipe("Courgettesoep", &[])),
+        ])
+    }
+
+    fn press(app: &mut App, keys: &str) -> Effect {
+        keys.chars().map(|c| app.handle_key(KeyEvent::from(KeyCode::Char(c)))).last().unwrap_or(Effect::None)
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let mut app = app();
+        assert_eq!(app.visible, vec![0, 1, 2]);
+        assert_eq!(app.recipes[0].1.title.as_deref(), Some("Courgettesoep"));
+
+        press(&mut app, "/psta");
+        assert_eq!(app.visible.iter().map(|&index| &app.recipes[index].0).collect::<Vec<_>>(), vec!["#2"]);
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert_eq!((app.visible.len(), &app.mode), (3, &Mode::Browse));
+
+        press(&mut app, "/xyz");
+        assert_eq!(app.selected(), None);
+        assert_eq!(press(&mut app, "d"), Effect::None);
+    }
+
+    #[test]
+    fn test_draw_detail_pane() {
+        let mut app = app();
+        app.recipes[0].1.ingredients = Some(vec!["2 courgettes".to_string()]);
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Search:   3/3"));
+        assert!(screen.contains("• 2 courgettes"));
+        assert!(screen.contains("15gram.be"));
+    }
+
+    #[test]
+    fn test_tag_and_delete() {
+        let mut app = app();
+        press(&mut app, "j");
+        assert_eq!(app.selected(), Some(1));
+        press(&mut app, "t");
+        assert_eq!(app.mode, Mode::Tags("pasta".to_string()));
+        press(&mut app, ", Weeknight,pasta,");
+        assert_eq!(app.handle_key(KeyEvent::from(KeyCode::Enter)), Effect::Save(1));
+        assert_eq!(app.recipes[1].1.tags, vec!["Weeknight", "pasta"]);
+
+        press(&mut app, "dn");
+        assert_eq!(app.recipes.len(), 3);
+        assert_eq!(press(&mut app, "dy"), Effect::Delete(1));
+        app.remove(1);
+        assert_eq!(app.visible, vec![0, 1]);
+        assert_eq!(app.selected(), Some(1));
+    }
+}
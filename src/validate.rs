@@ -0,0 +1,134 @@
+//! `validate`: checking that the selectors still work on the live sites.
+//!
+//! Each site lists a few recipe pages in `test_urls`; they are scraped and every field the site is expected to
+//! fill is checked:
+//!
+//! ```toml
+//! [15gram]
+//! test_urls = ["https://15gram.be/recepten/courgettesoep"]
+//! ```
+
+use foodscraper::{Recipe, RecipeCssSelectors};
+
+/// The result of one field on one test page.
+#[derive(Debug, PartialEq)]
+pub struct FieldCheck {
+    pub field: &'static str,
+    pub passed: bool,
+}
+
+/// The test pages of one site.
+#[derive(Debug, PartialEq)]
+pub struct SiteTests {
+    /// The site's key in the selectors file.
+    pub site: String,
+    pub urls: Vec<String>,
+}
+
+/// The test URLs of the sites in the selectors file, or of only `site` when given (by key or by domain).
+pub fn test_urls(selectors: &str, site: Option<&str>) -> Result<Vec<SiteTests>, Box<dyn std::error::Error>> {
+    let sites: toml::Table = toml::from_str(selectors)?;
+    let names: Vec<String> = match site {
+        Some(site) => vec![foodscraper::site_key(selectors, site).ok_or_else(|| format!("Unknown site '{}'.", site))?],
+        None => foodscraper_core::schema::site_names(&sites).cloned().collect(),
+    };
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let urls = sites[&name].get("test_urls").and_then(toml::Value::as_array).into_iter().flatten();
+            let urls = urls.filter_map(toml::Value::as_str).map(str::to_string).collect();
+            SiteTests { site: name, urls }
+        })
+        .collect())
+}
+
+/// Checks the fields of a recipe scraped with the site's selectors. The total time is only expected when the site
+/// has a selector for it or reads structured data.
+pub fn check_fields(recipe: &Recipe, selectors: &RecipeCssSelectors) -> Vec<FieldCheck> {
+    let filled = |value: Option<&String>| value.is_some_and(|value| !value.trim().is_empty());
+    let listed = |values: Option<&Vec<String>>| values.is_some_and(|values| !values.is_empty());
+    let mut checks = vec![
+        FieldCheck { field: "title", passed: filled(recipe.title.as_ref()) },
+        FieldCheck { field: "description", passed: filled(recipe.description.as_ref()) },
+        FieldCheck { field: "ingredients", passed: listed(recipe.ingredients.as_ref()) },
+        FieldCheck { field: "steps", passed: listed(recipe.steps.as_ref()) },
+        FieldCheck { field: "image", passed: filled(recipe.image_link.as_ref()) },
+    ];
+    if !selectors.total_time.candidates.is_empty() || selectors.structured_data {
+        checks.push(FieldCheck { field: "total_time", passed: recipe.total_time.is_some() });
+    }
+    checks
+}
+
+/// Scrapes the test URLs of the sites and prints a pass/fail line per field, failing when any check failed.
+pub async fn validate_sites(client: &reqwest::Client, selectors: &str, site: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut checked, mut failed) = (0, 0);
+    for SiteTests { site: name, urls } in test_urls(selectors, site)? {
+        if urls.is_empty() {
+            println!("{}: no test_urls configured, skipped.", name);
+            continue;
+        }
+        let site_selectors = RecipeCssSelectors::from_toml(selectors, &name)?;
+        for url in urls {
+            println!("{} {}", name, url);
+            checked += 1;
+            match foodscraper::scrape_page_with_selectors(client, &url, selectors).await {
+                Ok((recipe, _)) => {
+                    let checks = check_fields(&recipe, &site_selectors);
+                    for check in &checks {
+                        println!("  {:<12} {}", check.field, if check.passed { "pass" } else { "FAIL" });
+                    }
+                    if checks.iter().any(|check| !check.passed) {
+                        failed += 1;
+                    }
+                }
+                Err(error) => {
+                    println!("  could not scrape: {}", error);
+                    failed += 1;
+                }
+            }
+        }
+    }
+    match failed {
+        0 => {
+            println!("{} test pages passed.", checked);
+            Ok(())
+        }
+        _ => Err(format!("{} of {} test pages failed.", failed, checked).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SELECTORS: &str = r#"
+        [15gram]
+        domain = "15gram.be"
+        title = "h1"
+        test_urls = ["https://15gram.be/recepten/courgettesoep"]
+
+        [njam]
+        domain = "njam.tv"
+        total_time = ".duration"
+    "#;
+
+    #[test]
+    fn test_test_urls() {
+        let all = test_urls(SELECTORS, None).unwrap();
+        assert_eq!(all[0], SiteTests { site: "15gram".to_string(), urls: vec!["https://15gram.be/recepten/courgettesoep".to_string()] });
+        assert_eq!(all[1], SiteTests { site: "njam".to_string(), urls: vec![] });
+        assert_eq!(test_urls(SELECTORS, Some("www.njam.tv")).unwrap()[0].site, "njam");
+        assert!(test_urls(SELECTORS, Some("example.com")).is_err());
+    }
+
+    #[test]
+    fn test_check_fields() {
+        let recipe = Recipe { title: Some("Soep".to_string()), ingredients: Some(vec![]), total_time: Some(20), ..Default::default() };
+        let checks = check_fields(&recipe, &RecipeCssSelectors::from_toml(SELECTORS, "15gram").unwrap());
+        assert_eq!(checks.len(), 5);
+        assert_eq!(checks.iter().filter(|check| check.passed).map(|check| check.field).collect::<Vec<_>>(), vec!["title"]);
+        let checks = check_fields(&recipe, &RecipeCssSelectors::from_toml(SELECTORS, "njam").unwrap());
+        assert_eq!(checks.last(), Some(&FieldCheck { field: "total_time", passed: true }));
+    }
+}
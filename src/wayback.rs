@@ -0,0 +1,65 @@
+//! Finding Internet Archive snapshots of recipe pages that are gone.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::Value;
+
+/// The Wayback Machine availability API; without a `timestamp` it answers with the most recent snapshot.
+const AVAILABILITY_API: &str = "https://archive.org/wayback/available";
+
+/// A snapshot of a page in the Wayback Machine.
+#[derive(Debug, PartialEq)]
+pub struct Snapshot {
+    /// The snapshot's page on web.archive.org.
+    pub url: String,
+    /// The archived URL.
+    pub original: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Snapshot {
+    /// The snapshot as originally served, without the Wayback Machine's toolbar and rewritten links.
+    pub fn raw_url(&self) -> String {
+        format!("https://web.archive.org/web/{}id_/{}", self.timestamp.format("%Y%m%d%H%M%S"), self.original)
+    }
+}
+
+/// Looks up the most recent successful snapshot of the URL.
+pub async fn latest_snapshot(client: &reqwest::Client, url: &str) -> Result<Option<Snapshot>, Box<dyn std::error::Error>> {
+    let response: Value = client.get(AVAILABILITY_API).query(&[("url", url)]).send().await?.error_for_status()?.json().await?;
+    Ok(parse_availability(&response, url))
+}
+
+/// Reads the closest snapshot from an availability API response, if it is available and was archived with `200 OK`.
+fn parse_availability(response: &Value, original: &str) -> Option<Snapshot> {
+    let closest = response.get("archived_snapshots")?.get("closest")?;
+    if closest.get("available").and_then(Value::as_bool) != Some(true)
+        || closest.get("status").and_then(Value::as_str).is_some_and(|status| status != "200")
+    {
+        return None;
+    }
+    let timestamp = NaiveDateTime::parse_from_str(closest.get("timestamp")?.as_str()?, "%Y%m%d%H%M%S").ok()?.and_utc();
+    let url = closest.get("url")?.as_str()?.replacen("http://", "https://", 1);
+    Some(Snapshot { url, original: original.to_string(), timestamp })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_availability() {
+        let response = json!({"url": "15gram.be/recepten/soep", "archived_snapshots": {"closest": {
+            "status": "200",
+            "available": true,
+            "url": "http://web.archive.org/web/20240102030405/https://15gram.be/recepten/soep",
+            "timestamp": "20240102030405"
+        }}});
+        let snapshot = parse_availability(&response, "https://15gram.be/recepten/soep").unwrap();
+        assert_eq!(snapshot.url, "https://web.archive.org/web/20240102030405/https://15gram.be/recepten/soep");
+        assert_eq!(snapshot.timestamp.to_rfc3339(), "2024-01-02T03:04:05+00:00");
+        assert_eq!(snapshot.raw_url(), "https://web.archive.org/web/20240102030405id_/https://15gram.be/recepten/soep");
+
+        assert_eq!(parse_availability(&json!({"archived_snapshots": {}}), "https://15gram.be/recepten/soep"), None);
+    }
+}
@@ -0,0 +1,30 @@
+//! Checks the extraction of every fixture in `tests/fixtures` against its snapshot.
+//!
+//! After an intended change to the extraction, `FOODSCRAPER_UPDATE_SNAPSHOTS=1 cargo test --test fixtures` rewrites
+//! the snapshots from the saved pages; review the diff of the `expected.json` files before committing it.
+
+use foodscraper::snapshot::{FIXTURES_DIR, check_fixture, record_fixture};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture_dirs() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURES_DIR);
+    let mut dirs: Vec<_> = fs::read_dir(root).unwrap().map(|entry| entry.unwrap().path()).filter(|path| path.is_dir()).collect();
+    dirs.sort();
+    dirs
+}
+
+#[test]
+fn test_fixtures_match_snapshots() {
+    let dirs = fixture_dirs();
+    assert!(!dirs.is_empty());
+    if std::env::var_os("FOODSCRAPER_UPDATE_SNAPSHOTS").is_some() {
+        for dir in &dirs {
+            let expected: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join("expected.json")).unwrap()).unwrap();
+            let html = fs::read_to_string(dir.join("page.html")).unwrap();
+            record_fixture(dir.parent().unwrap(), expected["source_url"].as_str().unwrap(), &html).unwrap();
+        }
+    }
+    let failures: Vec<String> = dirs.iter().filter_map(|dir| check_fixture(dir).err()).map(|e| e.to_string()).collect();
+    assert!(failures.is_empty(), "Extraction differs from the snapshots:\n{}", failures.join("\n"));
+}